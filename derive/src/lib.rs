@@ -0,0 +1,272 @@
+//! Derive macros for `wgpu_experiments`. Use them through the re-exports there, e.g.
+//! `wgpu_experiments::wgsl::WgslLayout`, since the generated code refers to that crate.
+
+use proc_macro2::TokenStream;
+use quote::{quote, quote_spanned};
+use syn::spanned::Spanned;
+
+/// See `wgpu_experiments::wgsl::WgslLayout`.
+#[proc_macro_derive(WgslLayout, attributes(wgsl))]
+pub fn derive_wgsl_layout(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = syn::parse_macro_input!(input as syn::DeriveInput);
+    wgsl_layout(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// See `wgpu_experiments::vertex::VertexLayout`.
+#[proc_macro_derive(VertexLayout, attributes(vertex))]
+pub fn derive_vertex_layout(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = syn::parse_macro_input!(input as syn::DeriveInput);
+    vertex_layout(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// `@align` and `@size` from a field's `#[wgsl(align = N, size = N)]`.
+#[derive(Default)]
+struct FieldAttributes {
+    align: Option<syn::LitInt>,
+    size: Option<syn::LitInt>,
+}
+
+impl FieldAttributes {
+    fn parse(field: &syn::Field) -> syn::Result<Self> {
+        let mut attributes = Self::default();
+        for attr in field
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("wgsl"))
+        {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("align") {
+                    attributes.align = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("size") {
+                    attributes.size = Some(meta.value()?.parse()?);
+                } else {
+                    return Err(meta.error("expected `align` or `size`"));
+                }
+                Ok(())
+            })?;
+        }
+        Ok(attributes)
+    }
+}
+
+/// From a field's `#[vertex(location = N, format = F)]`.
+#[derive(Default)]
+struct VertexFieldAttributes {
+    location: Option<syn::LitInt>,
+    format: Option<syn::Ident>,
+}
+
+impl VertexFieldAttributes {
+    fn parse(field: &syn::Field) -> syn::Result<Self> {
+        let mut attributes = Self::default();
+        for attr in field
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("vertex"))
+        {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("location") {
+                    attributes.location = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("format") {
+                    attributes.format = Some(meta.value()?.parse()?);
+                } else {
+                    return Err(meta.error("expected `location` or `format`"));
+                }
+                Ok(())
+            })?;
+        }
+        Ok(attributes)
+    }
+}
+
+/// From the struct's `#[vertex(step_mode = Instance)]`, `Vertex` by default.
+fn step_mode(input: &syn::DeriveInput) -> syn::Result<syn::Ident> {
+    let mut step_mode = syn::Ident::new("Vertex", proc_macro2::Span::call_site());
+    for attr in input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("vertex"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("step_mode") {
+                step_mode = meta.value()?.parse()?;
+                Ok(())
+            } else {
+                Err(meta.error("expected `step_mode`"))
+            }
+        })?;
+    }
+    Ok(step_mode)
+}
+
+/// Offsets are only meaningful on the GPU if the layout is C's.
+fn check_repr_c(input: &syn::DeriveInput) -> syn::Result<()> {
+    let mut repr_c = false;
+    for attr in input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("repr"))
+    {
+        attr.parse_nested_meta(|meta| {
+            repr_c |= meta.path.is_ident("C");
+            Ok(())
+        })?;
+    }
+    if repr_c {
+        Ok(())
+    } else {
+        Err(syn::Error::new(
+            input.ident.span(),
+            "vertex structs must be #[repr(C)]",
+        ))
+    }
+}
+
+/// Named fields of a non-generic struct, which is all WGSL has.
+fn named_fields(input: &syn::DeriveInput) -> syn::Result<Vec<&syn::Field>> {
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new(
+            input.generics.span(),
+            "generic structs aren't supported",
+        ));
+    }
+    let syn::Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new(input.span(), "only structs are supported"));
+    };
+    let syn::Fields::Named(fields) = &data.fields else {
+        return Err(syn::Error::new(
+            data.fields.span(),
+            "only structs with named fields are supported",
+        ));
+    };
+    if fields.named.is_empty() {
+        return Err(syn::Error::new(
+            fields.span(),
+            "WGSL structs need at least one member",
+        ));
+    }
+    Ok(fields.named.iter().collect())
+}
+
+fn wgsl_layout(input: &syn::DeriveInput) -> syn::Result<TokenStream> {
+    let name = &input.ident;
+    let fields = named_fields(input)?;
+    let mut members = Vec::new();
+    let mut writes = Vec::new();
+    let mut declarations = Vec::new();
+    for (i, field) in fields.iter().enumerate() {
+        let ident = field.ident.as_ref().unwrap();
+        let ty = &field.ty;
+        let attributes = FieldAttributes::parse(field)?;
+        let layout = quote_spanned!(ty.span()=> <#ty as ::wgpu_experiments::wgsl::WgslLayout>);
+        let align = match &attributes.align {
+            Some(align) => quote!(#align),
+            None => quote!(#layout::ALIGN),
+        };
+        let size = match &attributes.size {
+            Some(size) => quote!(#size),
+            None => quote!(#layout::SIZE),
+        };
+        let member_name = ident.to_string();
+        let mut declared_name = String::new();
+        if let Some(align) = &attributes.align {
+            declared_name += &format!("@align({}) ", align.base10_digits());
+        }
+        if let Some(size) = &attributes.size {
+            declared_name += &format!("@size({}) ", size.base10_digits());
+        }
+        declared_name += &member_name;
+        declarations.push(quote! {
+            (#declared_name, #layout::wgsl_type())
+        });
+        members.push(quote! {
+            ::wgpu_experiments::wgsl::MemberLayout {
+                name: #member_name,
+                type_align: #layout::ALIGN,
+                type_size: #layout::SIZE,
+                align: #align,
+                size: #size,
+            }
+        });
+        writes.push(quote! {
+            ::wgpu_experiments::wgsl::pad(bytes, start + Self::MEMBERS[#i].offset as usize);
+            ::wgpu_experiments::wgsl::WgslLayout::write_to(&self.#ident, bytes);
+        });
+    }
+    let types = fields.iter().map(|field| &field.ty);
+    let wgsl_name = name.to_string();
+    Ok(quote! {
+        impl ::wgpu_experiments::wgsl::WgslLayout for #name {
+            const ALIGN: u64 = ::wgpu_experiments::wgsl::struct_align(Self::MEMBERS);
+            const SIZE: u64 = ::wgpu_experiments::wgsl::struct_size(Self::MEMBERS);
+            const MEMBERS: &'static [::wgpu_experiments::wgsl::Member] =
+                &::wgpu_experiments::wgsl::struct_members([#(#members),*]);
+
+            fn wgsl_type() -> ::std::string::String {
+                #wgsl_name.to_string()
+            }
+
+            fn wgsl_structs(declarations: &mut ::std::vec::Vec<::std::string::String>) {
+                #(<#types as ::wgpu_experiments::wgsl::WgslLayout>::wgsl_structs(declarations);)*
+                let declaration = ::wgpu_experiments::wgsl::struct_declaration(
+                    #wgsl_name,
+                    &[#(#declarations),*],
+                );
+                ::wgpu_experiments::wgsl::push_declaration(declarations, declaration);
+            }
+
+            fn write_to(&self, bytes: &mut ::std::vec::Vec<u8>) {
+                let start = bytes.len();
+                #(#writes)*
+                ::wgpu_experiments::wgsl::pad(bytes, start + Self::SIZE as usize);
+            }
+        }
+    })
+}
+
+fn vertex_layout(input: &syn::DeriveInput) -> syn::Result<TokenStream> {
+    let name = &input.ident;
+    let fields = named_fields(input)?;
+    check_repr_c(input)?;
+    let step_mode = step_mode(input)?;
+    let mut attributes = Vec::new();
+    let mut next_location = 0;
+    for field in fields {
+        let ident = field.ident.as_ref().unwrap();
+        let ty = &field.ty;
+        let field_attributes = VertexFieldAttributes::parse(field)?;
+        let location = match &field_attributes.location {
+            Some(location) => location.base10_parse::<u32>()?,
+            None => next_location,
+        };
+        next_location = location + 1;
+        let format = match &field_attributes.format {
+            Some(format) => quote!(::wgpu::VertexFormat::#format),
+            None => quote_spanned! {ty.span()=>
+                <#ty as ::wgpu_experiments::vertex::AttributeFormat>::FORMAT
+            },
+        };
+        attributes.push(quote! {
+            ::wgpu_experiments::vertex::attribute(
+                #format,
+                ::std::mem::offset_of!(#name, #ident),
+                #location,
+                ::std::mem::size_of::<#ty>(),
+            )
+        });
+    }
+    Ok(quote! {
+        impl ::wgpu_experiments::vertex::VertexLayout for #name {
+            const ATTRIBUTES: &'static [::wgpu::VertexAttribute] = &[#(#attributes),*];
+            const LAYOUT: ::wgpu::VertexBufferLayout<'static> = ::wgpu::VertexBufferLayout {
+                array_stride: ::std::mem::size_of::<Self>() as u64,
+                step_mode: ::wgpu::VertexStepMode::#step_mode,
+                attributes: Self::ATTRIBUTES,
+            };
+        }
+    })
+}
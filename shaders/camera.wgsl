@@ -0,0 +1,31 @@
+// CameraUniform is declared by the Rust code, see camera::shader_prelude in src/demo.rs.
+
+@group(0) @binding(0) var<uniform> camera: CameraUniform;
+
+// Between tiles, in world units
+const GAP: f32 = 0.1;
+
+struct TileIn {
+    // A corner of a unit square
+    @location(0) corner: vec2f,
+    // Bottom left, in world units
+    @location(1) position: vec2f,
+    @location(2) color: vec3f,
+};
+
+struct VSOut {
+    @builtin(position) position: vec4f,
+    @location(0) color: vec3f,
+};
+
+@vertex
+fn vertex_main(tile: TileIn) -> VSOut {
+    // The square, shrunk around the tile's center to leave a gap between tiles
+    let world = tile.position + GAP / 2.0 + tile.corner * (1.0 - GAP);
+    return VSOut(camera.view_projection * vec4f(world, 0.0, 1.0), tile.color);
+}
+
+@fragment
+fn fragment_main(@location(0) color: vec3f) -> @location(0) vec4f {
+    return vec4f(color, 1.0);
+}
@@ -0,0 +1,27 @@
+// Draws a mip level by averaging 2x2 pixels of the level before it, see generate_mipmaps in
+// src/texture.rs.
+
+@group(0) @binding(0) var mipmapped: texture_2d<f32>;
+// The level to average, one less than the level being drawn
+@group(0) @binding(1) var<uniform> source_level: u32;
+
+// One triangle covering the whole viewport, so no vertex buffer is needed.
+@vertex
+fn vertex_main(@builtin(vertex_index) vertex_index: u32) -> @builtin(position) vec4f {
+    let uv = vec2f(f32((vertex_index << 1u) & 2u), f32(vertex_index & 2u));
+    return vec4f(uv * vec2f(2.0, -2.0) + vec2f(-1.0, 1.0), 0.0, 1.0);
+}
+
+@fragment
+fn fragment_main(@builtin(position) position: vec4f) -> @location(0) vec4f {
+    let level = i32(source_level);
+    // Odd sizes round down, so the last row or column can be past the edge.
+    let last = vec2i(textureDimensions(mipmapped, level)) - 1;
+    let top_left = vec2i(position.xy) * 2;
+    // Loaded texels are linear even in sRGB textures, so this averages light, not sRGB values.
+    let sum = textureLoad(mipmapped, top_left, level)
+        + textureLoad(mipmapped, min(top_left + vec2i(1, 0), last), level)
+        + textureLoad(mipmapped, min(top_left + vec2i(0, 1), last), level)
+        + textureLoad(mipmapped, min(top_left + vec2i(1, 1), last), level);
+    return sum / 4.0;
+}
@@ -0,0 +1,32 @@
+struct Viewport {
+    // In pixels
+    size: vec2f,
+};
+
+@group(0) @binding(0) var<uniform> viewport: Viewport;
+
+struct RectIn {
+    // A corner of the unit square, 0.0 to 1.0
+    @location(0) corner: vec2f,
+    // Top left, in pixels, y down
+    @location(1) position: vec2f,
+    @location(2) size: vec2f,
+    @location(3) color: vec4f,
+};
+
+struct VSOut {
+    @builtin(position) position: vec4f,
+    @location(0) color: vec4f,
+};
+
+@vertex
+fn vertex_main(rect: RectIn) -> VSOut {
+    let pixel = rect.position + rect.corner * rect.size;
+    let clip = pixel / viewport.size * vec2f(2.0, -2.0) + vec2f(-1.0, 1.0);
+    return VSOut(vec4f(clip, 0.0, 1.0), rect.color);
+}
+
+@fragment
+fn fragment_main(@location(0) color: vec4f) -> @location(0) vec4f {
+    return color;
+}
@@ -0,0 +1,43 @@
+// CameraUniform is declared by the Rust code, see SpriteBatch::shader_prelude in src/sprite.rs.
+
+@group(0) @binding(0) var<uniform> camera: CameraUniform;
+
+@group(1) @binding(0) var sprite_texture: texture_2d<f32>;
+@group(1) @binding(1) var sprite_sampler: sampler;
+
+struct SpriteIn {
+    // A corner of the unit square, 0.0 to 1.0, y up
+    @location(0) corner: vec2f,
+    // Where the origin goes, in world units
+    @location(1) position: vec2f,
+    @location(2) size: vec2f,
+    // The point rotated around, as a fraction of the size
+    @location(3) origin: vec2f,
+    // The cosine and sine of the counter-clockwise rotation
+    @location(4) rotation: vec2f,
+    // The top left then the bottom right in UV coordinates, swapped to flip
+    @location(5) uv_rect: vec4f,
+    @location(6) tint: vec4f,
+};
+
+struct VSOut {
+    @builtin(position) position: vec4f,
+    @location(0) uv: vec2f,
+    @location(1) tint: vec4f,
+};
+
+@vertex
+fn vertex_main(sprite: SpriteIn) -> VSOut {
+    let local = (sprite.corner - sprite.origin) * sprite.size;
+    let c = sprite.rotation.x;
+    let s = sprite.rotation.y;
+    let world = sprite.position + vec2f(local.x * c - local.y * s, local.x * s + local.y * c);
+    // UVs have y down, so the bottom corners take the bottom of the rect.
+    let uv = mix(sprite.uv_rect.xw, sprite.uv_rect.zy, sprite.corner);
+    return VSOut(camera.view_projection * vec4f(world, 0.0, 1.0), uv, sprite.tint);
+}
+
+@fragment
+fn fragment_main(vertex: VSOut) -> @location(0) vec4f {
+    return textureSample(sprite_texture, sprite_sampler, vertex.uv) * vertex.tint;
+}
@@ -1,8 +1,4 @@
-struct OurStruct {
-    color: vec3f,
-    scale: vec2f,
-    offset: vec2f,
-};
+// OurStruct is declared by the Rust code, see shader_prelude in src/demo.rs.
 
 struct VSOut {
     @builtin(position) position: vec4f,
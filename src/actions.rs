@@ -0,0 +1,304 @@
+//! Named actions and axes, bound to keys and mouse buttons by a bindings file.
+//!
+//! Game code asks "is `fire` active?" rather than "is Space down?",
+//! so controls can be changed without touching the code.
+//!
+//! A bindings file looks like this:
+//!
+//! ```ron
+//! (
+//!     actions: {
+//!         "fire": ["Space", "MouseLeft"],
+//!         "save": ["Ctrl+S"],
+//!     },
+//!     axes: {
+//!         "move_x": [(negative: "A", positive: "D"), (negative: "Left", positive: "Right")],
+//!     },
+//! )
+//! ```
+//!
+//! Keys are named after `winit::event::VirtualKeyCode` variants.
+//! Mouse buttons are `MouseLeft`, `MouseRight`, `MouseMiddle` or `Mouse<N>`.
+//! Modifiers are `Ctrl`, `Shift`, `Alt` and `Logo`, joined to the button with `+`.
+
+use crate::input::{Button, InputState};
+use serde::de::IntoDeserializer as _;
+use serde::Deserialize as _;
+
+/// A button, plus the modifiers that must be held with it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Binding {
+    pub button: Button,
+    pub modifiers: winit::event::ModifiersState,
+}
+
+impl Binding {
+    fn modifiers_held(&self, held: winit::event::ModifiersState) -> bool {
+        held.contains(self.modifiers)
+    }
+}
+
+const MODIFIER_NAMES: &[(&str, winit::event::ModifiersState)] = &[
+    ("Ctrl", winit::event::ModifiersState::CTRL),
+    ("Shift", winit::event::ModifiersState::SHIFT),
+    ("Alt", winit::event::ModifiersState::ALT),
+    ("Logo", winit::event::ModifiersState::LOGO),
+];
+
+impl std::str::FromStr for Binding {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts: Vec<&str> = s.split('+').map(str::trim).collect();
+        let button_name = parts.pop().unwrap();
+        let mut modifiers = winit::event::ModifiersState::empty();
+        for modifier_name in parts {
+            let (_, modifier) = MODIFIER_NAMES
+                .iter()
+                .find(|(name, _)| *name == modifier_name)
+                .ok_or_else(|| format!("unknown modifier `{}` in `{}`", modifier_name, s))?;
+            modifiers |= *modifier;
+        }
+        let button = parse_button(button_name)
+            .ok_or_else(|| format!("unknown key or mouse button `{}` in `{}`", button_name, s))?;
+        Ok(Binding { button, modifiers })
+    }
+}
+
+fn parse_button(name: &str) -> Option<Button> {
+    if let Some(mouse_name) = name.strip_prefix("Mouse") {
+        return match mouse_name {
+            "Left" => Some(winit::event::MouseButton::Left.into()),
+            "Right" => Some(winit::event::MouseButton::Right.into()),
+            "Middle" => Some(winit::event::MouseButton::Middle.into()),
+            n => n
+                .parse()
+                .ok()
+                .map(|n| winit::event::MouseButton::Other(n).into()),
+        };
+    }
+    let deserializer: serde::de::value::StrDeserializer<serde::de::value::Error> =
+        name.into_deserializer();
+    winit::event::VirtualKeyCode::deserialize(deserializer)
+        .ok()
+        .map(Button::Key)
+}
+
+impl std::fmt::Display for Binding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (name, modifier) in MODIFIER_NAMES {
+            if self.modifiers.contains(*modifier) {
+                write!(f, "{}+", name)?;
+            }
+        }
+        match self.button {
+            Button::Key(key) => write!(f, "{:?}", key),
+            Button::Mouse(winit::event::MouseButton::Other(n)) => write!(f, "Mouse{}", n),
+            Button::Mouse(button) => write!(f, "Mouse{:?}", button),
+        }
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Binding {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Deserialize)]
+pub struct AxisBinding {
+    pub negative: Binding,
+    pub positive: Binding,
+}
+
+/// The contents of a bindings file.
+#[derive(Debug, Default, Clone, PartialEq, serde::Deserialize)]
+pub struct Bindings {
+    #[serde(default)]
+    pub actions: std::collections::BTreeMap<String, Vec<Binding>>,
+    #[serde(default)]
+    pub axes: std::collections::BTreeMap<String, Vec<AxisBinding>>,
+}
+
+impl std::str::FromStr for Bindings {
+    type Err = BindingsError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(ron::from_str(s)?)
+    }
+}
+
+#[derive(Debug)]
+pub enum BindingsError {
+    Io(std::io::Error),
+    Parse(ron::error::SpannedError),
+}
+
+impl std::fmt::Display for BindingsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BindingsError::Io(e) => write!(f, "could not read bindings file: {}", e),
+            BindingsError::Parse(e) => write!(f, "could not parse bindings file: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for BindingsError {}
+
+impl From<std::io::Error> for BindingsError {
+    fn from(e: std::io::Error) -> Self {
+        BindingsError::Io(e)
+    }
+}
+
+impl From<ron::error::SpannedError> for BindingsError {
+    fn from(e: ron::error::SpannedError) -> Self {
+        BindingsError::Parse(e)
+    }
+}
+
+/// One binding used by more than one action or axis direction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Conflict {
+    pub binding: Binding,
+    /// Action names, or axis names suffixed with `-` or `+`.
+    pub names: Vec<String>,
+}
+
+impl std::fmt::Display for Conflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} is bound to {}", self.binding, self.names.join(", "))
+    }
+}
+
+pub struct ActionMap {
+    bindings: Bindings,
+    path: Option<std::path::PathBuf>,
+}
+
+impl ActionMap {
+    pub fn new(bindings: Bindings) -> Self {
+        Self {
+            bindings,
+            path: None,
+        }
+    }
+
+    pub fn load(path: impl Into<std::path::PathBuf>) -> Result<Self, BindingsError> {
+        let path = path.into();
+        let bindings = std::fs::read_to_string(&path)?.parse()?;
+        Ok(Self {
+            bindings,
+            path: Some(path),
+        })
+    }
+
+    /// Re-reads the bindings file this map was loaded from.
+    ///
+    /// On error the current bindings are kept.
+    pub fn reload(&mut self) -> Result<(), BindingsError> {
+        if let Some(path) = &self.path {
+            self.bindings = std::fs::read_to_string(path)?.parse()?;
+        }
+        Ok(())
+    }
+
+    pub fn bindings(&self) -> &Bindings {
+        &self.bindings
+    }
+
+    /// Every binding shared by two or more actions or axis directions.
+    pub fn conflicts(&self) -> Vec<Conflict> {
+        let mut users: std::collections::BTreeMap<String, (Binding, Vec<String>)> =
+            std::collections::BTreeMap::new();
+        let mut add = |binding: &Binding, name: String| {
+            let (_, names) = users
+                .entry(binding.to_string())
+                .or_insert_with(|| (*binding, Vec::new()));
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        };
+        for (action, bindings) in &self.bindings.actions {
+            for binding in bindings {
+                add(binding, action.clone());
+            }
+        }
+        for (axis, axis_bindings) in &self.bindings.axes {
+            for axis_binding in axis_bindings {
+                add(&axis_binding.negative, format!("{}-", axis));
+                add(&axis_binding.positive, format!("{}+", axis));
+            }
+        }
+        users
+            .into_values()
+            .filter(|(_, names)| names.len() > 1)
+            .map(|(binding, names)| Conflict { binding, names })
+            .collect()
+    }
+
+    /// True if the binding's button and modifiers are held.
+    ///
+    /// When several bindings share a button, only the most specific chord that is held counts.
+    /// So holding Ctrl+S activates `Ctrl+S` but not a plain `S`.
+    fn binding_active(&self, input: &InputState, binding: &Binding) -> bool {
+        let held = input.modifiers();
+        if !input.is_down(binding.button) || !binding.modifiers_held(held) {
+            return false;
+        }
+        !self.all_bindings().any(|other| {
+            other.button == binding.button
+                && other.modifiers != binding.modifiers
+                && other.modifiers.contains(binding.modifiers)
+                && other.modifiers_held(held)
+        })
+    }
+
+    fn all_bindings(&self) -> impl Iterator<Item = &Binding> {
+        self.bindings.actions.values().flatten().chain(
+            self.bindings
+                .axes
+                .values()
+                .flatten()
+                .flat_map(|axis| [&axis.negative, &axis.positive]),
+        )
+    }
+
+    fn action_bindings(&self, action: &str) -> &[Binding] {
+        self.bindings
+            .actions
+            .get(action)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    /// True while any of the action's bindings are held. Unknown actions are never active.
+    pub fn is_active(&self, input: &InputState, action: &str) -> bool {
+        self.action_bindings(action)
+            .iter()
+            .any(|binding| self.binding_active(input, binding))
+    }
+
+    /// True if one of the action's bindings was pressed since the last `InputState::end_frame`.
+    pub fn just_activated(&self, input: &InputState, action: &str) -> bool {
+        self.action_bindings(action).iter().any(|binding| {
+            input.just_pressed(binding.button) && self.binding_active(input, binding)
+        })
+    }
+
+    /// The axis value in -1.0..=1.0. Opposite directions held together cancel out.
+    pub fn axis(&self, input: &InputState, axis: &str) -> f32 {
+        let Some(axis_bindings) = self.bindings.axes.get(axis) else {
+            return 0.0;
+        };
+        let negative = axis_bindings
+            .iter()
+            .any(|axis_binding| self.binding_active(input, &axis_binding.negative));
+        let positive = axis_bindings
+            .iter()
+            .any(|axis_binding| self.binding_active(input, &axis_binding.positive));
+        positive as i32 as f32 - negative as i32 as f32
+    }
+}
@@ -0,0 +1,483 @@
+//! Sound effects and music.
+//!
+//! The `Mixer` does all the work and knows nothing about audio devices,
+//! so it can render into a plain buffer in tests.
+//! `Audio` wraps a mixer and an `AudioOutput`, which pulls mixed samples on its own thread.
+//!
+//! Sound only reaches the speakers with the `cpal` feature, see `default_output`. On Linux
+//! it needs ALSA's development files, e.g. the libasound2-dev package.
+
+/// Decoded audio, as interleaved f32 samples in -1.0..=1.0.
+#[derive(Debug, Clone)]
+pub struct Sound {
+    samples: std::sync::Arc<[f32]>,
+    channels: u16,
+    sample_rate: u32,
+}
+
+impl Sound {
+    /// Only mono and stereo sounds are supported.
+    pub fn from_samples(
+        channels: u16,
+        sample_rate: u32,
+        samples: Vec<f32>,
+    ) -> Result<Self, AudioError> {
+        if channels != 1 && channels != 2 {
+            return Err(AudioError::UnsupportedChannels(channels));
+        }
+        if sample_rate == 0 {
+            return Err(AudioError::ZeroSampleRate);
+        }
+        // `%` like `Mixer::mix`, as `is_multiple_of` needs Rust 1.87.
+        #[allow(clippy::manual_is_multiple_of)]
+        if samples.len() % channels as usize != 0 {
+            return Err(AudioError::PartialFrame);
+        }
+        Ok(Self {
+            samples: samples.into(),
+            channels,
+            sample_rate,
+        })
+    }
+
+    pub fn load_wav(path: impl AsRef<std::path::Path>) -> Result<Self, AudioError> {
+        Self::from_wav_reader(hound::WavReader::open(path)?)
+    }
+
+    pub fn from_wav_bytes(bytes: &[u8]) -> Result<Self, AudioError> {
+        Self::from_wav_reader(hound::WavReader::new(std::io::Cursor::new(bytes))?)
+    }
+
+    fn from_wav_reader<R: std::io::Read>(reader: hound::WavReader<R>) -> Result<Self, AudioError> {
+        let spec = reader.spec();
+        // Checked before decoding too, so a bad header fails fast.
+        if spec.channels != 1 && spec.channels != 2 {
+            return Err(AudioError::UnsupportedChannels(spec.channels));
+        }
+        let samples: Vec<f32> = match spec.sample_format {
+            hound::SampleFormat::Float => reader.into_samples::<f32>().collect::<Result<_, _>>()?,
+            hound::SampleFormat::Int => {
+                let scale = 1.0 / (1_i64 << (spec.bits_per_sample - 1)) as f32;
+                reader
+                    .into_samples::<i32>()
+                    .map(|sample| sample.map(|sample| sample as f32 * scale))
+                    .collect::<Result<_, _>>()?
+            }
+        };
+        Self::from_samples(spec.channels, spec.sample_rate, samples)
+    }
+
+    pub fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    pub fn frames(&self) -> usize {
+        self.samples.len() / self.channels as usize
+    }
+
+    pub fn duration(&self) -> std::time::Duration {
+        std::time::Duration::from_secs_f64(self.frames() as f64 / self.sample_rate as f64)
+    }
+
+    /// The (left, right) samples of a frame. Mono sounds play the same sample on both sides.
+    fn frame(&self, index: usize) -> (f32, f32) {
+        match self.channels {
+            1 => (self.samples[index], self.samples[index]),
+            _ => (self.samples[index * 2], self.samples[index * 2 + 1]),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum AudioError {
+    Wav(hound::Error),
+    UnsupportedChannels(u16),
+    /// The samples end part way through a frame, e.g. a stereo sound with an odd number.
+    PartialFrame,
+    /// A sound with a sample rate of 0 would never finish playing.
+    ZeroSampleRate,
+    /// The output device couldn't be found or opened.
+    Output(String),
+}
+
+impl std::fmt::Display for AudioError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AudioError::Wav(e) => write!(f, "could not decode WAV: {}", e),
+            AudioError::UnsupportedChannels(channels) => {
+                write!(
+                    f,
+                    "only mono and stereo are supported, not {} channels",
+                    channels
+                )
+            }
+            AudioError::PartialFrame => {
+                write!(f, "the last frame is missing samples for some channels")
+            }
+            AudioError::ZeroSampleRate => write!(f, "the sample rate is 0"),
+            AudioError::Output(e) => write!(f, "audio output: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for AudioError {}
+
+impl From<hound::Error> for AudioError {
+    fn from(e: hound::Error) -> Self {
+        AudioError::Wav(e)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PlayParams {
+    pub volume: f32,
+    /// -1.0 is fully left, 1.0 is fully right.
+    pub pan: f32,
+    /// Playback speed. 2.0 plays an octave higher and twice as fast. Zero or negative speeds,
+    /// which would never finish, play at `MIN_PITCH`.
+    pub pitch: f32,
+    pub looping: bool,
+}
+
+impl PlayParams {
+    pub const MIN_PITCH: f32 = 0.01;
+}
+
+impl Default for PlayParams {
+    fn default() -> Self {
+        Self {
+            volume: 1.0,
+            pan: 0.0,
+            pitch: 1.0,
+            looping: false,
+        }
+    }
+}
+
+/// Identifies a playing voice. Handles to voices that have finished are simply ignored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct VoiceHandle(u64);
+
+struct Voice {
+    handle: VoiceHandle,
+    sound: Sound,
+    params: PlayParams,
+    /// Position in source frames. Fractional because of pitch and sample rate conversion.
+    position: f64,
+}
+
+impl Voice {
+    /// Adds this voice into `out`. Returns false once the voice has finished.
+    fn mix_into(&mut self, out: &mut [f32], output_sample_rate: u32) -> bool {
+        // max also turns NaN into MIN_PITCH.
+        let pitch = self.params.pitch.max(PlayParams::MIN_PITCH);
+        let step = pitch as f64 * self.sound.sample_rate as f64 / output_sample_rate as f64;
+        let frames = self.sound.frames();
+        let pan = self.params.pan.clamp(-1.0, 1.0);
+        let left_gain = self.params.volume * (1.0 - pan).min(1.0);
+        let right_gain = self.params.volume * (1.0 + pan).min(1.0);
+        for out_frame in out.chunks_exact_mut(2) {
+            if self.position >= frames as f64 {
+                if !self.params.looping || frames == 0 {
+                    return false;
+                }
+                self.position %= frames as f64;
+            }
+            let index = self.position as usize;
+            let next_index = if index + 1 < frames {
+                index + 1
+            } else if self.params.looping {
+                0
+            } else {
+                index
+            };
+            // Linear interpolation between neighbouring frames.
+            let t = (self.position - index as f64) as f32;
+            let (left_a, right_a) = self.sound.frame(index);
+            let (left_b, right_b) = self.sound.frame(next_index);
+            out_frame[0] += (left_a + (left_b - left_a) * t) * left_gain;
+            out_frame[1] += (right_a + (right_b - right_a) * t) * right_gain;
+            self.position += step;
+        }
+        true
+    }
+}
+
+/// Mixes any number of voices into interleaved stereo f32.
+pub struct Mixer {
+    sample_rate: u32,
+    voices: Vec<Voice>,
+    next_handle: u64,
+}
+
+impl Mixer {
+    pub fn new(sample_rate: u32) -> Self {
+        Self {
+            sample_rate,
+            voices: Vec::new(),
+            next_handle: 0,
+        }
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    pub fn play(&mut self, sound: &Sound) -> VoiceHandle {
+        self.play_with(sound, PlayParams::default())
+    }
+
+    pub fn play_with(&mut self, sound: &Sound, params: PlayParams) -> VoiceHandle {
+        let handle = VoiceHandle(self.next_handle);
+        self.next_handle += 1;
+        self.voices.push(Voice {
+            handle,
+            sound: sound.clone(),
+            params,
+            position: 0.0,
+        });
+        handle
+    }
+
+    /// Returns false if the voice had already finished.
+    pub fn stop(&mut self, handle: VoiceHandle) -> bool {
+        let voice_count = self.voices.len();
+        self.voices.retain(|voice| voice.handle != handle);
+        self.voices.len() != voice_count
+    }
+
+    pub fn stop_all(&mut self) {
+        self.voices.clear();
+    }
+
+    pub fn is_playing(&self, handle: VoiceHandle) -> bool {
+        self.voices.iter().any(|voice| voice.handle == handle)
+    }
+
+    pub fn voice_count(&self) -> usize {
+        self.voices.len()
+    }
+
+    /// Changes the volume, pan, pitch or looping of a voice that is already playing.
+    pub fn set_params(&mut self, handle: VoiceHandle, params: PlayParams) {
+        if let Some(voice) = self.voices.iter_mut().find(|voice| voice.handle == handle) {
+            voice.params = params;
+        }
+    }
+
+    pub fn params(&self, handle: VoiceHandle) -> Option<PlayParams> {
+        self.voices
+            .iter()
+            .find(|voice| voice.handle == handle)
+            .map(|voice| voice.params)
+    }
+
+    /// Overwrites `out`, an interleaved stereo buffer, with the next `out.len() / 2` frames.
+    pub fn mix(&mut self, out: &mut [f32]) {
+        assert_eq!(out.len() % 2, 0, "the output buffer must be stereo");
+        out.fill(0.0);
+        let sample_rate = self.sample_rate;
+        self.voices
+            .retain_mut(|voice| voice.mix_into(out, sample_rate));
+    }
+}
+
+/// Fills an interleaved stereo buffer with the next mixed samples.
+pub type RenderCallback = Box<dyn FnMut(&mut [f32]) + Send>;
+
+/// Where mixed audio goes, usually a sound card.
+///
+/// Outputs call `render` from their own thread whenever they need more samples.
+pub trait AudioOutput: Send {
+    fn sample_rate(&self) -> u32;
+
+    fn start(&mut self, render: RenderCallback);
+}
+
+/// Discards all audio. For tests, headless runs, and machines without a sound card.
+pub struct NullOutput {
+    pub sample_rate: u32,
+}
+
+impl Default for NullOutput {
+    fn default() -> Self {
+        Self {
+            sample_rate: 48_000,
+        }
+    }
+}
+
+impl AudioOutput for NullOutput {
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn start(&mut self, _render: RenderCallback) {}
+}
+
+/// The system's default output device, through cpal. Needs the `cpal` feature.
+///
+/// Stereo is mixed down for mono devices, and only fills the first two channels of surround
+/// ones.
+#[cfg(feature = "cpal")]
+pub struct CpalOutput {
+    /// Taken by `start`.
+    device: Option<cpal::Device>,
+    config: cpal::SupportedStreamConfig,
+    /// cpal streams can't move between threads, so one thread makes the stream and keeps it
+    /// playing until this is dropped.
+    _stop: Option<std::sync::mpsc::Sender<()>>,
+}
+
+#[cfg(feature = "cpal")]
+impl CpalOutput {
+    pub fn new() -> Result<Self, AudioError> {
+        use cpal::traits::{DeviceTrait, HostTrait};
+        let device = cpal::default_host()
+            .default_output_device()
+            .ok_or_else(|| AudioError::Output("no output device".to_string()))?;
+        let config = device
+            .default_output_config()
+            .map_err(|e| AudioError::Output(e.to_string()))?;
+        use cpal::SampleFormat as F;
+        if !matches!(config.sample_format(), F::F32 | F::I16 | F::U16 | F::I32) {
+            return Err(AudioError::Output(format!(
+                "unsupported sample format {}",
+                config.sample_format()
+            )));
+        }
+        log::info!(
+            "Audio output {:?}: {:?}",
+            device.name().unwrap_or_default(),
+            config
+        );
+        Ok(Self {
+            device: Some(device),
+            config,
+            _stop: None,
+        })
+    }
+}
+
+/// Plays what `render` mixes, converted to `T` and the device's channels.
+#[cfg(feature = "cpal")]
+fn build_stream<T: cpal::SizedSample + cpal::FromSample<f32>>(
+    device: &cpal::Device,
+    config: &cpal::StreamConfig,
+    mut render: RenderCallback,
+) -> Result<cpal::Stream, cpal::BuildStreamError> {
+    use cpal::traits::DeviceTrait;
+    let channels = config.channels as usize;
+    let mut stereo = Vec::new();
+    device.build_output_stream(
+        config,
+        move |out: &mut [T], _| {
+            // Only allocates until it's as big as the device asks for.
+            stereo.resize(out.len() / channels * 2, 0.0);
+            render(&mut stereo);
+            for (out_frame, frame) in out.chunks_exact_mut(channels).zip(stereo.chunks_exact(2)) {
+                if channels == 1 {
+                    out_frame[0] = T::from_sample((frame[0] + frame[1]) / 2.0);
+                    continue;
+                }
+                for (channel, out_sample) in out_frame.iter_mut().enumerate() {
+                    *out_sample = T::from_sample(frame.get(channel).copied().unwrap_or(0.0));
+                }
+            }
+        },
+        |e| log::error!("Audio output: {}", e),
+        None,
+    )
+}
+
+#[cfg(feature = "cpal")]
+impl AudioOutput for CpalOutput {
+    fn sample_rate(&self) -> u32 {
+        self.config.sample_rate().0
+    }
+
+    fn start(&mut self, render: RenderCallback) {
+        let Some(device) = self.device.take() else {
+            log::warn!("Audio output already started");
+            return;
+        };
+        let config = self.config.clone();
+        let (stop, stopped) = std::sync::mpsc::channel::<()>();
+        self._stop = Some(stop);
+        std::thread::Builder::new()
+            .name("audio output".to_string())
+            .spawn(move || {
+                use cpal::traits::StreamTrait;
+                let stream_config = config.config();
+                let stream = match config.sample_format() {
+                    cpal::SampleFormat::F32 => build_stream::<f32>(&device, &stream_config, render),
+                    cpal::SampleFormat::I16 => build_stream::<i16>(&device, &stream_config, render),
+                    cpal::SampleFormat::U16 => build_stream::<u16>(&device, &stream_config, render),
+                    // I32, the last format `new` accepts.
+                    _ => build_stream::<i32>(&device, &stream_config, render),
+                };
+                let played = stream
+                    .map_err(|e| e.to_string())
+                    .and_then(|stream| stream.play().map(|()| stream).map_err(|e| e.to_string()));
+                match played {
+                    // Nothing is sent, so this returns when the `CpalOutput` drops the sender.
+                    Ok(_stream) => _ = stopped.recv(),
+                    Err(e) => log::error!("Could not start audio output: {}", e),
+                }
+            })
+            .expect("failed to spawn the audio output thread");
+    }
+}
+
+/// The default device with the `cpal` feature, falling back to a `NullOutput` if it can't be
+/// opened. Always a `NullOutput` without the feature.
+pub fn default_output() -> Box<dyn AudioOutput> {
+    #[cfg(feature = "cpal")]
+    match CpalOutput::new() {
+        Ok(output) => return Box::new(output),
+        Err(e) => log::warn!("No sound: {}", e),
+    }
+    Box::new(NullOutput::default())
+}
+
+/// What game code uses to play sounds.
+pub struct Audio {
+    mixer: std::sync::Arc<std::sync::Mutex<Mixer>>,
+    _output: Box<dyn AudioOutput>,
+}
+
+impl Audio {
+    pub fn new(mut output: Box<dyn AudioOutput>) -> Self {
+        let mixer = std::sync::Arc::new(std::sync::Mutex::new(Mixer::new(output.sample_rate())));
+        let output_mixer = mixer.clone();
+        output.start(Box::new(move |out| output_mixer.lock().unwrap().mix(out)));
+        Self {
+            mixer,
+            _output: output,
+        }
+    }
+
+    pub fn play(&self, sound: &Sound) -> VoiceHandle {
+        self.mixer.lock().unwrap().play(sound)
+    }
+
+    pub fn play_with(&self, sound: &Sound, params: PlayParams) -> VoiceHandle {
+        self.mixer.lock().unwrap().play_with(sound, params)
+    }
+
+    pub fn stop(&self, handle: VoiceHandle) -> bool {
+        self.mixer.lock().unwrap().stop(handle)
+    }
+
+    pub fn set_params(&self, handle: VoiceHandle, params: PlayParams) {
+        self.mixer.lock().unwrap().set_params(handle, params)
+    }
+
+    pub fn is_playing(&self, handle: VoiceHandle) -> bool {
+        self.mixer.lock().unwrap().is_playing(handle)
+    }
+}
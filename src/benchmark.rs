@@ -0,0 +1,250 @@
+//! Timing rendering strategies against each other, for the `benchmark` binary.
+//!
+//! Each frame is timed three ways: encoding its commands on the CPU, `queue.submit`, and, if
+//! the adapter has `TIMESTAMP_QUERY`, the GPU's own time between timestamps around the work.
+//! Reports give the median frame, which one slow frame from the OS doesn't move.
+//!
+//! On GL, `submit` draws before it returns, so without timestamps its time stands in for the
+//! GPU's.
+
+use crate::game::ArgsError;
+use std::time::Duration;
+
+/// Command line options: `[--counts 100,1000] [--frames N] [--output DIR]`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchmarkOptions {
+    /// Object counts to run every strategy at.
+    pub counts: Vec<u32>,
+    /// Frames to time at each count, after one untimed warm up frame.
+    pub frames: u32,
+    /// Where report.csv and report.md go.
+    pub output: std::path::PathBuf,
+}
+
+impl Default for BenchmarkOptions {
+    fn default() -> Self {
+        Self {
+            counts: vec![100, 1_000, 10_000, 100_000, 1_000_000],
+            frames: 20,
+            output: "benchmark".into(),
+        }
+    }
+}
+
+impl BenchmarkOptions {
+    /// `args` starts with the program name, like `std::env::args`. Unknown arguments are
+    /// ignored, like `--seed`, which `random::rng_from_args` reads.
+    pub fn from_args(args: impl IntoIterator<Item = String>) -> Result<Self, ArgsError> {
+        let mut args = args.into_iter().skip(1);
+        let mut options = Self::default();
+        while let Some(arg) = args.next() {
+            let flag = match arg.as_str() {
+                "--counts" => "--counts",
+                "--frames" => "--frames",
+                "--output" => "--output",
+                _ => continue,
+            };
+            let value = args.next().ok_or(ArgsError::MissingValue(flag))?;
+            let invalid = || ArgsError::InvalidValue {
+                flag,
+                value: value.clone(),
+            };
+            match flag {
+                "--counts" => {
+                    options.counts = value
+                        .split(',')
+                        .map(|count| count.trim().parse().map_err(|_| invalid()))
+                        .collect::<Result<_, _>>()?;
+                    if options.counts.contains(&0) {
+                        return Err(invalid());
+                    }
+                }
+                "--frames" => {
+                    options.frames = value.parse().map_err(|_| invalid())?;
+                    if options.frames == 0 {
+                        return Err(invalid());
+                    }
+                }
+                _ => options.output = value.into(),
+            }
+        }
+        Ok(options)
+    }
+}
+
+/// Measures GPU time between `begin` and `end` with timestamp queries.
+pub struct GpuTimer {
+    query_set: wgpu::QuerySet,
+    resolve_buffer: wgpu::Buffer,
+    read_buffer: wgpu::Buffer,
+    period: f32,
+}
+
+const TIMESTAMP_SIZE: u64 = std::mem::size_of::<u64>() as u64;
+
+impl GpuTimer {
+    /// `None` unless the device was created with `TIMESTAMP_QUERY`.
+    pub fn new(device: &wgpu::Device, queue: &wgpu::Queue) -> Option<Self> {
+        if !device.features().contains(wgpu::Features::TIMESTAMP_QUERY) {
+            return None;
+        }
+        let query_set = device.create_query_set(&wgpu::QuerySetDescriptor {
+            label: Some("gpu timer"),
+            ty: wgpu::QueryType::Timestamp,
+            count: 2,
+        });
+        let buffer = |label, usage| {
+            crate::logging::create_buffer(
+                device,
+                &wgpu::BufferDescriptor {
+                    label: Some(label),
+                    size: 2 * TIMESTAMP_SIZE,
+                    usage,
+                    mapped_at_creation: false,
+                },
+            )
+        };
+        Some(Self {
+            query_set,
+            resolve_buffer: buffer(
+                "gpu timer resolve",
+                wgpu::BufferUsages::QUERY_RESOLVE | wgpu::BufferUsages::COPY_SRC,
+            ),
+            read_buffer: buffer(
+                "gpu timer read",
+                wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            ),
+            period: queue.get_timestamp_period(),
+        })
+    }
+
+    pub fn begin(&self, command_encoder: &mut wgpu::CommandEncoder) {
+        command_encoder.write_timestamp(&self.query_set, 0);
+    }
+
+    /// Also copies the timestamps to where `read` finds them.
+    pub fn end(&self, command_encoder: &mut wgpu::CommandEncoder) {
+        command_encoder.write_timestamp(&self.query_set, 1);
+        command_encoder.resolve_query_set(&self.query_set, 0..2, &self.resolve_buffer, 0);
+        command_encoder.copy_buffer_to_buffer(
+            &self.resolve_buffer,
+            0,
+            &self.read_buffer,
+            0,
+            2 * TIMESTAMP_SIZE,
+        );
+    }
+
+    /// Waits for the commands from `end` to finish, and returns the time measured.
+    pub fn read(&self, device: &wgpu::Device) -> Duration {
+        let slice = self.read_buffer.slice(..);
+        slice.map_async(wgpu::MapMode::Read, |result| result.unwrap());
+        device.poll(wgpu::Maintain::Wait);
+        let timestamps: [u64; 2] = bytemuck::pod_read_unaligned(&slice.get_mapped_range());
+        self.read_buffer.unmap();
+        let ticks = timestamps[1].saturating_sub(timestamps[0]);
+        Duration::from_nanos((ticks as f64 * self.period as f64) as u64)
+    }
+}
+
+/// One frame's times. `gpu` is `None` without timestamp queries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameTimes {
+    pub encode: Duration,
+    pub submit: Duration,
+    pub gpu: Option<Duration>,
+}
+
+/// The median of each time over `frames`. `gpu` is `None` unless every frame has one.
+pub fn median_times(frames: &[FrameTimes]) -> Option<FrameTimes> {
+    if frames.is_empty() {
+        return None;
+    }
+    let median = |mut times: Vec<Duration>| {
+        times.sort();
+        times[times.len() / 2]
+    };
+    let gpu: Option<Vec<Duration>> = frames.iter().map(|frame| frame.gpu).collect();
+    Some(FrameTimes {
+        encode: median(frames.iter().map(|frame| frame.encode).collect()),
+        submit: median(frames.iter().map(|frame| frame.submit).collect()),
+        gpu: gpu.map(median),
+    })
+}
+
+/// A strategy at one object count. `times` is `None` if it was skipped, see `note`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Measurement {
+    pub strategy: &'static str,
+    pub objects: u32,
+    pub times: Option<FrameTimes>,
+    pub note: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Report {
+    /// E.g. the adapter's name and backend, so reports from different machines are told
+    /// apart.
+    pub adapter: String,
+    pub frames: u32,
+    pub measurements: Vec<Measurement>,
+}
+
+/// Microseconds, which is the scale these times are at.
+fn micros(duration: Duration) -> String {
+    format!("{:.1}", duration.as_secs_f64() * 1e6)
+}
+
+impl Report {
+    /// One row per measurement, times in microseconds. Missing times are empty.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("strategy,objects,encode_us,submit_us,gpu_us,note\n");
+        for measurement in &self.measurements {
+            let times = measurement.times;
+            csv += &format!(
+                "{},{},{},{},{},{}\n",
+                measurement.strategy,
+                measurement.objects,
+                times.map_or(String::new(), |times| micros(times.encode)),
+                times.map_or(String::new(), |times| micros(times.submit)),
+                times
+                    .and_then(|times| times.gpu)
+                    .map_or(String::new(), micros),
+                measurement.note.replace(',', ";"),
+            );
+        }
+        csv
+    }
+
+    /// A table per object count, smallest first, so strategies are compared side by side.
+    pub fn to_markdown(&self) -> String {
+        let mut markdown = format!(
+            "# Benchmark\n\n{}, median of {} frames, times in microseconds.\n",
+            self.adapter, self.frames
+        );
+        let mut counts: Vec<u32> = self.measurements.iter().map(|m| m.objects).collect();
+        counts.sort_unstable();
+        counts.dedup();
+        for objects in counts {
+            markdown += &format!(
+                "\n## {} objects\n\n\
+                | Strategy | Encode | Submit | GPU | Note |\n\
+                | --- | ---: | ---: | ---: | --- |\n",
+                objects
+            );
+            for measurement in self.measurements.iter().filter(|m| m.objects == objects) {
+                let times = measurement.times;
+                let time = |time: Option<Duration>| time.map_or("-".to_string(), micros);
+                markdown += &format!(
+                    "| {} | {} | {} | {} | {} |\n",
+                    measurement.strategy,
+                    time(times.map(|times| times.encode)),
+                    time(times.map(|times| times.submit)),
+                    time(times.and_then(|times| times.gpu)),
+                    measurement.note,
+                );
+            }
+        }
+        markdown
+    }
+}
@@ -0,0 +1,422 @@
+//! Draws the same random objects the ways the `uniforms`, `storage` and `vertex` demos do, at
+//! each object count, and reports how long frames take to encode, submit and draw.
+//!
+//! `cargo run --release --bin benchmark -- [--counts 100,1000] [--frames N] [--output DIR]`
+//!
+//! Objects are small, so the GPU time is mostly per object work rather than filling pixels.
+
+use rand::Rng;
+use wgpu_experiments::benchmark::{BenchmarkOptions, FrameTimes, GpuTimer, Measurement, Report};
+use wgpu_experiments::context::RenderContext;
+use wgpu_experiments::demo::{vertex, OurStruct};
+use wgpu_experiments::reflect::{PipelineLayout, ShaderLayout};
+use wgpu_experiments::scene::SceneObject;
+
+fn random_objects(rng: &mut impl Rng, count: usize) -> Vec<SceneObject> {
+    (0..count)
+        .map(|_| SceneObject {
+            color: glam::Vec3::new(rng.gen(), rng.gen(), rng.gen()),
+            scale: glam::Vec2::new(rng.gen_range(0.01..0.05), rng.gen_range(0.01..0.05)),
+            offset: glam::Vec2::new(rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0)),
+        })
+        .collect()
+}
+
+/// A way to get per object data to the shader, set up for some objects.
+trait Strategy {
+    fn draw<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>);
+}
+
+struct StrategyKind {
+    name: &'static str,
+    /// One draw call per object gets too slow to wait for beyond this.
+    max_objects: u32,
+    create: fn(&RenderContext, &[SceneObject]) -> Box<dyn Strategy>,
+}
+
+const STRATEGIES: &[StrategyKind] = &[
+    StrategyKind {
+        name: "uniform bind groups",
+        max_objects: 10_000,
+        create: UniformBindGroups::boxed,
+    },
+    StrategyKind {
+        name: "dynamic offsets",
+        max_objects: 100_000,
+        create: DynamicOffsets::boxed,
+    },
+    StrategyKind {
+        name: "storage buffers",
+        max_objects: u32::MAX,
+        create: StorageBuffers::boxed,
+    },
+    StrategyKind {
+        name: "instanced vertices",
+        max_objects: u32::MAX,
+        create: InstancedVertices::boxed,
+    },
+];
+
+fn render_pipeline(
+    context: &RenderContext,
+    source: &str,
+    layout: &PipelineLayout,
+    buffers: &[wgpu::VertexBufferLayout],
+) -> wgpu::RenderPipeline {
+    let device = &context.device;
+    let shader_module = wgpu_experiments::logging::create_shader_module(
+        device,
+        wgpu::ShaderModuleDescriptor {
+            label: None,
+            source: wgpu::ShaderSource::Wgsl(source.into()),
+        },
+    );
+    wgpu_experiments::logging::create_render_pipeline(
+        device,
+        &wgpu::RenderPipelineDescriptor {
+            label: None,
+            layout: Some(layout.layout()),
+            vertex: wgpu::VertexState {
+                module: &shader_module,
+                entry_point: "vertex_main",
+                buffers,
+            },
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            fragment: Some(wgpu::FragmentState {
+                module: &shader_module,
+                entry_point: "fragment_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: context.format(),
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            multiview: None,
+        },
+    )
+}
+
+fn uniforms_source() -> String {
+    wgpu_experiments::shader_file!("uniforms.wgsl")
+        .with_prelude(wgpu_experiments::demo::shader_prelude())
+        .source()
+        .into_owned()
+}
+
+fn reflect(source: &str) -> ShaderLayout {
+    ShaderLayout::reflect(source).unwrap_or_else(|e| panic!("{}", e))
+}
+
+/// Like the `uniforms` demo did at first: a uniform buffer and bind group per object.
+struct UniformBindGroups {
+    pipeline: wgpu::RenderPipeline,
+    bind_groups: Vec<wgpu::BindGroup>,
+}
+
+impl UniformBindGroups {
+    fn boxed(context: &RenderContext, objects: &[SceneObject]) -> Box<dyn Strategy> {
+        let source = uniforms_source();
+        let layout = PipelineLayout::new(&context.device, "uniform bind groups", reflect(&source));
+        let bind_groups = objects
+            .iter()
+            .map(|object| {
+                let buffer = wgpu_experiments::logging::create_buffer_init(
+                    &context.device,
+                    "object",
+                    wgpu::BufferUsages::UNIFORM,
+                    &wgpu_experiments::wgsl::WgslLayout::to_bytes(&OurStruct::from(object)),
+                );
+                layout
+                    .bind_group(0)
+                    .buffer(0, &buffer)
+                    .create(&context.device)
+                    .unwrap_or_else(|e| panic!("{}", e))
+            })
+            .collect();
+        Box::new(Self {
+            pipeline: render_pipeline(context, &source, &layout, &[]),
+            bind_groups,
+        })
+    }
+}
+
+impl Strategy for UniformBindGroups {
+    fn draw<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+        render_pass.set_pipeline(&self.pipeline);
+        for bind_group in &self.bind_groups {
+            render_pass.set_bind_group(0, bind_group, &[]);
+            render_pass.draw(0..3, 0..1);
+        }
+    }
+}
+
+/// Like the `uniforms` demo: one uniform buffer, bound at a dynamic offset per object.
+struct DynamicOffsets {
+    pipeline: wgpu::RenderPipeline,
+    bind_group: wgpu::BindGroup,
+    offsets: Vec<u32>,
+}
+
+impl DynamicOffsets {
+    fn boxed(context: &RenderContext, objects: &[SceneObject]) -> Box<dyn Strategy> {
+        let source = uniforms_source();
+        let layout = PipelineLayout::new(
+            &context.device,
+            "dynamic offsets",
+            reflect(&source).dynamic_offset(0, 0),
+        );
+        let mut arena =
+            wgpu_experiments::buffer::UniformArena::new(&context.device, "objects", objects.len());
+        let offsets = objects
+            .iter()
+            .map(|object| arena.push(&OurStruct::from(object)))
+            .collect();
+        arena.write(&context.device, &context.queue);
+        let bind_group = layout
+            .bind_group(0)
+            .buffer_binding(0, arena.binding())
+            .create(&context.device)
+            .unwrap_or_else(|e| panic!("{}", e));
+        Box::new(Self {
+            pipeline: render_pipeline(context, &source, &layout, &[]),
+            bind_group,
+            offsets,
+        })
+    }
+}
+
+impl Strategy for DynamicOffsets {
+    fn draw<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+        render_pass.set_pipeline(&self.pipeline);
+        for &offset in &self.offsets {
+            render_pass.set_bind_group(0, &self.bind_group, &[offset]);
+            render_pass.draw(0..3, 0..1);
+        }
+    }
+}
+
+/// Like the `storage` demo: the vertex shader pulls everything from storage buffers, in one
+/// instanced draw.
+struct StorageBuffers {
+    pipeline: wgpu::RenderPipeline,
+    bind_group: wgpu::BindGroup,
+    objects: u32,
+}
+
+impl StorageBuffers {
+    fn boxed(context: &RenderContext, objects: &[SceneObject]) -> Box<dyn Strategy> {
+        let source = wgpu_experiments::shader_file!("storage.wgsl")
+            .with_prelude(wgpu_experiments::demo::shader_prelude())
+            .source()
+            .into_owned();
+        let layout = PipelineLayout::new(&context.device, "storage buffers", reflect(&source));
+        let our_structs: Vec<OurStruct> = objects.iter().map(OurStruct::from).collect();
+        let object_buffer = wgpu_experiments::logging::create_buffer_init(
+            &context.device,
+            "objects",
+            wgpu::BufferUsages::STORAGE,
+            &wgpu_experiments::wgsl::slice_to_bytes(&our_structs),
+        );
+        let vertex_buffer = wgpu_experiments::logging::create_buffer_init(
+            &context.device,
+            "vertices",
+            wgpu::BufferUsages::STORAGE,
+            &wgpu_experiments::wgsl::slice_to_bytes(&wgpu_experiments::demo::storage::square()),
+        );
+        let bind_group = layout
+            .bind_group(0)
+            .buffer(0, &object_buffer)
+            .buffer(1, &vertex_buffer)
+            .create(&context.device)
+            .unwrap_or_else(|e| panic!("{}", e));
+        Box::new(Self {
+            pipeline: render_pipeline(context, &source, &layout, &[]),
+            bind_group,
+            objects: objects.len() as u32,
+        })
+    }
+}
+
+impl Strategy for StorageBuffers {
+    fn draw<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &self.bind_group, &[]);
+        render_pass.draw(0..6, 0..self.objects);
+    }
+}
+
+/// Like the `vertex` demo: each object's transform is an instance vertex attribute.
+struct InstancedVertices {
+    pipeline: wgpu::RenderPipeline,
+    vertex_buffer: wgpu::Buffer,
+    index_buffer: wgpu::Buffer,
+    transform_buffer: wgpu::Buffer,
+    objects: u32,
+}
+
+impl InstancedVertices {
+    fn boxed(context: &RenderContext, objects: &[SceneObject]) -> Box<dyn Strategy> {
+        let source = wgpu_experiments::shader_file!("vertex.wgsl")
+            .source()
+            .into_owned();
+        let layout = PipelineLayout::new(&context.device, "instanced vertices", reflect(&source));
+        let (vertices, indices) = vertex::square();
+        let transforms: Vec<vertex::Transform> =
+            objects.iter().map(vertex::Transform::from).collect();
+        Box::new(Self {
+            pipeline: render_pipeline(context, &source, &layout, vertex::VERTEX_BUFFERS),
+            vertex_buffer: wgpu_experiments::logging::create_buffer_init(
+                &context.device,
+                "vertices",
+                wgpu::BufferUsages::VERTEX,
+                bytemuck::cast_slice(&vertices),
+            ),
+            index_buffer: wgpu_experiments::logging::create_buffer_init(
+                &context.device,
+                "indices",
+                wgpu::BufferUsages::INDEX,
+                bytemuck::cast_slice(&indices),
+            ),
+            transform_buffer: wgpu_experiments::logging::create_buffer_init(
+                &context.device,
+                "transforms",
+                wgpu::BufferUsages::VERTEX,
+                bytemuck::cast_slice(&transforms),
+            ),
+            objects: objects.len() as u32,
+        })
+    }
+}
+
+impl Strategy for InstancedVertices {
+    fn draw<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        render_pass.set_vertex_buffer(1, self.transform_buffer.slice(..));
+        render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+        render_pass.draw_indexed(0..vertex::INDICES_IN_SQUARE, 0, 0..self.objects);
+    }
+}
+
+/// Times `frames` frames, after an untimed one to warm up, e.g. for drivers that compile
+/// pipelines on first use. Waits for each frame, so they don't overlap.
+fn measure(
+    context: &mut RenderContext,
+    timer: Option<&GpuTimer>,
+    strategy: &dyn Strategy,
+    frames: u32,
+) -> Vec<FrameTimes> {
+    let mut times = Vec::with_capacity(frames as usize);
+    for frame_index in 0..=frames {
+        let frame = context
+            .acquire()
+            .expect("offscreen frames are always there");
+        let start = std::time::Instant::now();
+        let mut command_encoder = context
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        if let Some(timer) = timer {
+            timer.begin(&mut command_encoder);
+        }
+        {
+            let mut render_pass = command_encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: None,
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: frame.view(),
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        store: true,
+                    },
+                })],
+                depth_stencil_attachment: None,
+            });
+            strategy.draw(&mut render_pass);
+        }
+        if let Some(timer) = timer {
+            timer.end(&mut command_encoder);
+        }
+        let command_buffer = command_encoder.finish();
+        let encoded = std::time::Instant::now();
+        context.queue.submit([command_buffer]);
+        let submitted = std::time::Instant::now();
+        let gpu = timer.map(|timer| timer.read(&context.device));
+        context.device.poll(wgpu::Maintain::Wait);
+        frame.present();
+        if frame_index > 0 {
+            times.push(FrameTimes {
+                encode: encoded - start,
+                submit: submitted - encoded,
+                gpu,
+            });
+        }
+    }
+    times
+}
+
+fn main() {
+    wgpu_experiments::logging::init();
+    let options = BenchmarkOptions::from_args(std::env::args()).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(2);
+    });
+    let mut context = RenderContext::offscreen_with_features(
+        wgpu_experiments::context::DEFAULT_HEADLESS_SIZE,
+        wgpu::Features::TIMESTAMP_QUERY,
+    );
+    let timer = GpuTimer::new(&context.device, &context.queue);
+    let mut adapter = format!(
+        "{} ({:?})",
+        context.adapter_info.name, context.adapter_info.backend
+    );
+    if timer.is_none() {
+        adapter += ", no timestamp queries so no GPU times";
+    }
+    // Every strategy draws the same objects, and smaller counts the first few of them.
+    let max_count = options.counts.iter().copied().max().unwrap_or(0);
+    let objects = random_objects(
+        &mut wgpu_experiments::random::rng_from_args(),
+        max_count as usize,
+    );
+    let mut measurements = Vec::new();
+    for &count in &options.counts {
+        for kind in STRATEGIES {
+            let mut measurement = Measurement {
+                strategy: kind.name,
+                objects: count,
+                times: None,
+                note: String::new(),
+            };
+            if count > kind.max_objects {
+                measurement.note = format!("skipped, over {} objects", kind.max_objects);
+            } else {
+                eprintln!("{}: {} objects", kind.name, count);
+                let strategy = (kind.create)(&context, &objects[..count as usize]);
+                let frames = measure(
+                    &mut context,
+                    timer.as_ref(),
+                    strategy.as_ref(),
+                    options.frames,
+                );
+                measurement.times = wgpu_experiments::benchmark::median_times(&frames);
+            }
+            measurements.push(measurement);
+        }
+    }
+    let report = Report {
+        adapter,
+        frames: options.frames,
+        measurements,
+    };
+    let markdown = report.to_markdown();
+    print!("{}", markdown);
+    let written = std::fs::create_dir_all(&options.output)
+        .and_then(|()| std::fs::write(options.output.join("report.csv"), report.to_csv()))
+        .and_then(|()| std::fs::write(options.output.join("report.md"), markdown));
+    if let Err(e) = written {
+        eprintln!("Failed to write to {}: {}", options.output.display(), e);
+        std::process::exit(1);
+    }
+}
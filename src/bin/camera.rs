@@ -0,0 +1,272 @@
+//! A grid of tiles in world units, seen through a `Camera2D`.
+//!
+//! Drag with the mouse to pan, scroll to zoom in on the cursor, Q and E rotate, R resets.
+//! The tile under the cursor, found with `screen_to_world`, is highlighted.
+
+use wgpu_experiments::camera::{Camera2D, CameraUniform};
+use wgpu_experiments::demo::camera::{shader_prelude, Tile, VERTEX_BUFFERS};
+use wgpu_experiments::vertex::{QUAD_CORNERS, QUAD_INDICES};
+use wgpu_experiments::wgsl::WgslLayout;
+
+/// Tiles along each side of the grid, which is centered on the origin.
+const GRID_SIZE: i32 = 16;
+const HOVERED_COLOR: glam::Vec3 = glam::Vec3::ONE;
+/// Zoom is multiplied by this per line scrolled.
+const ZOOM_PER_SCROLL_LINE: f32 = 1.1;
+/// In pixels per world unit.
+const ZOOM_LIMITS: (f32, f32) = (2.0, 500.0);
+/// In radians per second.
+const ROTATION_SPEED: f32 = 1.5;
+
+/// The grid, with the tile at `hovered`, in whole world units, highlighted.
+fn tiles(hovered: Option<glam::IVec2>) -> Vec<Tile> {
+    let half = GRID_SIZE / 2;
+    let mut tiles = Vec::with_capacity((GRID_SIZE * GRID_SIZE) as usize);
+    for y in -half..GRID_SIZE - half {
+        for x in -half..GRID_SIZE - half {
+            let position = glam::IVec2::new(x, y);
+            let fraction = (position + half).as_vec2() / GRID_SIZE as f32;
+            // A checkerboard, so neighbours stand apart.
+            let shade = if (x + y).rem_euclid(2) == 0 { 1.0 } else { 0.7 };
+            let color = if Some(position) == hovered {
+                HOVERED_COLOR
+            } else {
+                glam::Vec3::new(fraction.x, fraction.y, 0.5) * shade
+            };
+            tiles.push(Tile {
+                position: position.as_vec2(),
+                color,
+            });
+        }
+    }
+    tiles
+}
+
+/// The whole grid in view, with a tile's margin around it.
+fn initial_camera(viewport_size: (u32, u32)) -> Camera2D {
+    let mut camera = Camera2D::new(viewport_size);
+    let viewport_size = camera.viewport_size();
+    camera.zoom = viewport_size.min_element() / (GRID_SIZE + 2) as f32;
+    camera
+}
+
+struct WgpuStuff {
+    context: wgpu_experiments::context::RenderContext,
+    render_pipeline: wgpu_experiments::shader::HotPipeline<wgpu::RenderPipeline>,
+    corner_buffer: wgpu::Buffer,
+    index_buffer: wgpu::Buffer,
+    tile_buffer: wgpu_experiments::buffer::DynamicBuffer<Tile>,
+    camera_buffer: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    camera: Camera2D,
+    hovered: Option<glam::IVec2>,
+    input: wgpu_experiments::input::InputState,
+    actions: wgpu_experiments::actions::ActionMap,
+}
+
+impl WgpuStuff {
+    fn new(context: wgpu_experiments::context::RenderContext) -> Self {
+        let device = &context.device;
+        let preferred_format = context.format();
+        let mut shader =
+            wgpu_experiments::shader_file!("camera.wgsl").with_prelude(shader_prelude());
+        let layout = std::rc::Rc::new(
+            wgpu_experiments::reflect::PipelineLayout::from_wgsl(
+                device,
+                "camera",
+                &shader.source(),
+            )
+            .unwrap_or_else(|e| panic!("{}", e)),
+        );
+        let pipeline_layout = layout.clone();
+        let render_pipeline = wgpu_experiments::shader::HotPipeline::new(
+            device,
+            shader,
+            move |device, shader_module| {
+                wgpu_experiments::logging::create_render_pipeline(
+                    device,
+                    &wgpu::RenderPipelineDescriptor {
+                        label: None,
+                        layout: Some(pipeline_layout.layout()),
+                        vertex: wgpu::VertexState {
+                            module: shader_module,
+                            entry_point: "vertex_main",
+                            buffers: VERTEX_BUFFERS,
+                        },
+                        primitive: wgpu::PrimitiveState::default(),
+                        depth_stencil: None,
+                        multisample: wgpu::MultisampleState::default(),
+                        fragment: Some(wgpu::FragmentState {
+                            module: shader_module,
+                            entry_point: "fragment_main",
+                            targets: &[Some(wgpu::ColorTargetState {
+                                format: preferred_format,
+                                blend: None,
+                                write_mask: wgpu::ColorWrites::ALL,
+                            })],
+                        }),
+                        multiview: None,
+                    },
+                )
+            },
+        );
+        let corner_buffer = wgpu_experiments::logging::create_buffer_init(
+            device,
+            "tile corner buffer",
+            wgpu::BufferUsages::VERTEX,
+            bytemuck::cast_slice(QUAD_CORNERS),
+        );
+        let index_buffer = wgpu_experiments::logging::create_buffer_init(
+            device,
+            "tile index buffer",
+            wgpu::BufferUsages::INDEX,
+            bytemuck::cast_slice(QUAD_INDICES),
+        );
+        let tile_buffer = wgpu_experiments::buffer::DynamicBuffer::new(
+            device,
+            "tile buffer",
+            wgpu::BufferUsages::VERTEX,
+            (GRID_SIZE * GRID_SIZE) as usize,
+        );
+        let camera_buffer = wgpu_experiments::logging::create_buffer(
+            device,
+            &wgpu::BufferDescriptor {
+                label: Some("camera buffer"),
+                size: CameraUniform::SIZE,
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            },
+        );
+        let bind_group = layout
+            .bind_group(0)
+            .buffer(0, &camera_buffer)
+            .create(device)
+            .unwrap_or_else(|e| panic!("{}", e));
+        let actions = wgpu_experiments::actions::ActionMap::load(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/bindings/camera.ron"
+        ))
+        .unwrap();
+        for conflict in actions.conflicts() {
+            println!("Binding conflict: {}", conflict);
+        }
+        let camera = initial_camera(context.size());
+        WgpuStuff {
+            context,
+            render_pipeline,
+            corner_buffer,
+            index_buffer,
+            tile_buffer,
+            camera_buffer,
+            bind_group,
+            camera,
+            hovered: None,
+            input: wgpu_experiments::input::InputState::new(),
+            actions,
+        }
+    }
+}
+
+impl wgpu_experiments::game::Game for WgpuStuff {
+    fn process_input(&mut self, event: &winit::event::Event<()>) {
+        self.input.handle_event(event);
+    }
+
+    fn update(&mut self, dt: std::time::Duration) {
+        if self.actions.just_activated(&self.input, "reload_bindings") {
+            match self.actions.reload() {
+                Ok(()) => {
+                    for conflict in self.actions.conflicts() {
+                        println!("Binding conflict: {}", conflict);
+                    }
+                }
+                Err(e) => println!("{}", e),
+            }
+        }
+        if self.actions.just_activated(&self.input, "reset") {
+            self.camera = initial_camera(self.context.size());
+        }
+        if self.actions.is_active(&self.input, "pan") {
+            self.camera.pan(self.input.cursor_delta());
+        }
+        let scroll = self.input.scroll_delta().y;
+        if scroll != 0.0 {
+            let zoom = (self.camera.zoom * ZOOM_PER_SCROLL_LINE.powf(scroll))
+                .clamp(ZOOM_LIMITS.0, ZOOM_LIMITS.1);
+            // Without a cursor, zoom in on the middle of the window.
+            let center = self.camera.viewport_size() / 2.0;
+            let at = self.input.cursor_position().unwrap_or(center);
+            self.camera.zoom_at(at, zoom / self.camera.zoom);
+        }
+        self.camera.rotation +=
+            self.actions.axis(&self.input, "rotate") * ROTATION_SPEED * dt.as_secs_f32();
+        self.hovered = self
+            .input
+            .cursor_position()
+            .map(|cursor| self.camera.screen_to_world(cursor).floor().as_ivec2());
+        self.input.end_frame();
+    }
+
+    fn render(&mut self, _alpha: f32) {
+        self.render_pipeline.reload_if_changed(&self.context.device);
+        let Some(frame) = self.context.acquire() else {
+            return;
+        };
+        self.context
+            .queue
+            .write_buffer(&self.camera_buffer, 0, &self.camera.uniform().to_bytes());
+        self.tile_buffer.write(
+            &self.context.device,
+            &self.context.queue,
+            &tiles(self.hovered),
+        );
+        let mut command_encoder = self
+            .context
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        {
+            let mut render_pass = command_encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: None,
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: frame.view(),
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color {
+                            r: 0.3,
+                            g: 0.3,
+                            b: 0.3,
+                            a: 1.0,
+                        }),
+                        store: true,
+                    },
+                })],
+                depth_stencil_attachment: None,
+            });
+            render_pass.set_pipeline(self.render_pipeline.get());
+            render_pass.set_bind_group(0, &self.bind_group, &[]);
+            render_pass.set_vertex_buffer(0, self.corner_buffer.slice(..));
+            render_pass.set_vertex_buffer(1, self.tile_buffer.slice());
+            render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+            render_pass.draw_indexed(
+                0..QUAD_INDICES.len() as u32,
+                0,
+                0..self.tile_buffer.len() as u32,
+            );
+        }
+        self.context.queue.submit([command_encoder.finish()]);
+        frame.present();
+    }
+
+    fn resize(&mut self, new_size: winit::dpi::PhysicalSize<u32>) {
+        self.context.resize(new_size);
+        self.camera.resize((new_size.width, new_size.height));
+    }
+
+    fn render_context(&self) -> Option<&wgpu_experiments::context::RenderContext> {
+        Some(&self.context)
+    }
+}
+
+fn main() {
+    wgpu_experiments::game::run(WgpuStuff::new);
+}
@@ -1,96 +1,67 @@
+use wgpu_experiments::game::Game;
+
 struct WgpuStuff {
-    window: winit::window::Window,
-    surface: wgpu::Surface,
-    preferred_format: wgpu::TextureFormat,
-    device: wgpu::Device,
-    queue: wgpu::Queue,
-    render_pipeline: wgpu::RenderPipeline,
+    context: wgpu_experiments::context::RenderContext,
+    render_pipeline: wgpu_experiments::shader::HotPipeline<wgpu::RenderPipeline>,
 }
 
 impl WgpuStuff {
-    fn new(window: winit::window::Window) -> Self {
-        let instance = wgpu::Instance::new(wgpu::InstanceDescriptor::default());
-        let surface = unsafe { instance.create_surface(&window) }.unwrap();
-        let adapter = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
-            power_preference: wgpu::PowerPreference::None,
-            force_fallback_adapter: false,
-            compatible_surface: Some(&surface),
-        }))
-        .unwrap();
-        let surface_capabilities = surface.get_capabilities(&adapter);
-        let preferred_format = surface_capabilities.formats[0];
-        let (device, queue) =
-            pollster::block_on(adapter.request_device(&wgpu::DeviceDescriptor::default(), None))
-                .unwrap();
-        let shader_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-            label: None,
-            source: wgpu::ShaderSource::Wgsl(
-                include_str!("../../shaders/fundamentals.wgsl").into(),
-            ),
-        });
-        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: None,
-            layout: None,
-            vertex: wgpu::VertexState {
-                module: &shader_module,
-                entry_point: "vertex_main",
-                buffers: &[],
-            },
-            primitive: wgpu::PrimitiveState::default(),
-            depth_stencil: None,
-            multisample: wgpu::MultisampleState::default(),
-            fragment: Some(wgpu::FragmentState {
-                module: &shader_module,
-                entry_point: "fragment_main",
-                targets: &[Some(wgpu::ColorTargetState {
-                    format: preferred_format,
-                    blend: None,
-                    write_mask: wgpu::ColorWrites::ALL,
-                })],
-            }),
-            multiview: None,
-        });
-        let result = WgpuStuff {
-            window,
-            surface,
-            preferred_format,
+    fn new(context: wgpu_experiments::context::RenderContext) -> Self {
+        let device = &context.device;
+        let preferred_format = context.format();
+        let render_pipeline = wgpu_experiments::shader::HotPipeline::new(
             device,
-            queue,
-            render_pipeline,
-        };
-        result.configure_surface();
-        result
-    }
-
-    fn configure_surface(&self) {
-        let window_size = self.window.inner_size();
-        self.surface.configure(
-            &self.device,
-            &wgpu::SurfaceConfiguration {
-                usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-                format: self.preferred_format,
-                width: window_size.width,
-                height: window_size.height,
-                present_mode: wgpu::PresentMode::AutoNoVsync,
-                alpha_mode: wgpu::CompositeAlphaMode::Auto,
-                view_formats: vec![self.preferred_format],
+            wgpu_experiments::shader_file!("fundamentals.wgsl"),
+            move |device, shader_module| {
+                wgpu_experiments::logging::create_render_pipeline(
+                    device,
+                    &wgpu::RenderPipelineDescriptor {
+                        label: None,
+                        layout: None,
+                        vertex: wgpu::VertexState {
+                            module: shader_module,
+                            entry_point: "vertex_main",
+                            buffers: &[],
+                        },
+                        primitive: wgpu::PrimitiveState::default(),
+                        depth_stencil: None,
+                        multisample: wgpu::MultisampleState::default(),
+                        fragment: Some(wgpu::FragmentState {
+                            module: shader_module,
+                            entry_point: "fragment_main",
+                            targets: &[Some(wgpu::ColorTargetState {
+                                format: preferred_format,
+                                blend: None,
+                                write_mask: wgpu::ColorWrites::ALL,
+                            })],
+                        }),
+                        multiview: None,
+                    },
+                )
             },
         );
+        WgpuStuff {
+            context,
+            render_pipeline,
+        }
     }
+}
 
-    fn render(&self) {
-        let surface_texture: wgpu::SurfaceTexture = self.surface.get_current_texture().unwrap();
-        let texture_view: wgpu::TextureView = surface_texture
-            .texture
-            .create_view(&wgpu::TextureViewDescriptor::default());
+impl Game for WgpuStuff {
+    fn render(&mut self, _alpha: f32) {
+        self.render_pipeline.reload_if_changed(&self.context.device);
+        let Some(frame) = self.context.acquire() else {
+            return;
+        };
         let mut command_encoder = self
+            .context
             .device
             .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
         {
             let mut render_pass = command_encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                 label: None,
                 color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                    view: &texture_view,
+                    view: frame.view(),
                     resolve_target: None,
                     ops: wgpu::Operations {
                         load: wgpu::LoadOp::Clear(wgpu::Color {
@@ -104,52 +75,22 @@ impl WgpuStuff {
                 })],
                 depth_stencil_attachment: None,
             });
-            render_pass.set_pipeline(&self.render_pipeline);
+            render_pass.set_pipeline(self.render_pipeline.get());
             render_pass.draw(0..3, 0..1);
         }
-        self.queue.submit([command_encoder.finish()]);
-        surface_texture.present();
+        self.context.queue.submit([command_encoder.finish()]);
+        frame.present();
     }
-}
 
-fn main() {
-    let event_loop = winit::event_loop::EventLoop::new();
-    let window = winit::window::Window::new(&event_loop).unwrap();
+    fn resize(&mut self, new_size: winit::dpi::PhysicalSize<u32>) {
+        self.context.resize(new_size);
+    }
 
-    let wgpu_stuff = WgpuStuff::new(window);
-    let start = std::time::Instant::now();
-    let mut completed_renders: u64 = 0;
+    fn render_context(&self) -> Option<&wgpu_experiments::context::RenderContext> {
+        Some(&self.context)
+    }
+}
 
-    event_loop.run(move |event, _, control_flow| match event {
-        winit::event::Event::WindowEvent {
-            window_id: _,
-            event: window_event,
-        } => match window_event {
-            winit::event::WindowEvent::CloseRequested
-            | winit::event::WindowEvent::KeyboardInput {
-                input:
-                    winit::event::KeyboardInput {
-                        virtual_keycode: Some(winit::event::VirtualKeyCode::Escape),
-                        ..
-                    },
-                ..
-            } => control_flow.set_exit(),
-            winit::event::WindowEvent::Resized(_) => {
-                wgpu_stuff.configure_surface();
-            }
-            _ => {}
-        },
-        winit::event::Event::MainEventsCleared => {
-            wgpu_stuff.render();
-            completed_renders += 1;
-            if completed_renders % 100 == 0 {
-                println!(
-                    "FPS: {:.0}",
-                    1.0 / ((std::time::Instant::now() - start).as_secs_f32()
-                        / completed_renders as f32)
-                );
-            }
-        }
-        _ => {}
-    });
+fn main() {
+    wgpu_experiments::game::run(WgpuStuff::new);
 }
@@ -1,37 +1,52 @@
 fn main() {
-    let instance: wgpu::Instance = wgpu::Instance::new(wgpu::InstanceDescriptor::default());
-    let adapter: wgpu::Adapter =
-        pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions::default()))
-            .unwrap();
+    wgpu_experiments::logging::init();
+    let instance: wgpu::Instance =
+        wgpu_experiments::logging::create_instance(wgpu::InstanceDescriptor::default());
+    let adapter: wgpu::Adapter = wgpu_experiments::logging::request_adapter(
+        &instance,
+        &wgpu::RequestAdapterOptions::default(),
+    )
+    .unwrap();
     let (device, queue): (wgpu::Device, wgpu::Queue) =
-        pollster::block_on(adapter.request_device(&wgpu::DeviceDescriptor::default(), None))
+        wgpu_experiments::logging::request_device(&adapter, &wgpu::DeviceDescriptor::default())
             .unwrap();
-    let shader_module = device.create_shader_module(wgpu::include_wgsl!(
-        "../../shaders/fundamentals_compute.wgsl"
-    ));
-    let compute_pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
-        label: None,
-        layout: None,
-        module: &shader_module,
-        entry_point: "compute_main",
-    });
+    // This runs once, so there's nothing to reload, but debug builds still read the file.
+    let mut shader = wgpu_experiments::shader_file!("fundamentals_compute.wgsl");
+    let shader_module = shader
+        .compile(&device)
+        .unwrap_or_else(|error| panic!("{}: {}", shader.path(), error));
+    let compute_pipeline = wgpu_experiments::logging::create_compute_pipeline(
+        &device,
+        &wgpu::ComputePipelineDescriptor {
+            label: None,
+            layout: None,
+            module: &shader_module,
+            entry_point: "compute_main",
+        },
+    );
     let input: Vec<f32> = vec![1.0, 3.0, 5.0];
     let input_byte_size = (input.len() * std::mem::size_of::<f32>()) as u64;
-    let work_buffer = device.create_buffer(&wgpu::BufferDescriptor {
-        label: Some("work_buffer"),
-        size: input_byte_size,
-        usage: wgpu::BufferUsages::STORAGE
-            | wgpu::BufferUsages::COPY_SRC
-            | wgpu::BufferUsages::COPY_DST,
-        mapped_at_creation: false,
-    });
+    let work_buffer = wgpu_experiments::logging::create_buffer(
+        &device,
+        &wgpu::BufferDescriptor {
+            label: Some("work_buffer"),
+            size: input_byte_size,
+            usage: wgpu::BufferUsages::STORAGE
+                | wgpu::BufferUsages::COPY_SRC
+                | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        },
+    );
     queue.write_buffer(&work_buffer, 0, bytemuck::cast_slice(&input));
-    let result_buffer = device.create_buffer(&wgpu::BufferDescriptor {
-        label: Some("result_buffer"),
-        size: input_byte_size,
-        usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
-        mapped_at_creation: false,
-    });
+    let result_buffer = wgpu_experiments::logging::create_buffer(
+        &device,
+        &wgpu::BufferDescriptor {
+            label: Some("result_buffer"),
+            size: input_byte_size,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        },
+    );
     let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
         label: None,
         layout: &compute_pipeline.get_bind_group_layout(0),
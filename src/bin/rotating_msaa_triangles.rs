@@ -1,33 +1,17 @@
-// TODO: Game.run ?
-// TODO: Game.process_input
-// TODO: Game.update
-// TODO: Game.render
-// TODO: How will I play sounds?
 // TODO: Clear window with a color
-// TODO: I will need to track keystate myself, possible with a set
-// TODO: Simulate a lower resolution
-// TODO: Create a way to draw solid color rectangles at given coordinates
-use pollster::FutureExt as _;
 
-#[repr(C)]
-#[derive(Clone, Copy, bytemuck::Zeroable, bytemuck::Pod)]
-struct TextureVertex {
-    position: glam::Vec2,
-    uv: glam::Vec2,
-}
+use wgpu_experiments::demo::rotating_msaa_triangles::{TextureVertex, Vertex};
+use wgpu_experiments::vertex::VertexLayout;
 
-const TEXTURE_VERTEX_ATTRIBUTES: &[wgpu::VertexAttribute] = &[
-    wgpu::VertexAttribute {
-        format: wgpu::VertexFormat::Float32x2,
-        offset: 0,
-        shader_location: 0,
-    },
-    wgpu::VertexAttribute {
-        format: wgpu::VertexFormat::Float32x2,
-        offset: 4 * 2,
-        shader_location: 1,
-    },
-];
+/// We render at this resolution, then scale up to fit the window.
+const CANVAS_SIZE: (u32, u32) = (100, 100);
+/// The color of the bars around the canvas when the window's aspect ratio doesn't match.
+const BAR_COLOR: wgpu::Color = wgpu::Color {
+    r: 0.0,
+    g: 0.0,
+    b: 0.0,
+    a: 1.0,
+};
 
 fn square() -> Vec<TextureVertex> {
     let v0 = TextureVertex {
@@ -60,32 +44,12 @@ fn rotate_cc(angle_degrees: f32) -> glam::Mat2 {
     ])
 }
 
-#[repr(C)]
-#[derive(Clone, Copy, bytemuck::Zeroable, bytemuck::Pod)]
-struct Vertex {
-    position: glam::Vec2,
-    color: glam::Vec3,
-}
-
-const VERTEX_ATTRIBUTES: &[wgpu::VertexAttribute] = &[
-    wgpu::VertexAttribute {
-        format: wgpu::VertexFormat::Float32x2,
-        offset: 0,
-        shader_location: 0,
-    },
-    wgpu::VertexAttribute {
-        format: wgpu::VertexFormat::Float32x3,
-        offset: 4 * 2,
-        shader_location: 1,
-    },
-];
-
 // TODO: Make a TextureVertex struct that holds a position and uv coords
 // TODO: Make a square function that returns a unit square of TextureVertex
 
-fn triangle(angle_degrees: f32) -> Vec<Vertex> {
+fn triangle(angle_degrees: f32) -> [Vertex; 6] {
     let top_vert = glam::Vec2::new(0.0, 0.5);
-    vec![
+    [
         Vertex {
             position: rotate_cc(0.0 + angle_degrees * 90.0) * top_vert,
             color: glam::Vec3::new(1.0, 0.0, 0.0),
@@ -113,232 +77,301 @@ fn triangle(angle_degrees: f32) -> Vec<Vertex> {
     ]
 }
 
+/// A short beep, played every second of simulation time.
+fn tick_sound() -> wgpu_experiments::audio::Sound {
+    let sample_rate = 44_100;
+    let frames = sample_rate / 25;
+    let samples = (0..frames)
+        .map(|i| {
+            let t = i as f32 / sample_rate as f32;
+            let fade_out = 1.0 - i as f32 / frames as f32;
+            (t * 880.0 * std::f32::consts::TAU).sin() * fade_out * 0.3
+        })
+        .collect();
+    wgpu_experiments::audio::Sound::from_samples(1, sample_rate, samples).unwrap()
+}
+
+/// Both are built from rotating_msaa_triangles.wgsl, so they're reloaded together.
+struct Pipelines {
+    low_res: wgpu::RenderPipeline,
+    surface: wgpu::RenderPipeline,
+}
+
 struct Game {
-    window: winit::window::Window,
-    surface: wgpu::Surface,
-    device: wgpu::Device,
-    queue: wgpu::Queue,
+    context: wgpu_experiments::context::RenderContext,
     square_vertex_buffer: wgpu::Buffer,
-    low_res_render_pipeline: wgpu::RenderPipeline,
-    low_res_texture_view: wgpu::TextureView,
-    low_res_texture_resolved_view: wgpu::TextureView,
-    surface_render_pipeline: wgpu::RenderPipeline,
+    triangle_vertex_buffer: wgpu_experiments::buffer::DynamicBuffer<Vertex>,
+    pipelines: wgpu_experiments::shader::HotPipeline<Pipelines>,
+    low_res_target: wgpu_experiments::surface::RenderTarget,
+    low_res_resolved_target: wgpu_experiments::surface::RenderTarget,
+    surface_render_sampler: wgpu::Sampler,
     surface_render_bind_group: wgpu::BindGroup,
+    previous_simulation_time: std::time::Duration,
+    simulation_time: std::time::Duration,
+    input: wgpu_experiments::input::InputState,
+    actions: wgpu_experiments::actions::ActionMap,
+    rect_renderer: wgpu_experiments::rect::RectRenderer,
+    canvas: wgpu_experiments::canvas::VirtualCanvas,
+    audio: wgpu_experiments::audio::Audio,
+    tick_sound: wgpu_experiments::audio::Sound,
 }
 
 impl Game {
-    fn new(window: winit::window::Window) -> Self {
-        // TODO: Log all these things we're creating
-        // TODO: Especially log the default instances so we can review their settings
-        let instance: wgpu::Instance = wgpu::Instance::new(wgpu::InstanceDescriptor::default());
-        let surface: wgpu::Surface = unsafe { instance.create_surface(&window) }.unwrap();
-        let adapter: wgpu::Adapter = instance
-            .request_adapter(&wgpu::RequestAdapterOptions::default())
-            .block_on()
-            .unwrap();
-        let (device, queue): (wgpu::Device, wgpu::Queue) = adapter
-            .request_device(&wgpu::DeviceDescriptor::default(), None)
-            .block_on()
-            .unwrap();
-        let square_verts = square();
-        let square_vert_bytes: &[u8] = bytemuck::cast_slice(square_verts.as_slice());
-        let square_vertex_buffer: wgpu::Buffer = device.create_buffer(&wgpu::BufferDescriptor {
-            label: Some("square buffer"),
-            size: square_vert_bytes.len() as u64,
-            usage: wgpu::BufferUsages::VERTEX,
-            mapped_at_creation: true,
-        });
-        square_vertex_buffer
-            .slice(..)
-            .get_mapped_range_mut()
-            .copy_from_slice(square_vert_bytes);
-        square_vertex_buffer.unmap();
-        let shader_module: wgpu::ShaderModule = device.create_shader_module(wgpu::include_wgsl!(
-            "../../shaders/rotating_msaa_triangles.wgsl"
-        ));
-        let low_res_render_pipeline =
-            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-                label: Some("render pipeline"),
-                layout: None,
-                vertex: wgpu::VertexState {
-                    module: &shader_module,
-                    entry_point: "vertex_main",
-                    buffers: &[wgpu::VertexBufferLayout {
-                        array_stride: std::mem::size_of::<Vertex>() as u64,
-                        step_mode: wgpu::VertexStepMode::Vertex,
-                        attributes: VERTEX_ATTRIBUTES,
-                    }],
-                },
-                primitive: wgpu::PrimitiveState::default(),
-                depth_stencil: None,
-                multisample: wgpu::MultisampleState {
-                    count: 4,
-                    mask: !0,
-                    alpha_to_coverage_enabled: false,
-                },
-                fragment: Some(wgpu::FragmentState {
-                    module: &shader_module,
-                    entry_point: "fragment_main",
-                    targets: &[Some(wgpu::ColorTargetState {
-                        format: wgpu::TextureFormat::Bgra8UnormSrgb,
-                        blend: None,
-                        write_mask: wgpu::ColorWrites::ALL,
-                    })],
-                }),
-                multiview: None,
-            });
-        let low_res_texture: wgpu::Texture = device.create_texture(&wgpu::TextureDescriptor {
-            label: Some("low res texture"),
-            size: wgpu::Extent3d {
-                width: 100,
-                height: 100,
-                depth_or_array_layers: 1,
+    fn new(context: wgpu_experiments::context::RenderContext) -> Self {
+        let device = &context.device;
+        let format = context.format();
+        let square_vertex_buffer = wgpu_experiments::logging::create_buffer_init(
+            device,
+            "square buffer",
+            wgpu::BufferUsages::VERTEX,
+            bytemuck::cast_slice(&square()),
+        );
+        let triangle_vertex_buffer = wgpu_experiments::buffer::DynamicBuffer::new(
+            device,
+            "triangle vertex buffer",
+            wgpu::BufferUsages::VERTEX,
+            6,
+        );
+        let low_res_target = wgpu_experiments::surface::RenderTarget::new(
+            device,
+            "low res texture",
+            CANVAS_SIZE,
+            format,
+            4,
+            // Only the resolved texture is sampled, and GL can't sample multisampled textures.
+            wgpu::TextureUsages::RENDER_ATTACHMENT,
+        );
+        let low_res_resolved_target = wgpu_experiments::surface::RenderTarget::new(
+            device,
+            "low res texture resolved",
+            CANVAS_SIZE,
+            format,
+            1,
+            wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+        );
+        let pipelines = wgpu_experiments::shader::HotPipeline::new(
+            device,
+            wgpu_experiments::shader_file!("rotating_msaa_triangles.wgsl"),
+            move |device, shader_module| Pipelines {
+                low_res: wgpu_experiments::logging::create_render_pipeline(
+                    device,
+                    &wgpu::RenderPipelineDescriptor {
+                        label: Some("render pipeline"),
+                        layout: None,
+                        vertex: wgpu::VertexState {
+                            module: shader_module,
+                            entry_point: "vertex_main",
+                            buffers: &[Vertex::LAYOUT],
+                        },
+                        primitive: wgpu::PrimitiveState::default(),
+                        depth_stencil: None,
+                        multisample: wgpu::MultisampleState {
+                            count: 4,
+                            mask: !0,
+                            alpha_to_coverage_enabled: false,
+                        },
+                        fragment: Some(wgpu::FragmentState {
+                            module: shader_module,
+                            entry_point: "fragment_main",
+                            targets: &[Some(wgpu::ColorTargetState {
+                                format,
+                                blend: None,
+                                write_mask: wgpu::ColorWrites::ALL,
+                            })],
+                        }),
+                        multiview: None,
+                    },
+                ),
+                surface: wgpu_experiments::logging::create_render_pipeline(
+                    device,
+                    &wgpu::RenderPipelineDescriptor {
+                        label: Some("surface render pipeline"),
+                        layout: None,
+                        vertex: wgpu::VertexState {
+                            module: shader_module,
+                            entry_point: "texture_to_texture_vertex_main",
+                            buffers: &[TextureVertex::LAYOUT],
+                        },
+                        primitive: wgpu::PrimitiveState::default(),
+                        depth_stencil: None,
+                        multisample: wgpu::MultisampleState::default(),
+                        fragment: Some(wgpu::FragmentState {
+                            module: shader_module,
+                            entry_point: "texture_to_texture_fragment_main",
+                            targets: &[Some(wgpu::ColorTargetState {
+                                format,
+                                blend: None,
+                                write_mask: wgpu::ColorWrites::ALL,
+                            })],
+                        }),
+                        multiview: None,
+                    },
+                ),
             },
-            mip_level_count: 1,
-            sample_count: 4,
-            dimension: wgpu::TextureDimension::D2,
-            format: wgpu::TextureFormat::Bgra8UnormSrgb,
-            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
-            view_formats: &[wgpu::TextureFormat::Bgra8UnormSrgb],
-        });
-        let low_res_texture_resolved: wgpu::Texture =
-            device.create_texture(&wgpu::TextureDescriptor {
-                label: Some("low res texture resolved"),
-                size: wgpu::Extent3d {
-                    width: 100,
-                    height: 100,
-                    depth_or_array_layers: 1,
-                },
-                mip_level_count: 1,
-                sample_count: 1,
-                dimension: wgpu::TextureDimension::D2,
-                format: wgpu::TextureFormat::Bgra8UnormSrgb,
-                usage: wgpu::TextureUsages::RENDER_ATTACHMENT
-                    | wgpu::TextureUsages::TEXTURE_BINDING,
-                view_formats: &[wgpu::TextureFormat::Bgra8UnormSrgb],
-            });
-        let surface_render_pipeline =
-            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-                label: Some("surface render pipeline"),
-                layout: None,
-                vertex: wgpu::VertexState {
-                    module: &shader_module,
-                    entry_point: "texture_to_texture_vertex_main",
-                    buffers: &[wgpu::VertexBufferLayout {
-                        array_stride: std::mem::size_of::<TextureVertex>() as u64,
-                        step_mode: wgpu::VertexStepMode::Vertex,
-                        attributes: TEXTURE_VERTEX_ATTRIBUTES,
-                    }],
-                },
-                primitive: wgpu::PrimitiveState::default(),
-                depth_stencil: None,
-                multisample: wgpu::MultisampleState::default(),
-                fragment: Some(wgpu::FragmentState {
-                    module: &shader_module,
-                    entry_point: "texture_to_texture_fragment_main",
-                    targets: &[Some(wgpu::ColorTargetState {
-                        format: wgpu::TextureFormat::Bgra8UnormSrgb,
-                        blend: None,
-                        write_mask: wgpu::ColorWrites::ALL,
-                    })],
-                }),
-                multiview: None,
-            });
-        let surface_render_sampler = device.create_sampler(&wgpu::SamplerDescriptor {
-            label: Some("low res sampler"),
-            address_mode_u: wgpu::AddressMode::ClampToEdge,
-            address_mode_v: wgpu::AddressMode::ClampToEdge,
-            address_mode_w: wgpu::AddressMode::ClampToEdge,
-            mag_filter: wgpu::FilterMode::Nearest,
-            min_filter: wgpu::FilterMode::Nearest,
-            mipmap_filter: wgpu::FilterMode::Nearest,
-            lod_min_clamp: 0.0,
-            lod_max_clamp: 0.0,
-            compare: None,
-            anisotropy_clamp: 1,
-            border_color: None,
-        });
-        let low_res_texture_view =
-            low_res_texture.create_view(&wgpu::TextureViewDescriptor::default());
-        let low_res_texture_resolved_view =
-            low_res_texture_resolved.create_view(&wgpu::TextureViewDescriptor::default());
-        let surface_render_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+        );
+        let surface_render_sampler = wgpu_experiments::logging::create_sampler(
+            device,
+            &wgpu::SamplerDescriptor {
+                label: Some("low res sampler"),
+                address_mode_u: wgpu::AddressMode::ClampToEdge,
+                address_mode_v: wgpu::AddressMode::ClampToEdge,
+                address_mode_w: wgpu::AddressMode::ClampToEdge,
+                mag_filter: wgpu::FilterMode::Nearest,
+                min_filter: wgpu::FilterMode::Nearest,
+                mipmap_filter: wgpu::FilterMode::Nearest,
+                lod_min_clamp: 0.0,
+                lod_max_clamp: 0.0,
+                compare: None,
+                anisotropy_clamp: 1,
+                border_color: None,
+            },
+        );
+        let surface_render_bind_group = Self::create_surface_render_bind_group(
+            device,
+            &pipelines.get().surface,
+            &low_res_resolved_target,
+            &surface_render_sampler,
+        );
+        let actions = wgpu_experiments::actions::ActionMap::load(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/bindings/rotating_msaa_triangles.ron"
+        ))
+        .unwrap();
+        for conflict in actions.conflicts() {
+            println!("Binding conflict: {}", conflict);
+        }
+        let rect_renderer = wgpu_experiments::rect::RectRenderer::new(device, format, 4);
+        let window_size = context.size();
+        let canvas =
+            wgpu_experiments::canvas::VirtualCanvas::new(CANVAS_SIZE, BAR_COLOR, window_size);
+        // Headless runs, like the golden tests, stay quiet.
+        let audio_output: Box<dyn wgpu_experiments::audio::AudioOutput> = match context.output {
+            wgpu_experiments::context::RenderOutput::Window(_) => {
+                wgpu_experiments::audio::default_output()
+            }
+            wgpu_experiments::context::RenderOutput::Offscreen(_) => {
+                Box::<wgpu_experiments::audio::NullOutput>::default()
+            }
+        };
+        Game {
+            context,
+            square_vertex_buffer,
+            triangle_vertex_buffer,
+            pipelines,
+            low_res_target,
+            low_res_resolved_target,
+            surface_render_sampler,
+            surface_render_bind_group,
+            previous_simulation_time: std::time::Duration::ZERO,
+            simulation_time: std::time::Duration::ZERO,
+            input: wgpu_experiments::input::InputState::new(),
+            actions,
+            rect_renderer,
+            canvas,
+            audio: wgpu_experiments::audio::Audio::new(audio_output),
+            tick_sound: tick_sound(),
+        }
+    }
+
+    fn create_surface_render_bind_group(
+        device: &wgpu::Device,
+        surface_render_pipeline: &wgpu::RenderPipeline,
+        low_res_resolved_target: &wgpu_experiments::surface::RenderTarget,
+        surface_render_sampler: &wgpu::Sampler,
+    ) -> wgpu::BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
             label: Some("surface render bind group"),
             layout: &surface_render_pipeline.get_bind_group_layout(0),
             entries: &[
                 wgpu::BindGroupEntry {
                     binding: 0,
-                    resource: wgpu::BindingResource::TextureView(&low_res_texture_resolved_view),
+                    resource: wgpu::BindingResource::TextureView(low_res_resolved_target.view()),
                 },
                 wgpu::BindGroupEntry {
                     binding: 1,
-                    resource: wgpu::BindingResource::Sampler(&surface_render_sampler),
+                    resource: wgpu::BindingResource::Sampler(surface_render_sampler),
                 },
             ],
-        });
-        let game = Game {
-            window,
-            surface,
-            device,
-            queue,
-            square_vertex_buffer,
-            low_res_render_pipeline,
-            low_res_texture_view,
-            low_res_texture_resolved_view,
-            surface_render_pipeline,
-            surface_render_bind_group,
-        };
-        game.configure_surface();
-        game
+        })
     }
+}
 
-    fn configure_surface(&self) {
-        let window_inner_size = self.window.inner_size();
-        self.surface.configure(
-            &self.device,
-            &wgpu::SurfaceConfiguration {
-                usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
-                format: wgpu::TextureFormat::Bgra8UnormSrgb,
-                width: window_inner_size.width,
-                height: window_inner_size.height,
-                present_mode: wgpu::PresentMode::AutoNoVsync,
-                // The window surface does not support alpha
-                alpha_mode: wgpu::CompositeAlphaMode::Auto,
-                view_formats: vec![wgpu::TextureFormat::Bgra8UnormSrgb],
-            },
-        );
+impl wgpu_experiments::game::Game for Game {
+    fn process_input(&mut self, event: &winit::event::Event<()>) {
+        self.input.handle_event(event);
+    }
+
+    fn update(&mut self, dt: std::time::Duration) {
+        if self.actions.just_activated(&self.input, "reload_bindings") {
+            match self.actions.reload() {
+                Ok(()) => {
+                    for conflict in self.actions.conflicts() {
+                        println!("Binding conflict: {}", conflict);
+                    }
+                }
+                Err(e) => println!("{}", e),
+            }
+        }
+        self.previous_simulation_time = self.simulation_time;
+        if !self.actions.is_active(&self.input, "pause") {
+            let speed = 1.0 + self.actions.axis(&self.input, "speed");
+            self.simulation_time += dt.mul_f32(speed);
+            if self.simulation_time.as_secs() > self.previous_simulation_time.as_secs() {
+                self.audio.play_with(
+                    &self.tick_sound,
+                    wgpu_experiments::audio::PlayParams {
+                        pitch: speed,
+                        ..Default::default()
+                    },
+                );
+            }
+        }
+        self.input.end_frame();
     }
 
-    fn render(&self, t: std::time::Duration) {
-        // TODO: Log all these things we're creating
-        // TODO: Especially log the default instances so we can review their settings
-        let triangle_vertices = triangle(t.as_secs_f32());
-        let triangle_vertice_bytes: &[u8] = bytemuck::cast_slice(triangle_vertices.as_slice());
-        let vertex_buffer = self.device.create_buffer(&wgpu::BufferDescriptor {
-            label: Some("vertex buffer"),
-            size: triangle_vertice_bytes.len() as u64,
-            usage: wgpu::BufferUsages::VERTEX,
-            mapped_at_creation: true,
-        });
-        vertex_buffer
-            .slice(..)
-            .get_mapped_range_mut()
-            .copy_from_slice(triangle_vertice_bytes);
-        vertex_buffer.unmap();
+    fn render(&mut self, alpha: f32) {
+        if self.pipelines.reload_if_changed(&self.context.device) {
+            self.surface_render_bind_group = Self::create_surface_render_bind_group(
+                &self.context.device,
+                &self.pipelines.get().surface,
+                &self.low_res_resolved_target,
+                &self.surface_render_sampler,
+            );
+        }
+        let t = self.previous_simulation_time.as_secs_f32() * (1.0 - alpha)
+            + self.simulation_time.as_secs_f32() * alpha;
+        let triangle_vertices = triangle(t);
+        self.triangle_vertex_buffer.write(
+            &self.context.device,
+            &self.context.queue,
+            &triangle_vertices,
+        );
+        // A frame around the edge of the low res texture
+        let (w, h) = (CANVAS_SIZE.0 as f32, CANVAS_SIZE.1 as f32);
+        let frame_color = [1.0, 1.0, 1.0, 0.5];
+        self.rect_renderer.draw_rect(0.0, 0.0, w, 2.0, frame_color);
+        self.rect_renderer
+            .draw_rect(0.0, h - 2.0, w, 2.0, frame_color);
+        self.rect_renderer
+            .draw_rect(0.0, 2.0, 2.0, h - 4.0, frame_color);
+        self.rect_renderer
+            .draw_rect(w - 2.0, 2.0, 2.0, h - 4.0, frame_color);
+        self.rect_renderer
+            .prepare(&self.context.device, &self.context.queue, CANVAS_SIZE);
         let mut command_encoder: wgpu::CommandEncoder = self
+            .context
             .device
             .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
-        let surface_texture: wgpu::SurfaceTexture = self.surface.get_current_texture().unwrap();
-        let surface_texture_view: wgpu::TextureView = surface_texture
-            .texture
-            .create_view(&wgpu::TextureViewDescriptor::default());
+        let Some(frame) = self.context.acquire() else {
+            return;
+        };
         {
             let mut low_res_render_pass: wgpu::RenderPass =
                 command_encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                     label: Some("low res render pass"),
                     color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                        view: &self.low_res_texture_view,
-                        resolve_target: Some(&self.low_res_texture_resolved_view),
+                        view: self.low_res_target.view(),
+                        resolve_target: Some(self.low_res_resolved_target.view()),
                         ops: wgpu::Operations {
                             load: wgpu::LoadOp::Clear(wgpu::Color {
                                 r: 0.1,
@@ -352,104 +385,52 @@ impl Game {
                     })],
                     depth_stencil_attachment: None,
                 });
-            low_res_render_pass.set_pipeline(&self.low_res_render_pipeline);
-            low_res_render_pass.set_vertex_buffer(0, vertex_buffer.slice(..));
-            low_res_render_pass.draw(0..6, 0..1);
+            low_res_render_pass.set_pipeline(&self.pipelines.get().low_res);
+            low_res_render_pass.set_vertex_buffer(0, self.triangle_vertex_buffer.slice());
+            low_res_render_pass.draw(0..self.triangle_vertex_buffer.len() as u32, 0..1);
+            self.rect_renderer.render(&mut low_res_render_pass);
         }
         {
             let mut surface_render_pass: wgpu::RenderPass =
                 command_encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                     label: Some("surface render pass"),
                     color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                        view: &surface_texture_view,
+                        view: frame.view(),
                         resolve_target: None,
                         ops: wgpu::Operations {
-                            load: wgpu::LoadOp::Clear(wgpu::Color {
-                                r: 0.1,
-                                g: 0.2,
-                                b: 0.3,
-                                // We're rendering to a window surface which ignores alpha
-                                a: 1.0,
-                            }),
+                            load: wgpu::LoadOp::Clear(self.canvas.bar_color()),
                             store: true,
                         },
                     })],
                     depth_stencil_attachment: None,
                 });
-            surface_render_pass.set_pipeline(&self.surface_render_pipeline);
+            let (x, y, w, h) = self.canvas.layout().viewport();
+            surface_render_pass.set_viewport(x, y, w, h, 0.0, 1.0);
+            surface_render_pass.set_pipeline(&self.pipelines.get().surface);
             surface_render_pass.set_bind_group(0, &self.surface_render_bind_group, &[]);
             surface_render_pass.set_vertex_buffer(0, self.square_vertex_buffer.slice(..));
             surface_render_pass.draw(0..6, 0..1);
         }
-        self.queue.submit([command_encoder.finish()]);
-        surface_texture.present();
+        self.context.queue.submit([command_encoder.finish()]);
+        frame.present();
+    }
+
+    fn render_context(&self) -> Option<&wgpu_experiments::context::RenderContext> {
+        Some(&self.context)
+    }
+
+    fn frame_stats_reporter(&self) -> Box<dyn wgpu_experiments::frame_stats::Reporter> {
+        Box::new(wgpu_experiments::frame_stats::WindowTitleReporter::new(
+            "Rotating MSAA triangles",
+        ))
+    }
+
+    fn resize(&mut self, new_size: winit::dpi::PhysicalSize<u32>) {
+        self.canvas.resize((new_size.width, new_size.height));
+        self.context.resize(new_size);
     }
 }
 
 fn main() {
-    // TODO: Process input
-    // TODO: Update game state
-    // TODO: Render
-    let event_loop = winit::event_loop::EventLoop::new();
-    let window: winit::window::Window = winit::window::Window::new(&event_loop).unwrap();
-    let game = Game::new(window);
-    let start_time = std::time::Instant::now();
-    let mut last_render_time = start_time;
-    // Render time exponential moving average in seconds
-    let mut render_time_ema_seconds: f32 = 0.0;
-    let mut rendered_frames: u64 = 0;
-    event_loop.run(move |event, _, control_flow| {
-        let time_since_start: std::time::Duration = std::time::Instant::now() - start_time;
-        match event {
-            winit::event::Event::WindowEvent {
-                window_id: _,
-                event: window_event,
-            } => match window_event {
-                winit::event::WindowEvent::CloseRequested => {
-                    control_flow.set_exit();
-                }
-                winit::event::WindowEvent::KeyboardInput {
-                    device_id: _,
-                    input:
-                        winit::event::KeyboardInput {
-                            scancode: _,
-                            state: _,
-                            virtual_keycode: Some(winit::event::VirtualKeyCode::Escape),
-                            ..
-                        },
-                    is_synthetic: _,
-                } => {
-                    control_flow.set_exit();
-                }
-                winit::event::WindowEvent::Resized(_) => {
-                    game.configure_surface();
-                }
-                _ => {}
-            },
-            winit::event::Event::DeviceEvent {
-                device_id: _,
-                event: _device_event,
-            } => {
-                // TODO: Handle button presses
-                // TODO: Track button states
-            }
-            winit::event::Event::MainEventsCleared => {
-                // The winit docs say:
-                // Programs that draw graphics continuously, like most games,
-                // can render here unconditionally for simplicity.
-                // See: https://docs.rs/winit/latest/winit/event/enum.Event.html#variant.MainEventsCleared
-                game.render(time_since_start);
-                let now = std::time::Instant::now();
-                let render_time_seconds: f32 = (now - last_render_time).as_secs_f32();
-                render_time_ema_seconds *= 0.99;
-                render_time_ema_seconds += 0.01 * render_time_seconds;
-                last_render_time = now;
-                rendered_frames += 1;
-                if rendered_frames % 100 == 0 {
-                    println!("FPS: {:.0}", 1.0 / render_time_ema_seconds);
-                }
-            }
-            _ => {}
-        }
-    });
+    wgpu_experiments::game::run(Game::new);
 }
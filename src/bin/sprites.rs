@@ -0,0 +1,263 @@
+//! Thousands of sprites from a texture atlas, drifting and spinning, in a `SpriteBatch`.
+//!
+//! Shapes come from one atlas texture, with tiles from a second texture under and over them,
+//! so the batch needs a draw call per layer. It prints how many it used.
+
+use rand::Rng;
+use wgpu_experiments::camera::Camera2D;
+use wgpu_experiments::game::Game;
+use wgpu_experiments::image::Image;
+use wgpu_experiments::sprite::{Sprite, SpriteBatch, TextureAtlas, TextureId};
+use wgpu_experiments::texture::{Texture, TextureOptions};
+
+const SPRITE_COUNT: usize = 5_000;
+/// Tiles under the shapes, and over them.
+const TILE_COUNTS: (usize, usize) = (40, 10);
+/// Centered on the origin. Sprites that drift off one edge come back on the other.
+const WORLD_SIZE: glam::Vec2 = glam::Vec2::new(800.0, 600.0);
+/// Pixels in each cell of the shape atlas.
+const CELL_SIZE: u32 = 32;
+const SHAPE_COUNT: u32 = 4;
+
+/// White shapes on transparent, side by side, to be tinted: a disc, a diamond, a ring and a
+/// cross.
+fn shape_atlas_image() -> Image {
+    let (width, height) = (CELL_SIZE * SHAPE_COUNT, CELL_SIZE);
+    let mut pixels = Vec::with_capacity((width * height * 4) as usize);
+    for y in 0..height {
+        for x in 0..width {
+            let shape = x / CELL_SIZE;
+            // -1.0 to 1.0 across the cell, at the pixel's center.
+            let p = (glam::Vec2::new((x % CELL_SIZE) as f32, y as f32) + 0.5) / CELL_SIZE as f32
+                * 2.0
+                - 1.0;
+            let inside = match shape {
+                0 => p.length() < 0.9,
+                1 => p.x.abs() + p.y.abs() < 0.9,
+                2 => (0.6..0.9).contains(&p.length()),
+                _ => p.x.abs().min(p.y.abs()) < 0.25 && p.max_element() < 0.9,
+            };
+            let alpha = if inside { 255 } else { 0 };
+            pixels.extend_from_slice(&[255, 255, 255, alpha]);
+        }
+    }
+    Image::new(width, height, pixels)
+}
+
+/// A dark gray and white checkerboard, 4 squares across.
+fn tile_image() -> Image {
+    let size = 8;
+    let pixels = (0..size * size)
+        .flat_map(|i| {
+            let (x, y) = (i % size, i / size);
+            let value = if (x / 2 + y / 2) % 2 == 0 { 255 } else { 70 };
+            [value, value, value, 255]
+        })
+        .collect();
+    Image::new(size, size, pixels)
+}
+
+/// A sprite's motion. Where it is at any time is worked out from this, so frames rendered at
+/// the same time match however they got there.
+struct Drifter {
+    sprite: Sprite,
+    texture: TextureId,
+    velocity: glam::Vec2,
+    /// Radians per second.
+    spin: f32,
+}
+
+impl Drifter {
+    fn at(&self, time: f32) -> Sprite {
+        let half = WORLD_SIZE / 2.0;
+        let position = (self.sprite.position + self.velocity * time + half).rem_euclid(WORLD_SIZE);
+        Sprite {
+            position: position - half,
+            rotation: self.sprite.rotation + self.spin * time,
+            ..self.sprite
+        }
+    }
+}
+
+fn random_position(rng: &mut impl Rng) -> glam::Vec2 {
+    (glam::Vec2::new(rng.gen(), rng.gen()) - 0.5) * WORLD_SIZE
+}
+
+fn shape(rng: &mut impl Rng, atlas: &TextureAtlas, texture: TextureId) -> Drifter {
+    let mut sprite = Sprite::new(
+        random_position(rng),
+        glam::Vec2::splat(rng.gen_range(4.0..12.0)),
+    );
+    sprite.rotation = rng.gen_range(0.0..std::f32::consts::TAU);
+    sprite.uv_rect = atlas.uv_rect(rng.gen_range(0..atlas.len()));
+    sprite.tint = glam::Vec4::new(rng.gen(), rng.gen(), rng.gen(), 1.0);
+    sprite.flip_x = rng.gen();
+    sprite.layer = 1;
+    Drifter {
+        sprite,
+        texture,
+        velocity: glam::Vec2::new(rng.gen_range(-40.0..40.0), rng.gen_range(-40.0..40.0)),
+        spin: rng.gen_range(-2.0..2.0),
+    }
+}
+
+/// Big, slow, and see-through over the shapes.
+fn tile(rng: &mut impl Rng, texture: TextureId, layer: i32) -> Drifter {
+    let mut sprite = Sprite::new(
+        random_position(rng),
+        glam::Vec2::splat(rng.gen_range(40.0..80.0)),
+    );
+    sprite.rotation = rng.gen_range(0.0..std::f32::consts::TAU);
+    // Around a corner rather than the center, to show off `origin`.
+    sprite.origin = glam::Vec2::ZERO;
+    sprite.layer = layer;
+    if layer > 1 {
+        sprite.tint = glam::Vec4::new(1.0, 1.0, 1.0, 0.4);
+    }
+    Drifter {
+        sprite,
+        texture,
+        velocity: glam::Vec2::new(rng.gen_range(-10.0..10.0), rng.gen_range(-10.0..10.0)),
+        spin: rng.gen_range(-0.3..0.3),
+    }
+}
+
+/// The whole world fits the window's height.
+fn camera(viewport_size: (u32, u32)) -> Camera2D {
+    let mut camera = Camera2D::new(viewport_size);
+    camera.zoom = camera.viewport_size().y / WORLD_SIZE.y;
+    camera
+}
+
+struct WgpuStuff {
+    context: wgpu_experiments::context::RenderContext,
+    sprite_batch: SpriteBatch,
+    drifters: Vec<Drifter>,
+    camera: Camera2D,
+    previous_simulation_time: std::time::Duration,
+    simulation_time: std::time::Duration,
+    reported_draw_calls: bool,
+}
+
+impl WgpuStuff {
+    fn new(context: wgpu_experiments::context::RenderContext, mut rng: impl Rng) -> Self {
+        let device = &context.device;
+        let mut sprite_batch = SpriteBatch::new(device, context.format(), 1);
+        let shape_image = shape_atlas_image();
+        let atlas = TextureAtlas::grid(
+            (shape_image.width, shape_image.height),
+            (CELL_SIZE, CELL_SIZE),
+        )
+        .unwrap();
+        let shapes = sprite_batch.add_texture(
+            device,
+            &Texture::from_image(
+                device,
+                &context.queue,
+                "shape atlas",
+                &shape_image,
+                &TextureOptions::default(),
+            ),
+        );
+        let tiles = sprite_batch.add_texture(
+            device,
+            &Texture::from_image(
+                device,
+                &context.queue,
+                "tile",
+                &tile_image(),
+                &TextureOptions {
+                    mipmaps: false,
+                    filter: wgpu::FilterMode::Nearest,
+                    ..Default::default()
+                },
+            ),
+        );
+        let mut drifters: Vec<Drifter> = (0..TILE_COUNTS.0)
+            .map(|_| tile(&mut rng, tiles, 0))
+            .collect();
+        drifters.extend((0..SPRITE_COUNT).map(|_| shape(&mut rng, &atlas, shapes)));
+        drifters.extend((0..TILE_COUNTS.1).map(|_| tile(&mut rng, tiles, 2)));
+        let camera = camera(context.size());
+        WgpuStuff {
+            context,
+            sprite_batch,
+            drifters,
+            camera,
+            previous_simulation_time: std::time::Duration::ZERO,
+            simulation_time: std::time::Duration::ZERO,
+            reported_draw_calls: false,
+        }
+    }
+}
+
+impl Game for WgpuStuff {
+    fn update(&mut self, dt: std::time::Duration) {
+        self.previous_simulation_time = self.simulation_time;
+        self.simulation_time += dt;
+    }
+
+    fn render(&mut self, alpha: f32) {
+        let Some(frame) = self.context.acquire() else {
+            return;
+        };
+        let time = self.previous_simulation_time.as_secs_f32() * (1.0 - alpha)
+            + self.simulation_time.as_secs_f32() * alpha;
+        for drifter in &self.drifters {
+            self.sprite_batch.draw(drifter.texture, &drifter.at(time));
+        }
+        self.sprite_batch.prepare(
+            &self.context.device,
+            &self.context.queue,
+            &self.camera.uniform(),
+        );
+        if !self.reported_draw_calls {
+            let stats = self.sprite_batch.stats();
+            println!(
+                "{} sprites in {} draw calls",
+                stats.sprites, stats.draw_calls
+            );
+            self.reported_draw_calls = true;
+        }
+        let mut command_encoder = self
+            .context
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        {
+            let mut render_pass = command_encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: None,
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: frame.view(),
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color {
+                            r: 0.3,
+                            g: 0.3,
+                            b: 0.3,
+                            a: 1.0,
+                        }),
+                        store: true,
+                    },
+                })],
+                depth_stencil_attachment: None,
+            });
+            self.sprite_batch.render(&mut render_pass);
+        }
+        self.context.queue.submit([command_encoder.finish()]);
+        frame.present();
+    }
+
+    fn resize(&mut self, new_size: winit::dpi::PhysicalSize<u32>) {
+        self.context.resize(new_size);
+        self.camera = camera((new_size.width, new_size.height));
+    }
+
+    fn render_context(&self) -> Option<&wgpu_experiments::context::RenderContext> {
+        Some(&self.context)
+    }
+}
+
+fn main() {
+    let rng = wgpu_experiments::random::rng_from_args();
+    wgpu_experiments::game::run(|context| WgpuStuff::new(context, rng));
+}
@@ -0,0 +1,176 @@
+//! GPU buffers rewritten every frame, without creating a new buffer every frame.
+
+/// One buffer of `T`s, overwritten with `queue.write_buffer` and replaced only when the data
+/// outgrows it.
+///
+/// The buffer grows to the next power of two elements, so data that grows slowly doesn't
+/// reallocate every frame, and it never shrinks.
+pub struct DynamicBuffer<T: bytemuck::Pod> {
+    label: String,
+    usage: wgpu::BufferUsages,
+    buffer: wgpu::Buffer,
+    len: usize,
+    _elements: std::marker::PhantomData<T>,
+}
+
+impl<T: bytemuck::Pod> DynamicBuffer<T> {
+    /// `usage` is e.g. `VERTEX`. `COPY_DST` is added, for the writes.
+    pub fn new(
+        device: &wgpu::Device,
+        label: &str,
+        usage: wgpu::BufferUsages,
+        capacity: usize,
+    ) -> Self {
+        let usage = usage | wgpu::BufferUsages::COPY_DST;
+        Self {
+            buffer: Self::create_buffer(device, label, usage, capacity.max(1)),
+            label: label.to_string(),
+            usage,
+            len: 0,
+            _elements: std::marker::PhantomData,
+        }
+    }
+
+    fn create_buffer(
+        device: &wgpu::Device,
+        label: &str,
+        usage: wgpu::BufferUsages,
+        capacity: usize,
+    ) -> wgpu::Buffer {
+        let size = (capacity * std::mem::size_of::<T>()) as u64;
+        crate::logging::create_buffer(
+            device,
+            &wgpu::BufferDescriptor {
+                label: Some(label),
+                // Writes are in multiples of 4 bytes, see `write`.
+                size: crate::wgsl::round_up(wgpu::COPY_BUFFER_ALIGNMENT, size),
+                usage,
+                mapped_at_creation: false,
+            },
+        )
+    }
+
+    /// Replaces the contents with `data`, growing the buffer first if it's too small.
+    ///
+    /// Returns whether the buffer was replaced, so bind groups using it need recreating.
+    pub fn write(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, data: &[T]) -> bool {
+        let grown = data.len() > self.capacity();
+        if grown {
+            let capacity = data.len().next_power_of_two();
+            self.buffer = Self::create_buffer(device, &self.label, self.usage, capacity);
+        }
+        self.len = data.len();
+        let bytes: &[u8] = bytemuck::cast_slice(data);
+        // write_buffer only takes multiples of 4 bytes, so pad the last few, e.g. of an odd
+        // number of u16 indices, without allocating.
+        let aligned = bytes.len() - bytes.len() % wgpu::COPY_BUFFER_ALIGNMENT as usize;
+        if aligned > 0 {
+            queue.write_buffer(&self.buffer, 0, &bytes[..aligned]);
+        }
+        if aligned < bytes.len() {
+            let mut tail = [0; wgpu::COPY_BUFFER_ALIGNMENT as usize];
+            tail[..bytes.len() - aligned].copy_from_slice(&bytes[aligned..]);
+            queue.write_buffer(&self.buffer, aligned as u64, &tail);
+        }
+        grown
+    }
+
+    /// How many elements the last `write` wrote.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// How many elements fit before `write` has to replace the buffer.
+    pub fn capacity(&self) -> usize {
+        self.buffer.size() as usize / std::mem::size_of::<T>()
+    }
+
+    /// The whole buffer, e.g. for bind groups.
+    pub fn buffer(&self) -> &wgpu::Buffer {
+        &self.buffer
+    }
+
+    /// The elements the last `write` wrote, for `set_vertex_buffer` and `set_index_buffer`.
+    ///
+    /// Panics if that was none, since wgpu has no empty slices. Skip the draw instead.
+    pub fn slice(&self) -> wgpu::BufferSlice<'_> {
+        self.buffer
+            .slice(..(self.len * std::mem::size_of::<T>()) as u64)
+    }
+}
+
+/// Many `T`s in one uniform buffer, each bound in turn with a dynamic offset.
+///
+/// Each is placed at a multiple of `min_uniform_buffer_offset_alignment`, so one bind group
+/// with `has_dynamic_offset`, see `reflect::ShaderLayout::dynamic_offset`, serves them all:
+/// `render_pass.set_bind_group(0, &bind_group, &[offset])` per draw.
+pub struct UniformArena<T: crate::wgsl::WgslLayout> {
+    stride: u64,
+    bytes: Vec<u8>,
+    buffer: DynamicBuffer<u8>,
+    _objects: std::marker::PhantomData<T>,
+}
+
+impl<T: crate::wgsl::WgslLayout> UniformArena<T> {
+    pub fn new(device: &wgpu::Device, label: &str, capacity: usize) -> Self {
+        let alignment = device.limits().min_uniform_buffer_offset_alignment as u64;
+        let stride = crate::wgsl::round_up(alignment, T::SIZE);
+        Self {
+            stride,
+            bytes: Vec::new(),
+            // Room for one at least, so `binding` always fits in the buffer.
+            buffer: DynamicBuffer::new(
+                device,
+                label,
+                wgpu::BufferUsages::UNIFORM,
+                capacity.max(1) * stride as usize,
+            ),
+            _objects: std::marker::PhantomData,
+        }
+    }
+
+    /// Adds `value`, returning its dynamic offset. It's on the GPU after the next `write`.
+    pub fn push(&mut self, value: &T) -> u32 {
+        let offset = self.bytes.len();
+        value.write_to(&mut self.bytes);
+        crate::wgsl::pad(&mut self.bytes, offset + self.stride as usize);
+        offset as u32
+    }
+
+    /// Removes everything, e.g. to push this frame's objects. Offsets start from 0 again.
+    pub fn clear(&mut self) {
+        self.bytes.clear();
+    }
+
+    /// Uploads everything pushed. Returns whether the buffer was replaced, so the bind group
+    /// needs recreating with `binding`.
+    pub fn write(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) -> bool {
+        self.buffer.write(device, queue, &self.bytes)
+    }
+
+    pub fn len(&self) -> usize {
+        self.bytes.len() / self.stride as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    /// The distance between offsets: `T::SIZE` rounded up to the offset alignment.
+    pub fn stride(&self) -> u64 {
+        self.stride
+    }
+
+    /// One `T`, for a bind group entry with a dynamic offset.
+    pub fn binding(&self) -> wgpu::BufferBinding<'_> {
+        wgpu::BufferBinding {
+            buffer: self.buffer.buffer(),
+            offset: 0,
+            size: std::num::NonZeroU64::new(T::SIZE),
+        }
+    }
+}
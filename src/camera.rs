@@ -0,0 +1,114 @@
+//! A 2D camera, so things can be placed in world units instead of clip space.
+//!
+//! World space has y pointing up. Screen space is physical window pixels with the origin at the
+//! top left and y pointing down, like `InputState::cursor_position`.
+//!
+//! Shaders take the camera as a `CameraUniform`, declared for them by `Prelude`:
+//!
+//! ```wgsl
+//! @group(0) @binding(0) var<uniform> camera: CameraUniform;
+//!
+//! @vertex
+//! fn vertex_main(@location(0) position: vec2f) -> @builtin(position) vec4f {
+//!     return camera.view_projection * vec4f(position, 0.0, 1.0);
+//! }
+//! ```
+
+/// What a shader needs of a `Camera2D`.
+#[derive(Debug, Clone, Copy, PartialEq, crate::wgsl::WgslLayout)]
+pub struct CameraUniform {
+    /// World to clip space.
+    pub view_projection: glam::Mat4,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Camera2D {
+    /// The world position at the center of the viewport.
+    pub position: glam::Vec2,
+    /// Screen pixels per world unit.
+    pub zoom: f32,
+    /// Counter-clockwise, in radians. Turning the camera turns the world the other way.
+    pub rotation: f32,
+    viewport_size: glam::Vec2,
+}
+
+impl Camera2D {
+    /// Centered on the origin, at one pixel per world unit.
+    pub fn new(viewport_size: (u32, u32)) -> Self {
+        let mut camera = Self {
+            position: glam::Vec2::ZERO,
+            zoom: 1.0,
+            rotation: 0.0,
+            viewport_size: glam::Vec2::ONE,
+        };
+        camera.resize(viewport_size);
+        camera
+    }
+
+    /// Call when the window is resized. Zoom is in pixels, so a bigger window shows more world
+    /// rather than stretching it.
+    pub fn resize(&mut self, viewport_size: (u32, u32)) {
+        // A minimized window is 0x0, which would divide by zero.
+        self.viewport_size =
+            glam::Vec2::new(viewport_size.0 as f32, viewport_size.1 as f32).max(glam::Vec2::ONE);
+    }
+
+    /// In pixels.
+    pub fn viewport_size(&self) -> glam::Vec2 {
+        self.viewport_size
+    }
+
+    /// World space to camera space: the camera's position at the origin, unrotated.
+    pub fn view(&self) -> glam::Mat4 {
+        glam::Mat4::from_rotation_z(-self.rotation)
+            * glam::Mat4::from_translation(-self.position.extend(0.0))
+    }
+
+    /// Camera space to clip space. Scaling x and y separately keeps the world's aspect ratio
+    /// whatever the viewport's is.
+    pub fn projection(&self) -> glam::Mat4 {
+        let scale = 2.0 * self.zoom / self.viewport_size;
+        glam::Mat4::from_scale(scale.extend(1.0))
+    }
+
+    pub fn view_projection(&self) -> glam::Mat4 {
+        self.projection() * self.view()
+    }
+
+    pub fn uniform(&self) -> CameraUniform {
+        CameraUniform {
+            view_projection: self.view_projection(),
+        }
+    }
+
+    pub fn world_to_screen(&self, world_position: glam::Vec2) -> glam::Vec2 {
+        let clip = self
+            .view_projection()
+            .project_point3(world_position.extend(0.0))
+            .truncate();
+        (clip * glam::Vec2::new(0.5, -0.5) + 0.5) * self.viewport_size
+    }
+
+    pub fn screen_to_world(&self, screen_position: glam::Vec2) -> glam::Vec2 {
+        let clip = (screen_position / self.viewport_size - 0.5) * glam::Vec2::new(2.0, -2.0);
+        self.view_projection()
+            .inverse()
+            .project_point3(clip.extend(0.0))
+            .truncate()
+    }
+
+    /// Moves the camera so the world follows a cursor that moved `screen_delta` pixels, as when
+    /// dragging it.
+    pub fn pan(&mut self, screen_delta: glam::Vec2) {
+        let camera_delta = glam::Vec2::new(screen_delta.x, -screen_delta.y) / self.zoom;
+        self.position -= glam::Vec2::from_angle(self.rotation).rotate(camera_delta);
+    }
+
+    /// Multiplies the zoom by `factor`, keeping the world position under `screen_position`
+    /// where it is, so zooming with the scroll wheel zooms in on the cursor.
+    pub fn zoom_at(&mut self, screen_position: glam::Vec2, factor: f32) {
+        let before = self.screen_to_world(screen_position);
+        self.zoom *= factor;
+        self.position += before - self.screen_to_world(screen_position);
+    }
+}
@@ -0,0 +1,102 @@
+//! A fixed, low resolution canvas, scaled up to fill the window without distorting it.
+//!
+//! The canvas is scaled by the largest integer factor that fits, so every canvas pixel
+//! covers the same number of window pixels. The leftover space becomes bars on the
+//! sides (pillarbox) or top and bottom (letterbox).
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CanvasLayout {
+    pub canvas_size: (u32, u32),
+    pub window_size: (u32, u32),
+    /// Window pixels per canvas pixel.
+    ///
+    /// Always a whole number, unless the window is smaller than the canvas.
+    /// Then we shrink the canvas to fit, because a scale of 0 would show nothing.
+    pub scale: f32,
+    /// Top left corner of the canvas, in window pixels.
+    pub offset: glam::Vec2,
+}
+
+impl CanvasLayout {
+    pub fn new(canvas_size: (u32, u32), window_size: (u32, u32)) -> Self {
+        assert!(canvas_size.0 > 0 && canvas_size.1 > 0);
+        let fit = f32::min(
+            window_size.0 as f32 / canvas_size.0 as f32,
+            window_size.1 as f32 / canvas_size.1 as f32,
+        );
+        let scale = if fit >= 1.0 { fit.floor() } else { fit };
+        let scaled_size = glam::Vec2::new(canvas_size.0 as f32, canvas_size.1 as f32) * scale;
+        let window = glam::Vec2::new(window_size.0 as f32, window_size.1 as f32);
+        // Round so canvas pixels line up with window pixels.
+        let offset = ((window - scaled_size) / 2.0).floor();
+        Self {
+            canvas_size,
+            window_size,
+            scale,
+            offset,
+        }
+    }
+
+    /// The canvas's size in window pixels.
+    pub fn scaled_size(&self) -> glam::Vec2 {
+        glam::Vec2::new(self.canvas_size.0 as f32, self.canvas_size.1 as f32) * self.scale
+    }
+
+    /// The (x, y, width, height) to pass to `RenderPass::set_viewport` when drawing the canvas.
+    pub fn viewport(&self) -> (f32, f32, f32, f32) {
+        let size = self.scaled_size();
+        (self.offset.x, self.offset.y, size.x, size.y)
+    }
+
+    /// Converts window pixels, like a cursor position, to canvas pixels.
+    ///
+    /// Returns `None` if the position is in the bars outside the canvas.
+    pub fn window_to_canvas(&self, window_position: glam::Vec2) -> Option<glam::Vec2> {
+        let canvas_position = (window_position - self.offset) / self.scale;
+        let canvas_size = glam::Vec2::new(self.canvas_size.0 as f32, self.canvas_size.1 as f32);
+        if canvas_position.cmpge(glam::Vec2::ZERO).all() && canvas_position.cmplt(canvas_size).all()
+        {
+            Some(canvas_position)
+        } else {
+            None
+        }
+    }
+
+    pub fn canvas_to_window(&self, canvas_position: glam::Vec2) -> glam::Vec2 {
+        canvas_position * self.scale + self.offset
+    }
+}
+
+/// A canvas size and bar color, plus the layout for the current window size.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct VirtualCanvas {
+    bar_color: wgpu::Color,
+    layout: CanvasLayout,
+}
+
+impl VirtualCanvas {
+    pub fn new(canvas_size: (u32, u32), bar_color: wgpu::Color, window_size: (u32, u32)) -> Self {
+        Self {
+            bar_color,
+            layout: CanvasLayout::new(canvas_size, window_size),
+        }
+    }
+
+    /// Call on `WindowEvent::Resized`.
+    pub fn resize(&mut self, window_size: (u32, u32)) {
+        self.layout = CanvasLayout::new(self.layout.canvas_size, window_size);
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        self.layout.canvas_size
+    }
+
+    /// Clear the window with this before drawing the canvas, to color the bars.
+    pub fn bar_color(&self) -> wgpu::Color {
+        self.bar_color
+    }
+
+    pub fn layout(&self) -> &CanvasLayout {
+        &self.layout
+    }
+}
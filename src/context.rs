@@ -0,0 +1,254 @@
+//! A device and queue, plus where frames go: a window's surface, or an offscreen texture.
+//!
+//! Demos render through `RenderContext::acquire`, so the same render code runs in a window
+//! or headless on a machine without a display.
+
+use crate::image::Image;
+use crate::surface::{RenderTarget, SurfaceManager};
+
+/// The size of headless frames, matching winit's default window size.
+pub const DEFAULT_HEADLESS_SIZE: (u32, u32) = (800, 600);
+
+pub enum RenderOutput {
+    Window(SurfaceManager),
+    /// Created with `COPY_SRC` usage, so it can be read back with `RenderContext::read_pixels`.
+    Offscreen(RenderTarget),
+}
+
+/// The texture to draw the current frame to. Call `present` once it's submitted.
+pub struct OutputFrame {
+    surface_texture: Option<wgpu::SurfaceTexture>,
+    view: wgpu::TextureView,
+}
+
+impl OutputFrame {
+    pub fn view(&self) -> &wgpu::TextureView {
+        &self.view
+    }
+
+    /// Shows the frame in the window. Offscreen frames stay in their texture.
+    pub fn present(self) {
+        if let Some(surface_texture) = self.surface_texture {
+            surface_texture.present();
+        }
+    }
+}
+
+pub struct RenderContext {
+    pub device: wgpu::Device,
+    pub queue: wgpu::Queue,
+    /// Which adapter the device is on, e.g. for benchmark reports.
+    pub adapter_info: wgpu::AdapterInfo,
+    pub output: RenderOutput,
+}
+
+impl RenderContext {
+    /// Renders to `window`, in the surface's preferred format.
+    pub fn windowed(window: winit::window::Window) -> Self {
+        let instance = crate::logging::create_instance(wgpu::InstanceDescriptor::default());
+        // create_surface is unsafe because the window must outlive the surface.
+        // SurfaceManager keeps them together.
+        let surface = unsafe { instance.create_surface(&window) }.unwrap();
+        let adapter = crate::logging::request_adapter(
+            &instance,
+            &wgpu::RequestAdapterOptions {
+                power_preference: wgpu::PowerPreference::None,
+                force_fallback_adapter: false,
+                compatible_surface: Some(&surface),
+            },
+        )
+        .expect("No GPU adapter can present to this window");
+        let (device, queue) =
+            crate::logging::request_device(&adapter, &wgpu::DeviceDescriptor::default()).unwrap();
+        let format = surface.get_capabilities(&adapter).formats[0];
+        let surface = SurfaceManager::new(window, surface, &device, format);
+        Self {
+            device,
+            queue,
+            adapter_info: adapter.get_info(),
+            output: RenderOutput::Window(surface),
+        }
+    }
+
+    /// Renders to an RGBA texture of `size`.
+    ///
+    /// Uses the software fallback adapter if there's no GPU, so this works on CI machines.
+    pub fn offscreen(size: (u32, u32)) -> Self {
+        Self::offscreen_with_features(size, wgpu::Features::empty())
+    }
+
+    /// Like `offscreen`, also enabling whichever of `optional_features` the adapter has,
+    /// e.g. `TIMESTAMP_QUERY`. Check `device.features()` for which those were.
+    pub fn offscreen_with_features(size: (u32, u32), optional_features: wgpu::Features) -> Self {
+        let instance = crate::logging::create_instance(wgpu::InstanceDescriptor::default());
+        let adapter =
+            crate::logging::request_adapter(&instance, &wgpu::RequestAdapterOptions::default())
+                .or_else(|| {
+                    log::warn!("No GPU adapter, trying the fallback adapter");
+                    crate::logging::request_adapter(
+                        &instance,
+                        &wgpu::RequestAdapterOptions {
+                            force_fallback_adapter: true,
+                            ..Default::default()
+                        },
+                    )
+                })
+                .expect("No GPU adapter, and no fallback adapter either");
+        let (device, queue) = crate::logging::request_device(
+            &adapter,
+            &wgpu::DeviceDescriptor {
+                features: optional_features & adapter.features(),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let target = RenderTarget::new(
+            &device,
+            "offscreen output",
+            size,
+            wgpu::TextureFormat::Rgba8UnormSrgb,
+            1,
+            wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+        );
+        Self {
+            device,
+            queue,
+            adapter_info: adapter.get_info(),
+            output: RenderOutput::Offscreen(target),
+        }
+    }
+
+    /// The format of the textures `acquire` returns. Pipelines drawing to them must match.
+    pub fn format(&self) -> wgpu::TextureFormat {
+        match &self.output {
+            RenderOutput::Window(surface) => surface.format(),
+            RenderOutput::Offscreen(target) => target.format(),
+        }
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        match &self.output {
+            RenderOutput::Window(surface) => surface.size(),
+            RenderOutput::Offscreen(target) => target.size(),
+        }
+    }
+
+    pub fn window(&self) -> Option<&winit::window::Window> {
+        match &self.output {
+            RenderOutput::Window(surface) => Some(surface.window()),
+            RenderOutput::Offscreen(_) => None,
+        }
+    }
+
+    /// The texture to draw this frame to, or `None` if this frame should be skipped.
+    ///
+    /// See `SurfaceManager::acquire`.
+    pub fn acquire(&mut self) -> Option<OutputFrame> {
+        match &mut self.output {
+            RenderOutput::Window(surface) => {
+                let surface_texture = surface.acquire(&self.device)?;
+                let view = surface_texture
+                    .texture
+                    .create_view(&wgpu::TextureViewDescriptor::default());
+                Some(OutputFrame {
+                    surface_texture: Some(surface_texture),
+                    view,
+                })
+            }
+            RenderOutput::Offscreen(target) => Some(OutputFrame {
+                surface_texture: None,
+                view: target
+                    .texture()
+                    .create_view(&wgpu::TextureViewDescriptor::default()),
+            }),
+        }
+    }
+
+    /// Call on `WindowEvent::Resized`. Offscreen outputs keep their size.
+    pub fn resize(&mut self, size: winit::dpi::PhysicalSize<u32>) {
+        if let RenderOutput::Window(surface) = &mut self.output {
+            surface.resize(&self.device, size);
+        }
+    }
+
+    /// Copies the offscreen texture back from the GPU, waiting for rendering to finish.
+    ///
+    /// Returns `None` for windows, since surface textures can't be copied from.
+    pub fn read_pixels(&self) -> Option<Image> {
+        let RenderOutput::Offscreen(target) = &self.output else {
+            return None;
+        };
+        Some(read_texture(&self.device, &self.queue, target.texture()))
+    }
+}
+
+/// Reads back a 2D RGBA8 or BGRA8 texture with `COPY_SRC` usage, as RGBA.
+pub fn read_texture(device: &wgpu::Device, queue: &wgpu::Queue, texture: &wgpu::Texture) -> Image {
+    read_texture_level(device, queue, texture, 0)
+}
+
+/// Like `read_texture`, but reads mip level `mip_level`, which is smaller.
+pub fn read_texture_level(
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    texture: &wgpu::Texture,
+    mip_level: u32,
+) -> Image {
+    let size = texture
+        .size()
+        .mip_level_size(mip_level, texture.dimension());
+    let (width, height) = (size.width, size.height);
+    let swap_red_and_blue = match texture.format() {
+        wgpu::TextureFormat::Rgba8Unorm | wgpu::TextureFormat::Rgba8UnormSrgb => false,
+        wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb => true,
+        format => panic!("Can't read back a {:?} texture", format),
+    };
+    // Rows in the buffer must be aligned, so they may be padded.
+    let unpadded_bytes_per_row = width * 4;
+    let padded_bytes_per_row =
+        unpadded_bytes_per_row.next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT);
+    let buffer = crate::logging::create_buffer(
+        device,
+        &wgpu::BufferDescriptor {
+            label: Some("read back buffer"),
+            size: padded_bytes_per_row as u64 * height as u64,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        },
+    );
+    let mut command_encoder =
+        device.create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+    command_encoder.copy_texture_to_buffer(
+        wgpu::ImageCopyTexture {
+            mip_level,
+            ..texture.as_image_copy()
+        },
+        wgpu::ImageCopyBuffer {
+            buffer: &buffer,
+            layout: wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(padded_bytes_per_row),
+                rows_per_image: Some(height),
+            },
+        },
+        size,
+    );
+    queue.submit([command_encoder.finish()]);
+    let slice = buffer.slice(..);
+    slice.map_async(wgpu::MapMode::Read, |result| result.unwrap());
+    device.poll(wgpu::Maintain::Wait);
+    let mut pixels = Vec::with_capacity((unpadded_bytes_per_row * height) as usize);
+    for row in slice
+        .get_mapped_range()
+        .chunks_exact(padded_bytes_per_row as usize)
+    {
+        pixels.extend_from_slice(&row[..unpadded_bytes_per_row as usize]);
+    }
+    buffer.unmap();
+    if swap_red_and_blue {
+        for pixel in pixels.chunks_exact_mut(4) {
+            pixel.swap(0, 2);
+        }
+    }
+    Image::new(width, height, pixels)
+}
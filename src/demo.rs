@@ -0,0 +1,169 @@
+//! Types and data the demos in src/bin share, with each other and with tests/shaders.rs, which
+//! checks the vertex layouts and structs here against the demos' shaders.
+
+/// An object's color and transform, declared in storage.wgsl and uniforms.wgsl by
+/// `shader_prelude`.
+#[derive(PartialEq, Debug, Clone, Copy, crate::wgsl::WgslLayout)]
+pub struct OurStruct {
+    pub color: glam::Vec3,
+    pub scale: glam::Vec2,
+    pub offset: glam::Vec2,
+}
+
+impl From<&crate::scene::SceneObject> for OurStruct {
+    fn from(object: &crate::scene::SceneObject) -> Self {
+        Self {
+            color: object.color,
+            scale: object.scale,
+            offset: object.offset,
+        }
+    }
+}
+
+/// Declares `OurStruct`, for storage.wgsl and uniforms.wgsl.
+pub fn shader_prelude() -> String {
+    crate::wgsl::Prelude::new().with::<OurStruct>().to_wgsl()
+}
+
+pub mod camera {
+    use crate::vertex::VertexLayout;
+
+    /// One square of the grid, an instance for `vertex_main`.
+    #[repr(C)]
+    #[derive(PartialEq, Debug, Clone, Copy, bytemuck::Zeroable, bytemuck::Pod, VertexLayout)]
+    #[vertex(step_mode = Instance)]
+    pub struct Tile {
+        #[vertex(location = 1)]
+        pub position: glam::Vec2,
+        pub color: glam::Vec3,
+    }
+
+    /// What `vertex_main` in camera.wgsl takes: the corners, and a `Tile` per tile.
+    pub const VERTEX_BUFFERS: &[wgpu::VertexBufferLayout] = &[
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<glam::Vec2>() as u64,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &wgpu::vertex_attr_array![0 => Float32x2],
+        },
+        Tile::LAYOUT,
+    ];
+
+    /// Declares `CameraUniform`.
+    pub fn shader_prelude() -> String {
+        crate::wgsl::Prelude::new()
+            .with::<crate::camera::CameraUniform>()
+            .to_wgsl()
+    }
+}
+
+pub mod rotating_msaa_triangles {
+    use crate::vertex::VertexLayout;
+
+    /// For `vertex_main`.
+    #[repr(C)]
+    #[derive(Clone, Copy, bytemuck::Zeroable, bytemuck::Pod, VertexLayout)]
+    pub struct Vertex {
+        pub position: glam::Vec2,
+        pub color: glam::Vec3,
+    }
+
+    /// For `texture_to_texture_vertex_main`.
+    #[repr(C)]
+    #[derive(Clone, Copy, bytemuck::Zeroable, bytemuck::Pod, VertexLayout)]
+    pub struct TextureVertex {
+        pub position: glam::Vec2,
+        pub uv: glam::Vec2,
+    }
+}
+
+pub mod storage {
+    /// The two triangles of a square around the origin, which storage.wgsl indexes into.
+    pub fn square() -> Vec<glam::Vec2> {
+        vec![
+            glam::Vec2::new(-0.5, 0.5),
+            glam::Vec2::new(-0.5, -0.5),
+            glam::Vec2::new(0.5, 0.5),
+            glam::Vec2::new(0.5, -0.5),
+            glam::Vec2::new(0.5, 0.5),
+            glam::Vec2::new(-0.5, -0.5),
+        ]
+    }
+}
+
+pub mod vertex {
+    use crate::vertex::VertexLayout;
+
+    #[repr(C)]
+    #[derive(PartialEq, Debug, Clone, Copy, bytemuck::Zeroable, bytemuck::Pod)]
+    pub struct Vec4_u8 {
+        pub x: u8,
+        pub y: u8,
+        pub z: u8,
+        pub w: u8,
+    }
+
+    impl Vec4_u8 {
+        pub fn new(x: u8, y: u8, z: u8) -> Self {
+            Self { x, y, z, w: 0 }
+        }
+    }
+
+    #[repr(C)]
+    #[derive(PartialEq, Debug, Clone, Copy, bytemuck::Zeroable, bytemuck::Pod, VertexLayout)]
+    #[vertex(step_mode = Instance)]
+    pub struct Transform {
+        #[vertex(location = 2)]
+        pub scale: glam::Vec2,
+        pub offset: glam::Vec2,
+    }
+
+    impl From<&crate::scene::SceneObject> for Transform {
+        fn from(object: &crate::scene::SceneObject) -> Self {
+            Self {
+                scale: object.scale,
+                offset: object.offset,
+            }
+        }
+    }
+
+    #[repr(C)]
+    #[derive(PartialEq, Debug, Clone, Copy, bytemuck::Zeroable, bytemuck::Pod, VertexLayout)]
+    pub struct Vertex {
+        pub position: glam::Vec2,
+        #[vertex(format = Unorm8x4)]
+        pub color: Vec4_u8,
+    }
+
+    pub const VERTS_IN_SQUARE: u32 = 4;
+    pub const INDICES_IN_SQUARE: u32 = 6;
+
+    /// A square around the origin, with a different color at each corner.
+    pub fn square() -> (Vec<Vertex>, Vec<u32>) {
+        let vertices = vec![
+            Vertex {
+                position: glam::Vec2::new(-0.5, 0.5),
+                color: Vec4_u8::new(255, 0, 0),
+            },
+            Vertex {
+                position: glam::Vec2::new(-0.5, -0.5),
+                color: Vec4_u8::new(0, 255, 0),
+            },
+            Vertex {
+                position: glam::Vec2::new(0.5, -0.5),
+                color: Vec4_u8::new(255, 255, 255),
+            },
+            Vertex {
+                position: glam::Vec2::new(0.5, 0.5),
+                color: Vec4_u8::new(0, 0, 255),
+            },
+        ];
+        let indices = vec![0, 1, 3, 2, 3, 1];
+        assert_eq!(vertices.len(), VERTS_IN_SQUARE as usize);
+        assert_eq!(indices.len(), INDICES_IN_SQUARE as usize);
+        (vertices, indices)
+    }
+
+    /// What `vertex_main` in vertex.wgsl takes: a square's vertices, and a transform per
+    /// instance.
+    pub const VERTEX_BUFFERS: &[wgpu::VertexBufferLayout] = &[Vertex::LAYOUT, Transform::LAYOUT];
+}
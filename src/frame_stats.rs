@@ -0,0 +1,257 @@
+//! Frame time statistics over a window of recent frames, and ways to report them.
+
+use std::io::Write;
+use std::time::Duration;
+
+/// Remembers the last `capacity` frame times in a ring buffer.
+#[derive(Debug, Clone)]
+pub struct FrameStats {
+    frame_times: Vec<Duration>,
+    /// Where the next frame time goes once the buffer is full.
+    next: usize,
+    capacity: usize,
+    ema_seconds: Option<f64>,
+    ema_weight: f64,
+    total_frames: u64,
+}
+
+impl FrameStats {
+    /// `ema_weight` is how much each new frame moves the exponential moving average, in 0.0..=1.0.
+    pub fn new(capacity: usize, ema_weight: f64) -> Self {
+        assert!(capacity > 0);
+        assert!((0.0..=1.0).contains(&ema_weight));
+        Self {
+            frame_times: Vec::with_capacity(capacity),
+            next: 0,
+            capacity,
+            ema_seconds: None,
+            ema_weight,
+            total_frames: 0,
+        }
+    }
+
+    pub fn record(&mut self, frame_time: Duration) {
+        if self.frame_times.len() < self.capacity {
+            self.frame_times.push(frame_time);
+        } else {
+            self.frame_times[self.next] = frame_time;
+            self.next = (self.next + 1) % self.capacity;
+        }
+        let seconds = frame_time.as_secs_f64();
+        self.ema_seconds = Some(match self.ema_seconds {
+            Some(ema) => ema + self.ema_weight * (seconds - ema),
+            None => seconds,
+        });
+        self.total_frames += 1;
+    }
+
+    /// Every frame recorded, including those that have left the ring buffer.
+    pub fn total_frames(&self) -> u64 {
+        self.total_frames
+    }
+
+    /// The frame times in the ring buffer, oldest first.
+    pub fn frame_times(&self) -> impl Iterator<Item = Duration> + '_ {
+        let (newest, oldest) = self.frame_times.split_at(self.next);
+        oldest.iter().chain(newest).copied()
+    }
+
+    pub fn ema(&self) -> Option<Duration> {
+        self.ema_seconds.map(Duration::from_secs_f64)
+    }
+
+    /// `None` until at least one frame has been recorded.
+    pub fn report(&self) -> Option<FrameReport> {
+        let ema = self.ema()?;
+        let mut sorted = self.frame_times.clone();
+        sorted.sort_unstable();
+        Some(FrameReport {
+            total_frames: self.total_frames,
+            samples: sorted.len(),
+            ema,
+            min: sorted[0],
+            max: sorted[sorted.len() - 1],
+            p50: percentile(&sorted, 50.0),
+            p95: percentile(&sorted, 95.0),
+            p99: percentile(&sorted, 99.0),
+            low_1_percent: slowest_average(&sorted, 1.0),
+            low_0_1_percent: slowest_average(&sorted, 0.1),
+        })
+    }
+}
+
+impl Default for FrameStats {
+    /// About 16 seconds of frames at 60 FPS.
+    fn default() -> Self {
+        Self::new(1000, 0.05)
+    }
+}
+
+/// Nearest rank percentile of sorted frame times.
+fn percentile(sorted: &[Duration], percent: f64) -> Duration {
+    let rank = (percent / 100.0 * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+/// The average of the slowest `percent` of sorted frame times, and at least the slowest one.
+fn slowest_average(sorted: &[Duration], percent: f64) -> Duration {
+    let count = ((percent / 100.0 * sorted.len() as f64).ceil() as usize).max(1);
+    let slowest = &sorted[sorted.len() - count..];
+    slowest.iter().sum::<Duration>() / count as u32
+}
+
+/// A snapshot of `FrameStats`. All values are frame times, so bigger is slower.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FrameReport {
+    pub total_frames: u64,
+    /// How many frame times the statistics below were computed from.
+    pub samples: usize,
+    pub ema: Duration,
+    pub min: Duration,
+    pub max: Duration,
+    pub p50: Duration,
+    pub p95: Duration,
+    pub p99: Duration,
+    /// The average of the slowest 1% of frames. Usually quoted as FPS, see `fps`.
+    pub low_1_percent: Duration,
+    /// The average of the slowest 0.1% of frames.
+    pub low_0_1_percent: Duration,
+}
+
+impl FrameReport {
+    pub const CSV_HEADER: &'static str = concat!(
+        "total_frames,samples,ema_ms,min_ms,max_ms,",
+        "p50_ms,p95_ms,p99_ms,low_1_percent_ms,low_0_1_percent_ms"
+    );
+
+    pub fn fps(frame_time: Duration) -> f64 {
+        1.0 / frame_time.as_secs_f64()
+    }
+
+    pub fn to_csv_row(&self) -> String {
+        format!(
+            "{},{},{:.3},{:.3},{:.3},{:.3},{:.3},{:.3},{:.3},{:.3}",
+            self.total_frames,
+            self.samples,
+            milliseconds(self.ema),
+            milliseconds(self.min),
+            milliseconds(self.max),
+            milliseconds(self.p50),
+            milliseconds(self.p95),
+            milliseconds(self.p99),
+            milliseconds(self.low_1_percent),
+            milliseconds(self.low_0_1_percent),
+        )
+    }
+}
+
+fn milliseconds(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+impl std::fmt::Display for FrameReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "FPS: {:.0} ({:.2} ms), 1% low: {:.0}, 0.1% low: {:.0}, \
+            min/p50/p95/p99/max: {:.2}/{:.2}/{:.2}/{:.2}/{:.2} ms",
+            Self::fps(self.ema),
+            milliseconds(self.ema),
+            Self::fps(self.low_1_percent),
+            Self::fps(self.low_0_1_percent),
+            milliseconds(self.min),
+            milliseconds(self.p50),
+            milliseconds(self.p95),
+            milliseconds(self.p99),
+            milliseconds(self.max),
+        )
+    }
+}
+
+/// Where `game::run` sends a `FrameReport` every `REPORT_INTERVAL`.
+pub trait Reporter {
+    fn report(&mut self, report: &FrameReport, window: Option<&winit::window::Window>);
+}
+
+/// How often `game::run` reports frame statistics.
+pub const REPORT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Prints each report on its own line.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct StdoutReporter;
+
+impl Reporter for StdoutReporter {
+    fn report(&mut self, report: &FrameReport, _window: Option<&winit::window::Window>) {
+        println!("{}", report);
+    }
+}
+
+/// Appends a row per report, for graphing later.
+pub struct CsvReporter<W: Write> {
+    writer: W,
+}
+
+impl CsvReporter<std::io::BufWriter<std::fs::File>> {
+    pub fn create(path: impl AsRef<std::path::Path>) -> std::io::Result<Self> {
+        Self::new(std::io::BufWriter::new(std::fs::File::create(path)?))
+    }
+}
+
+impl<W: Write> CsvReporter<W> {
+    /// Writes the header row straight away.
+    pub fn new(mut writer: W) -> std::io::Result<Self> {
+        writeln!(writer, "{}", FrameReport::CSV_HEADER)?;
+        Ok(Self { writer })
+    }
+
+    /// Flushes after every row, so the file is complete even if we never exit cleanly.
+    pub fn write_row(&mut self, report: &FrameReport) -> std::io::Result<()> {
+        writeln!(self.writer, "{}", report.to_csv_row())?;
+        self.writer.flush()
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+impl<W: Write> Reporter for CsvReporter<W> {
+    fn report(&mut self, report: &FrameReport, _window: Option<&winit::window::Window>) {
+        if let Err(e) = self.write_row(report) {
+            log::warn!("Failed to write frame stats: {e}");
+        }
+    }
+}
+
+/// Shows the FPS in the window title, after `title`.
+///
+/// Does nothing unless `Game::render_context` has a window.
+#[derive(Debug, Clone)]
+pub struct WindowTitleReporter {
+    pub title: String,
+}
+
+impl WindowTitleReporter {
+    pub fn new(title: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+        }
+    }
+
+    pub fn window_title(&self, report: &FrameReport) -> String {
+        format!(
+            "{} - {:.0} FPS, 1% low {:.0}",
+            self.title,
+            FrameReport::fps(report.ema),
+            FrameReport::fps(report.low_1_percent)
+        )
+    }
+}
+
+impl Reporter for WindowTitleReporter {
+    fn report(&mut self, report: &FrameReport, window: Option<&winit::window::Window>) {
+        if let Some(window) = window {
+            window.set_title(&self.window_title(report));
+        }
+    }
+}
@@ -0,0 +1,247 @@
+//! A shared game loop so the windowed binaries don't each hand-roll `event_loop.run`.
+
+use crate::context::RenderContext;
+use crate::frame_stats::{FrameStats, Reporter, StdoutReporter, REPORT_INTERVAL};
+use crate::timestep::{FixedTimestep, TimestepConfig};
+
+/// The lifecycle every windowed demo goes through.
+///
+/// Each iteration of the event loop forwards events to `process_input`,
+/// then calls `update` zero or more times at a fixed rate, and `render` once.
+pub trait Game {
+    /// Receives every window and device event.
+    ///
+    /// Escape, CloseRequested and Resized are handled by `run`, but are still forwarded here.
+    fn process_input(&mut self, _event: &winit::event::Event<()>) {}
+
+    /// Advances the game state by `dt`, which is always `1 / ticks_per_second`.
+    fn update(&mut self, _dt: std::time::Duration) {}
+
+    /// `alpha` is how far we are between the previous update and the next, in 0.0..1.0.
+    /// Interpolate between the previous and current state with it for smooth motion.
+    fn render(&mut self, alpha: f32);
+
+    /// Called when the window is resized. Usually reconfigures the surface.
+    fn resize(&mut self, new_size: winit::dpi::PhysicalSize<u32>);
+
+    fn timestep_config(&self) -> TimestepConfig {
+        TimestepConfig::default()
+    }
+
+    /// What the game renders with.
+    ///
+    /// Headless runs read the last frame back through this, and reporters use its window.
+    fn render_context(&self) -> Option<&RenderContext> {
+        None
+    }
+
+    /// Where `run` sends frame statistics. Prints them by default.
+    fn frame_stats_reporter(&self) -> Box<dyn Reporter> {
+        Box::new(StdoutReporter)
+    }
+}
+
+/// Command line options for rendering without a window:
+/// `--headless [--frames N] [--size WIDTHxHEIGHT] [--output PATH]`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HeadlessOptions {
+    /// How many frames to render. Each one advances the game by one update.
+    pub frames: u32,
+    pub size: (u32, u32),
+    /// Where the last frame is saved, as a PNG.
+    pub output: std::path::PathBuf,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ArgsError {
+    MissingValue(&'static str),
+    InvalidValue { flag: &'static str, value: String },
+}
+
+impl std::fmt::Display for ArgsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ArgsError::MissingValue(flag) => write!(f, "{} needs a value", flag),
+            ArgsError::InvalidValue { flag, value } => {
+                write!(f, "Invalid value for {}: {:?}", flag, value)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ArgsError {}
+
+impl HeadlessOptions {
+    /// `args` starts with the program name, like `std::env::args`.
+    ///
+    /// Returns `None` without `--headless`. Unknown arguments are ignored,
+    /// so demos can have their own.
+    pub fn from_args(args: impl IntoIterator<Item = String>) -> Result<Option<Self>, ArgsError> {
+        let mut args = args.into_iter();
+        let program = args.next().unwrap_or_default();
+        let program_name = std::path::Path::new(&program)
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or("frame");
+        let mut headless = false;
+        let mut options = HeadlessOptions {
+            frames: 1,
+            size: crate::context::DEFAULT_HEADLESS_SIZE,
+            output: format!("{}.png", program_name).into(),
+        };
+        while let Some(arg) = args.next() {
+            let flag = match arg.as_str() {
+                "--headless" => {
+                    headless = true;
+                    continue;
+                }
+                "--frames" => "--frames",
+                "--size" => "--size",
+                "--output" => "--output",
+                _ => continue,
+            };
+            let value = args.next().ok_or(ArgsError::MissingValue(flag))?;
+            let invalid = || ArgsError::InvalidValue {
+                flag,
+                value: value.clone(),
+            };
+            match flag {
+                "--frames" => {
+                    options.frames = value.parse().map_err(|_| invalid())?;
+                    if options.frames == 0 {
+                        return Err(invalid());
+                    }
+                }
+                "--size" => {
+                    let (width, height) = value.split_once('x').ok_or_else(invalid)?;
+                    options.size = (
+                        width.parse().map_err(|_| invalid())?,
+                        height.parse().map_err(|_| invalid())?,
+                    );
+                    if options.size.0 == 0 || options.size.1 == 0 {
+                        return Err(invalid());
+                    }
+                }
+                _ => options.output = value.into(),
+            }
+        }
+        Ok(headless.then_some(options))
+    }
+}
+
+/// Renders `frames` frames to an offscreen texture of `size`, and returns the last one.
+///
+/// Each frame advances the game by exactly one update, so the result doesn't depend on
+/// how fast the machine is.
+///
+/// # Panics
+///
+/// If the game doesn't return its `RenderContext` from `Game::render_context`.
+pub fn render_headless<G, F>(new_game: F, frames: u32, size: (u32, u32)) -> crate::image::Image
+where
+    G: Game,
+    F: FnOnce(RenderContext) -> G,
+{
+    let mut game = new_game(RenderContext::offscreen(size));
+    let mut timestep = FixedTimestep::new(game.timestep_config());
+    let dt = timestep.dt();
+    for _ in 0..frames {
+        timestep.frame(&mut game, dt);
+    }
+    game.render_context()
+        .and_then(RenderContext::read_pixels)
+        .expect("Game::render_context must return the offscreen context to render headless")
+}
+
+/// Sets up logging, creates the window, hands it to `new_game`,
+/// and drives the resulting game until exit.
+///
+/// With `--headless`, renders offscreen instead and saves the last frame, see `HeadlessOptions`.
+pub fn run<G, F>(new_game: F) -> !
+where
+    G: Game + 'static,
+    F: FnOnce(RenderContext) -> G,
+{
+    crate::logging::init();
+    match HeadlessOptions::from_args(std::env::args()) {
+        Ok(Some(options)) => {
+            let image = render_headless(new_game, options.frames, options.size);
+            if let Err(e) = image.save_png(&options.output) {
+                eprintln!("Failed to save {}: {}", options.output.display(), e);
+                std::process::exit(1);
+            }
+            println!(
+                "Saved frame {} to {}",
+                options.frames,
+                options.output.display()
+            );
+            std::process::exit(0);
+        }
+        Ok(None) => {}
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+    }
+    let event_loop = winit::event_loop::EventLoop::new();
+    let window = winit::window::Window::new(&event_loop).unwrap();
+    let mut game = new_game(RenderContext::windowed(window));
+    let mut timestep = FixedTimestep::new(game.timestep_config());
+    let mut frame_stats = FrameStats::default();
+    let mut reporter = game.frame_stats_reporter();
+    let mut last_frame: Option<std::time::Instant> = None;
+    let mut last_report = std::time::Instant::now();
+
+    event_loop.run(move |event, _, control_flow| {
+        match &event {
+            winit::event::Event::WindowEvent {
+                window_id: _,
+                event: window_event,
+            } => {
+                match window_event {
+                    winit::event::WindowEvent::CloseRequested
+                    | winit::event::WindowEvent::KeyboardInput {
+                        input:
+                            winit::event::KeyboardInput {
+                                virtual_keycode: Some(winit::event::VirtualKeyCode::Escape),
+                                ..
+                            },
+                        ..
+                    } => control_flow.set_exit(),
+                    winit::event::WindowEvent::Resized(new_size) => {
+                        game.resize(*new_size);
+                    }
+                    _ => {}
+                }
+                game.process_input(&event);
+            }
+            winit::event::Event::DeviceEvent { .. } => {
+                game.process_input(&event);
+            }
+            winit::event::Event::MainEventsCleared => {
+                // The winit docs say:
+                // Programs that draw graphics continuously, like most games,
+                // can render here unconditionally for simplicity.
+                // See: https://docs.rs/winit/latest/winit/event/enum.Event.html#variant.MainEventsCleared
+                let now = std::time::Instant::now();
+                // The first frame has nothing to measure from.
+                let frame_time = last_frame.map_or(std::time::Duration::ZERO, |last| now - last);
+                timestep.frame(&mut game, frame_time);
+                if last_frame.is_some() {
+                    frame_stats.record(frame_time);
+                }
+                last_frame = Some(now);
+                if now - last_report >= REPORT_INTERVAL {
+                    if let Some(report) = frame_stats.report() {
+                        reporter.report(
+                            &report,
+                            game.render_context().and_then(RenderContext::window),
+                        );
+                    }
+                    last_report = now;
+                }
+            }
+            _ => {}
+        }
+    })
+}
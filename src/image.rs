@@ -0,0 +1,206 @@
+//! RGBA8 images in memory, reading them from PNG or QOI, and writing them as PNG.
+
+/// Pixels are RGBA, 4 bytes each, in rows from top to bottom.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+#[derive(Debug)]
+pub enum ImageError {
+    Io(std::io::Error),
+    Encoding(png::EncodingError),
+    Decoding(png::DecodingError),
+    /// A PNG that doesn't decode to 8 bits per channel.
+    UnsupportedFormat(png::ColorType, png::BitDepth),
+    Qoi(qoi::Error),
+    /// The bytes start like neither a PNG nor a QOI.
+    UnknownFormat,
+}
+
+impl std::fmt::Display for ImageError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImageError::Io(e) => write!(f, "{}", e),
+            ImageError::Encoding(e) => write!(f, "{}", e),
+            ImageError::Decoding(e) => write!(f, "{}", e),
+            ImageError::UnsupportedFormat(color_type, bit_depth) => write!(
+                f,
+                "Unsupported PNG format {:?} {:?}, expected 8 bits per channel",
+                color_type, bit_depth
+            ),
+            ImageError::Qoi(e) => write!(f, "{}", e),
+            ImageError::UnknownFormat => write!(f, "Unknown image format, expected PNG or QOI"),
+        }
+    }
+}
+
+impl std::error::Error for ImageError {}
+
+impl From<std::io::Error> for ImageError {
+    fn from(e: std::io::Error) -> Self {
+        ImageError::Io(e)
+    }
+}
+
+impl From<png::EncodingError> for ImageError {
+    fn from(e: png::EncodingError) -> Self {
+        ImageError::Encoding(e)
+    }
+}
+
+impl From<png::DecodingError> for ImageError {
+    fn from(e: png::DecodingError) -> Self {
+        ImageError::Decoding(e)
+    }
+}
+
+impl From<qoi::Error> for ImageError {
+    fn from(e: qoi::Error) -> Self {
+        ImageError::Qoi(e)
+    }
+}
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+const QOI_MAGIC: &[u8] = b"qoif";
+
+impl Image {
+    pub fn new(width: u32, height: u32, pixels: Vec<u8>) -> Self {
+        assert_eq!(pixels.len(), width as usize * height as usize * 4);
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    /// The RGBA of the pixel at column `x`, row `y`.
+    pub fn pixel(&self, x: u32, y: u32) -> [u8; 4] {
+        let i = (y as usize * self.width as usize + x as usize) * 4;
+        self.pixels[i..i + 4].try_into().unwrap()
+    }
+
+    pub fn save_png(&self, path: impl AsRef<std::path::Path>) -> Result<(), ImageError> {
+        let file = std::io::BufWriter::new(std::fs::File::create(path)?);
+        let mut encoder = png::Encoder::new(file, self.width, self.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.set_srgb(png::SrgbRenderingIntent::Perceptual);
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&self.pixels)?;
+        writer.finish()?;
+        Ok(())
+    }
+
+    /// A PNG or QOI, told apart by their first bytes rather than the file's extension.
+    pub fn load(path: impl AsRef<std::path::Path>) -> Result<Self, ImageError> {
+        Self::from_bytes(&std::fs::read(path)?)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ImageError> {
+        if bytes.starts_with(PNG_SIGNATURE) {
+            Self::from_png_bytes(bytes)
+        } else if bytes.starts_with(QOI_MAGIC) {
+            Self::from_qoi_bytes(bytes)
+        } else {
+            Err(ImageError::UnknownFormat)
+        }
+    }
+
+    pub fn load_png(path: impl AsRef<std::path::Path>) -> Result<Self, ImageError> {
+        Self::from_png_bytes(&std::fs::read(path)?)
+    }
+
+    /// Palette, grayscale and RGB PNGs are made RGBA, opaque unless they have transparency, and
+    /// 16 bit channels are cut to 8 bits.
+    pub fn from_png_bytes(bytes: &[u8]) -> Result<Self, ImageError> {
+        let mut decoder = png::Decoder::new(bytes);
+        decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+        let mut reader = decoder.read_info()?;
+        let mut buffer = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buffer)?;
+        buffer.truncate(info.buffer_size());
+        let pixels = match (info.color_type, info.bit_depth) {
+            (png::ColorType::Rgba, png::BitDepth::Eight) => buffer,
+            (png::ColorType::Rgb, png::BitDepth::Eight) => buffer
+                .chunks_exact(3)
+                .flat_map(|rgb| [rgb[0], rgb[1], rgb[2], 255])
+                .collect(),
+            (png::ColorType::GrayscaleAlpha, png::BitDepth::Eight) => buffer
+                .chunks_exact(2)
+                .flat_map(|gray_alpha| [gray_alpha[0], gray_alpha[0], gray_alpha[0], gray_alpha[1]])
+                .collect(),
+            (png::ColorType::Grayscale, png::BitDepth::Eight) => buffer
+                .iter()
+                .flat_map(|&gray| [gray, gray, gray, 255])
+                .collect(),
+            (color_type, bit_depth) => {
+                return Err(ImageError::UnsupportedFormat(color_type, bit_depth))
+            }
+        };
+        Ok(Self::new(info.width, info.height, pixels))
+    }
+
+    /// RGB QOIs are made opaque RGBA. The header's color space is ignored, like a PNG's.
+    pub fn from_qoi_bytes(bytes: &[u8]) -> Result<Self, ImageError> {
+        let (header, buffer) = qoi::decode_to_vec(bytes)?;
+        let pixels = match header.channels {
+            qoi::Channels::Rgba => buffer,
+            qoi::Channels::Rgb => buffer
+                .chunks_exact(3)
+                .flat_map(|rgb| [rgb[0], rgb[1], rgb[2], 255])
+                .collect(),
+        };
+        Ok(Self::new(header.width, header.height, pixels))
+    }
+
+    /// Compares each channel of each pixel, allowing differences up to `tolerance`.
+    ///
+    /// Returns `None` if the sizes differ.
+    pub fn compare(&self, expected: &Image, tolerance: u8) -> Option<ImageDiff> {
+        if (self.width, self.height) != (expected.width, expected.height) {
+            return None;
+        }
+        let mut differing_pixels = 0;
+        let mut max_difference = 0;
+        let mut pixels = Vec::with_capacity(self.pixels.len());
+        for (actual, expected) in self
+            .pixels
+            .chunks_exact(4)
+            .zip(expected.pixels.chunks_exact(4))
+        {
+            let difference = actual
+                .iter()
+                .zip(expected)
+                .map(|(a, e)| a.abs_diff(*e))
+                .max()
+                .unwrap();
+            max_difference = max_difference.max(difference);
+            if difference > tolerance {
+                differing_pixels += 1;
+                pixels.extend_from_slice(&[255, 0, 0, 255]);
+            } else {
+                let gray = (expected[0] as u32 + expected[1] as u32 + expected[2] as u32) / 12;
+                pixels.extend_from_slice(&[gray as u8, gray as u8, gray as u8, 255]);
+            }
+        }
+        Some(ImageDiff {
+            differing_pixels,
+            max_difference,
+            image: Image::new(self.width, self.height, pixels),
+        })
+    }
+}
+
+/// How two images of the same size differ. See `Image::compare`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImageDiff {
+    /// Pixels where some channel differs by more than the tolerance.
+    pub differing_pixels: usize,
+    /// The largest difference of any channel of any pixel.
+    pub max_difference: u8,
+    /// Differing pixels in red over a faded copy of the expected image.
+    pub image: Image,
+}
@@ -0,0 +1,154 @@
+//! Keyboard and mouse state, built up from winit window events.
+//!
+//! winit only tells us when a key changes; it doesn't tell us which keys are currently held.
+
+/// A keyboard key or a mouse button.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Button {
+    Key(winit::event::VirtualKeyCode),
+    Mouse(winit::event::MouseButton),
+}
+
+impl From<winit::event::VirtualKeyCode> for Button {
+    fn from(key: winit::event::VirtualKeyCode) -> Self {
+        Button::Key(key)
+    }
+}
+
+impl From<winit::event::MouseButton> for Button {
+    fn from(button: winit::event::MouseButton) -> Self {
+        Button::Mouse(button)
+    }
+}
+
+/// Some platforms scroll in pixels rather than lines. We convert pixels to lines at this rate.
+pub const PIXELS_PER_SCROLL_LINE: f32 = 20.0;
+
+#[derive(Debug, Default, Clone)]
+pub struct InputState {
+    down: std::collections::HashSet<Button>,
+    just_pressed: std::collections::HashSet<Button>,
+    just_released: std::collections::HashSet<Button>,
+    modifiers: winit::event::ModifiersState,
+    cursor_position: Option<glam::Vec2>,
+    cursor_delta: glam::Vec2,
+    scroll_delta: glam::Vec2,
+}
+
+impl InputState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Forwards window events to `handle_window_event`. Other events are ignored.
+    pub fn handle_event(&mut self, event: &winit::event::Event<()>) {
+        if let winit::event::Event::WindowEvent { event, .. } = event {
+            self.handle_window_event(event);
+        }
+    }
+
+    pub fn handle_window_event(&mut self, event: &winit::event::WindowEvent) {
+        match event {
+            winit::event::WindowEvent::KeyboardInput {
+                input:
+                    winit::event::KeyboardInput {
+                        state,
+                        virtual_keycode: Some(key),
+                        ..
+                    },
+                ..
+            } => self.set(Button::Key(*key), *state),
+            winit::event::WindowEvent::MouseInput { state, button, .. } => {
+                self.set(Button::Mouse(*button), *state)
+            }
+            winit::event::WindowEvent::CursorMoved { position, .. } => {
+                let position = glam::Vec2::new(position.x as f32, position.y as f32);
+                if let Some(previous_position) = self.cursor_position {
+                    self.cursor_delta += position - previous_position;
+                }
+                self.cursor_position = Some(position);
+            }
+            winit::event::WindowEvent::CursorLeft { .. } => {
+                self.cursor_position = None;
+            }
+            winit::event::WindowEvent::MouseWheel { delta, .. } => match delta {
+                winit::event::MouseScrollDelta::LineDelta(x, y) => {
+                    self.scroll_delta += glam::Vec2::new(*x, *y);
+                }
+                winit::event::MouseScrollDelta::PixelDelta(position) => {
+                    self.scroll_delta += glam::Vec2::new(position.x as f32, position.y as f32)
+                        / PIXELS_PER_SCROLL_LINE;
+                }
+            },
+            winit::event::WindowEvent::ModifiersChanged(modifiers) => {
+                self.modifiers = *modifiers;
+            }
+            winit::event::WindowEvent::Focused(false) => {
+                // We won't see the release events once focus is gone,
+                // so treat everything as released now rather than leaving keys stuck down.
+                self.just_released.extend(self.down.drain());
+                self.modifiers = winit::event::ModifiersState::empty();
+            }
+            _ => {}
+        }
+    }
+
+    fn set(&mut self, button: Button, state: winit::event::ElementState) {
+        match state {
+            winit::event::ElementState::Pressed => {
+                // Held keys send repeated Pressed events; only the first one counts.
+                if self.down.insert(button) {
+                    self.just_pressed.insert(button);
+                }
+            }
+            winit::event::ElementState::Released => {
+                if self.down.remove(&button) {
+                    self.just_released.insert(button);
+                }
+            }
+        }
+    }
+
+    /// Clears the per-frame state: just pressed, just released, cursor movement and scrolling.
+    ///
+    /// Call this once the game has looked at the input, usually at the end of `update`.
+    pub fn end_frame(&mut self) {
+        self.just_pressed.clear();
+        self.just_released.clear();
+        self.cursor_delta = glam::Vec2::ZERO;
+        self.scroll_delta = glam::Vec2::ZERO;
+    }
+
+    pub fn is_down(&self, button: impl Into<Button>) -> bool {
+        self.down.contains(&button.into())
+    }
+
+    /// True if the button went down since the last `end_frame`.
+    pub fn just_pressed(&self, button: impl Into<Button>) -> bool {
+        self.just_pressed.contains(&button.into())
+    }
+
+    /// True if the button went up since the last `end_frame`.
+    pub fn just_released(&self, button: impl Into<Button>) -> bool {
+        self.just_released.contains(&button.into())
+    }
+
+    pub fn modifiers(&self) -> winit::event::ModifiersState {
+        self.modifiers
+    }
+
+    /// Cursor position in physical window pixels, or `None` if the cursor is outside the window.
+    pub fn cursor_position(&self) -> Option<glam::Vec2> {
+        self.cursor_position
+    }
+
+    /// How far the cursor moved since the last `end_frame`, in physical pixels.
+    pub fn cursor_delta(&self) -> glam::Vec2 {
+        self.cursor_delta
+    }
+
+    /// Scrolling since the last `end_frame`, in lines. Positive y is scrolling up.
+    pub fn scroll_delta(&self) -> glam::Vec2 {
+        self.scroll_delta
+    }
+}
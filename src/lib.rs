@@ -0,0 +1,27 @@
+// So derive macros, which refer to `::wgpu_experiments`, work inside this crate too.
+extern crate self as wgpu_experiments;
+
+pub mod actions;
+pub mod audio;
+pub mod benchmark;
+pub mod buffer;
+pub mod camera;
+pub mod canvas;
+pub mod context;
+pub mod demo;
+pub mod frame_stats;
+pub mod game;
+pub mod image;
+pub mod input;
+pub mod logging;
+pub mod random;
+pub mod rect;
+pub mod reflect;
+pub mod scene;
+pub mod shader;
+pub mod sprite;
+pub mod surface;
+pub mod texture;
+pub mod timestep;
+pub mod vertex;
+pub mod wgsl;
@@ -0,0 +1,148 @@
+//! Logs the descriptor of every wgpu object as it is created, so we can review the settings
+//! we're getting, especially the ones hidden behind `Default`.
+//!
+//! Verbosity is set with `RUST_LOG`, see the `env_logger` docs. For example:
+//!
+//! - `RUST_LOG=wgpu_experiments=info` logs the instance, adapter and device.
+//! - `RUST_LOG=wgpu_experiments=debug` also logs adapter limits and features,
+//!   and every buffer, texture, sampler, shader module, layout and pipeline.
+//! - `RUST_LOG=wgpu_experiments=trace` also logs surface reconfigures.
+//! - `RUST_LOG=debug` includes wgpu's own logging too, which is a lot.
+
+/// Sets up logging to stderr. Only warnings are shown unless `RUST_LOG` says otherwise.
+///
+/// Safe to call more than once.
+pub fn init() {
+    let _ = env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("warn"))
+        .try_init();
+}
+
+pub fn create_instance(descriptor: wgpu::InstanceDescriptor) -> wgpu::Instance {
+    // InstanceDescriptor doesn't implement Debug.
+    log::info!(
+        "Creating instance: backends {:?}, dx12 shader compiler {:?}",
+        descriptor.backends,
+        descriptor.dx12_shader_compiler,
+    );
+    wgpu::Instance::new(descriptor)
+}
+
+/// Blocks until the adapter is ready, and logs what we got.
+pub fn request_adapter(
+    instance: &wgpu::Instance,
+    options: &wgpu::RequestAdapterOptions,
+) -> Option<wgpu::Adapter> {
+    log::info!("Requesting adapter: {options:?}");
+    let adapter = pollster::block_on(instance.request_adapter(options))?;
+    let info = adapter.get_info();
+    log::info!(
+        "Got adapter {:?}: {:?} via {:?}, vendor {:#x}, device {:#x}, driver {:?} {:?}",
+        info.name,
+        info.device_type,
+        info.backend,
+        info.vendor,
+        info.device,
+        info.driver,
+        info.driver_info,
+    );
+    log::debug!("Adapter features: {:?}", adapter.features());
+    log::debug!("Adapter limits: {:?}", adapter.limits());
+    Some(adapter)
+}
+
+/// Blocks until the device is ready.
+pub fn request_device(
+    adapter: &wgpu::Adapter,
+    descriptor: &wgpu::DeviceDescriptor,
+) -> Result<(wgpu::Device, wgpu::Queue), wgpu::RequestDeviceError> {
+    log::info!("Requesting device: {descriptor:?}");
+    let (device, queue) = pollster::block_on(adapter.request_device(descriptor, None))?;
+    log::debug!("Device features: {:?}", device.features());
+    log::debug!("Device limits: {:?}", device.limits());
+    Ok((device, queue))
+}
+
+pub fn create_buffer(device: &wgpu::Device, descriptor: &wgpu::BufferDescriptor) -> wgpu::Buffer {
+    log::debug!("Creating buffer: {descriptor:?}");
+    device.create_buffer(descriptor)
+}
+
+/// A buffer holding `contents`, copied in while it's mapped at creation.
+///
+/// Mapping needs a multiple of 4 bytes, so the size is rounded up and padded with zeros.
+pub fn create_buffer_init(
+    device: &wgpu::Device,
+    label: &str,
+    usage: wgpu::BufferUsages,
+    contents: &[u8],
+) -> wgpu::Buffer {
+    let buffer = create_buffer(
+        device,
+        &wgpu::BufferDescriptor {
+            label: Some(label),
+            size: crate::wgsl::round_up(wgpu::COPY_BUFFER_ALIGNMENT, contents.len() as u64),
+            usage,
+            mapped_at_creation: true,
+        },
+    );
+    buffer.slice(..).get_mapped_range_mut()[..contents.len()].copy_from_slice(contents);
+    buffer.unmap();
+    buffer
+}
+
+pub fn create_texture(
+    device: &wgpu::Device,
+    descriptor: &wgpu::TextureDescriptor,
+) -> wgpu::Texture {
+    log::debug!("Creating texture: {descriptor:?}");
+    device.create_texture(descriptor)
+}
+
+pub fn create_sampler(
+    device: &wgpu::Device,
+    descriptor: &wgpu::SamplerDescriptor,
+) -> wgpu::Sampler {
+    log::debug!("Creating sampler: {descriptor:?}");
+    device.create_sampler(descriptor)
+}
+
+/// Only the label is logged, the source would drown out everything else.
+pub fn create_shader_module(
+    device: &wgpu::Device,
+    descriptor: wgpu::ShaderModuleDescriptor,
+) -> wgpu::ShaderModule {
+    log::debug!("Creating shader module: {:?}", descriptor.label);
+    device.create_shader_module(descriptor)
+}
+
+pub fn create_bind_group_layout(
+    device: &wgpu::Device,
+    descriptor: &wgpu::BindGroupLayoutDescriptor,
+) -> wgpu::BindGroupLayout {
+    log::debug!("Creating bind group layout: {descriptor:?}");
+    device.create_bind_group_layout(descriptor)
+}
+
+pub fn create_pipeline_layout(
+    device: &wgpu::Device,
+    descriptor: &wgpu::PipelineLayoutDescriptor,
+) -> wgpu::PipelineLayout {
+    log::debug!("Creating pipeline layout: {descriptor:?}");
+    device.create_pipeline_layout(descriptor)
+}
+
+pub fn create_render_pipeline(
+    device: &wgpu::Device,
+    descriptor: &wgpu::RenderPipelineDescriptor,
+) -> wgpu::RenderPipeline {
+    log::debug!("Creating render pipeline: {descriptor:?}");
+    device.create_render_pipeline(descriptor)
+}
+
+pub fn create_compute_pipeline(
+    device: &wgpu::Device,
+    descriptor: &wgpu::ComputePipelineDescriptor,
+) -> wgpu::ComputePipeline {
+    log::debug!("Creating compute pipeline: {descriptor:?}");
+    device.create_compute_pipeline(descriptor)
+}
@@ -0,0 +1,41 @@
+//! Seeded randomness, so runs with the same `--seed` draw the same scene.
+
+use rand::SeedableRng;
+
+/// Finds `--seed N` in `args`. Other arguments are ignored.
+pub fn seed_from_args(
+    args: impl IntoIterator<Item = String>,
+) -> Result<Option<u64>, crate::game::ArgsError> {
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "--seed" {
+            let value = args
+                .next()
+                .ok_or(crate::game::ArgsError::MissingValue("--seed"))?;
+            return match value.parse() {
+                Ok(seed) => Ok(Some(seed)),
+                Err(_) => Err(crate::game::ArgsError::InvalidValue {
+                    flag: "--seed",
+                    value,
+                }),
+            };
+        }
+    }
+    Ok(None)
+}
+
+/// A generator seeded from `--seed`, or a random seed without one.
+///
+/// The seed is logged, so a run that looked wrong can be repeated.
+/// Exits if `--seed` isn't a number.
+pub fn rng_from_args() -> rand::rngs::StdRng {
+    let seed = match seed_from_args(std::env::args()) {
+        Ok(seed) => seed.unwrap_or_else(rand::random),
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+    };
+    log::info!("Seed: {}", seed);
+    rand::rngs::StdRng::seed_from_u64(seed)
+}
@@ -0,0 +1,184 @@
+//! Solid color rectangles in pixel coordinates, all drawn with one instanced draw call.
+
+use crate::vertex::VertexLayout;
+
+#[repr(C)]
+#[derive(PartialEq, Debug, Clone, Copy, bytemuck::Zeroable, bytemuck::Pod, VertexLayout)]
+#[vertex(step_mode = Instance)]
+struct RectInstance {
+    #[vertex(location = 1)]
+    position: glam::Vec2,
+    size: glam::Vec2,
+    color: glam::Vec4,
+}
+
+/// Collects `draw_rect` calls during a frame, then draws them all at once.
+///
+/// Coordinates are pixels with the origin at the top left and y pointing down.
+pub struct RectRenderer {
+    render_pipeline: crate::shader::HotPipeline<wgpu::RenderPipeline>,
+    corner_buffer: wgpu::Buffer,
+    index_buffer: wgpu::Buffer,
+    viewport_buffer: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    instance_buffer: crate::buffer::DynamicBuffer<RectInstance>,
+    instances: Vec<RectInstance>,
+}
+
+impl RectRenderer {
+    /// What `vertex_main` in rect.wgsl takes: the corners, and a `RectInstance` per rect.
+    pub const VERTEX_BUFFERS: &'static [wgpu::VertexBufferLayout<'static>] = &[
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<glam::Vec2>() as u64,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &wgpu::vertex_attr_array![0 => Float32x2],
+        },
+        RectInstance::LAYOUT,
+    ];
+
+    pub fn new(device: &wgpu::Device, format: wgpu::TextureFormat, sample_count: u32) -> Self {
+        let render_pipeline = crate::shader::HotPipeline::new(
+            device,
+            crate::shader_file!("rect.wgsl"),
+            move |device, shader_module| {
+                crate::logging::create_render_pipeline(
+                    device,
+                    &wgpu::RenderPipelineDescriptor {
+                        label: Some("rect render pipeline"),
+                        layout: None,
+                        vertex: wgpu::VertexState {
+                            module: shader_module,
+                            entry_point: "vertex_main",
+                            buffers: Self::VERTEX_BUFFERS,
+                        },
+                        primitive: wgpu::PrimitiveState::default(),
+                        depth_stencil: None,
+                        multisample: wgpu::MultisampleState {
+                            count: sample_count,
+                            mask: !0,
+                            alpha_to_coverage_enabled: false,
+                        },
+                        fragment: Some(wgpu::FragmentState {
+                            module: shader_module,
+                            entry_point: "fragment_main",
+                            targets: &[Some(wgpu::ColorTargetState {
+                                format,
+                                blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                                write_mask: wgpu::ColorWrites::ALL,
+                            })],
+                        }),
+                        multiview: None,
+                    },
+                )
+            },
+        );
+        let corner_buffer = crate::logging::create_buffer_init(
+            device,
+            "rect corner buffer",
+            wgpu::BufferUsages::VERTEX,
+            bytemuck::cast_slice(crate::vertex::QUAD_CORNERS),
+        );
+        let index_buffer = crate::logging::create_buffer_init(
+            device,
+            "rect index buffer",
+            wgpu::BufferUsages::INDEX,
+            bytemuck::cast_slice(crate::vertex::QUAD_INDICES),
+        );
+        let viewport_buffer = crate::logging::create_buffer(
+            device,
+            &wgpu::BufferDescriptor {
+                label: Some("rect viewport buffer"),
+                size: std::mem::size_of::<glam::Vec2>() as u64,
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            },
+        );
+        let bind_group = Self::create_bind_group(device, render_pipeline.get(), &viewport_buffer);
+        let instance_buffer = crate::buffer::DynamicBuffer::new(
+            device,
+            "rect instance buffer",
+            wgpu::BufferUsages::VERTEX,
+            1024,
+        );
+        Self {
+            render_pipeline,
+            corner_buffer,
+            index_buffer,
+            viewport_buffer,
+            bind_group,
+            instance_buffer,
+            instances: Vec::new(),
+        }
+    }
+
+    fn create_bind_group(
+        device: &wgpu::Device,
+        render_pipeline: &wgpu::RenderPipeline,
+        viewport_buffer: &wgpu::Buffer,
+    ) -> wgpu::BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("rect bind group"),
+            layout: &render_pipeline.get_bind_group_layout(0),
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: viewport_buffer.as_entire_binding(),
+            }],
+        })
+    }
+
+    /// Queues a rectangle for this frame. `color` is linear RGBA, and alpha blends.
+    pub fn draw_rect(&mut self, x: f32, y: f32, w: f32, h: f32, color: [f32; 4]) {
+        self.instances.push(RectInstance {
+            position: glam::Vec2::new(x, y),
+            size: glam::Vec2::new(w, h),
+            color: glam::Vec4::from_array(color),
+        });
+    }
+
+    /// Uploads the rectangles queued since the last `prepare`, and starts a new batch.
+    ///
+    /// `viewport_size` is the size in pixels of the texture we're about to draw to.
+    pub fn prepare(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        viewport_size: (u32, u32),
+    ) {
+        if self.render_pipeline.reload_if_changed(device) {
+            self.bind_group =
+                Self::create_bind_group(device, self.render_pipeline.get(), &self.viewport_buffer);
+        }
+        queue.write_buffer(
+            &self.viewport_buffer,
+            0,
+            bytemuck::bytes_of(&glam::Vec2::new(
+                viewport_size.0 as f32,
+                viewport_size.1 as f32,
+            )),
+        );
+        self.instance_buffer.write(device, queue, &self.instances);
+        self.instances.clear();
+    }
+
+    /// How many rects fit before `prepare` has to replace the instance buffer.
+    pub fn instance_capacity(&self) -> usize {
+        self.instance_buffer.capacity()
+    }
+
+    /// Draws everything uploaded by the last `prepare` in a single draw call.
+    pub fn render<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+        if self.instance_buffer.is_empty() {
+            return;
+        }
+        render_pass.set_pipeline(self.render_pipeline.get());
+        render_pass.set_bind_group(0, &self.bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.corner_buffer.slice(..));
+        render_pass.set_vertex_buffer(1, self.instance_buffer.slice());
+        render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+        render_pass.draw_indexed(
+            0..crate::vertex::QUAD_INDICES.len() as u32,
+            0,
+            0..self.instance_buffer.len() as u32,
+        );
+    }
+}
@@ -0,0 +1,590 @@
+//! Bind group and pipeline layouts reflected from a shader's WGSL with naga.
+//!
+//! Pipelines created with `layout: None` each get an implicit layout of their own, so their
+//! bind groups can't be used with other pipelines. `ShaderLayout::reflect` reads the bindings
+//! from the source instead, and `PipelineLayout` creates explicit layouts from them, which
+//! pipelines and bind groups can share. Its `bind_group` checks resources against the layout,
+//! naming the binding that doesn't fit rather than leaving it to a wgpu validation panic.
+
+#[derive(Debug)]
+pub enum ReflectError {
+    /// The WGSL doesn't parse or validate. Rendered with the source, like naga does.
+    Invalid(String),
+    /// A binding this module doesn't know how to reflect.
+    Unsupported {
+        group: u32,
+        binding: u32,
+        reason: String,
+    },
+}
+
+impl std::fmt::Display for ReflectError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReflectError::Invalid(e) => write!(f, "{}", e),
+            ReflectError::Unsupported {
+                group,
+                binding,
+                reason,
+            } => write!(
+                f,
+                "Can't reflect @group({}) @binding({}): {}",
+                group, binding, reason
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ReflectError {}
+
+/// The bindings a shader declares, with the stages that use them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShaderLayout {
+    /// Indexed by group. Groups the shader skips are empty. Entries are sorted by binding.
+    groups: Vec<Vec<wgpu::BindGroupLayoutEntry>>,
+}
+
+impl ShaderLayout {
+    /// Bindings no entry point uses are left out, as wgpu does for implicit layouts.
+    ///
+    /// Sampled float textures are assumed to be filterable, and samplers filtering, since
+    /// WGSL doesn't say.
+    pub fn reflect(source: &str) -> Result<Self, ReflectError> {
+        let module = naga::front::wgsl::parse_str(source)
+            .map_err(|e| ReflectError::Invalid(e.emit_to_string(source)))?;
+        let info = naga::valid::Validator::new(
+            naga::valid::ValidationFlags::all(),
+            naga::valid::Capabilities::all(),
+        )
+        .validate(&module)
+        .map_err(|e| ReflectError::Invalid(e.emit_to_string(source)))?;
+        let mut groups: Vec<Vec<wgpu::BindGroupLayoutEntry>> = Vec::new();
+        for (handle, global) in module.global_variables.iter() {
+            let Some(resource_binding) = &global.binding else {
+                continue;
+            };
+            let mut visibility = wgpu::ShaderStages::NONE;
+            for (i, entry_point) in module.entry_points.iter().enumerate() {
+                if !info.get_entry_point(i)[handle].is_empty() {
+                    visibility |= match entry_point.stage {
+                        naga::ShaderStage::Vertex => wgpu::ShaderStages::VERTEX,
+                        naga::ShaderStage::Fragment => wgpu::ShaderStages::FRAGMENT,
+                        naga::ShaderStage::Compute => wgpu::ShaderStages::COMPUTE,
+                    };
+                }
+            }
+            if visibility.is_empty() {
+                continue;
+            }
+            let unsupported = |reason: String| ReflectError::Unsupported {
+                group: resource_binding.group,
+                binding: resource_binding.binding,
+                reason,
+            };
+            let group = resource_binding.group as usize;
+            if groups.len() <= group {
+                groups.resize(group + 1, Vec::new());
+            }
+            groups[group].push(wgpu::BindGroupLayoutEntry {
+                binding: resource_binding.binding,
+                visibility,
+                ty: binding_type(&module, global).map_err(unsupported)?,
+                count: None,
+            });
+        }
+        for entries in &mut groups {
+            entries.sort_by_key(|entry| entry.binding);
+        }
+        Ok(Self { groups })
+    }
+
+    /// Makes a buffer binding take an offset in `set_bind_group`, e.g. for a
+    /// `buffer::UniformArena`. WGSL can't say so itself.
+    ///
+    /// Panics if the shader uses no buffer at `group` and `binding`.
+    pub fn dynamic_offset(mut self, group: u32, binding: u32) -> Self {
+        let entry = self
+            .groups
+            .get_mut(group as usize)
+            .and_then(|entries| entries.iter_mut().find(|entry| entry.binding == binding));
+        match entry {
+            Some(wgpu::BindGroupLayoutEntry {
+                ty:
+                    wgpu::BindingType::Buffer {
+                        has_dynamic_offset, ..
+                    },
+                ..
+            }) => *has_dynamic_offset = true,
+            _ => panic!("@group({}) @binding({}) isn't a buffer", group, binding),
+        }
+        self
+    }
+
+    pub fn groups(&self) -> &[Vec<wgpu::BindGroupLayoutEntry>] {
+        &self.groups
+    }
+
+    pub fn entry(&self, group: u32, binding: u32) -> Option<&wgpu::BindGroupLayoutEntry> {
+        self.groups
+            .get(group as usize)?
+            .iter()
+            .find(|entry| entry.binding == binding)
+    }
+}
+
+fn binding_type(
+    module: &naga::Module,
+    global: &naga::GlobalVariable,
+) -> Result<wgpu::BindingType, String> {
+    let inner = &module.types[global.ty].inner;
+    let buffer = |ty| wgpu::BindingType::Buffer {
+        ty,
+        has_dynamic_offset: false,
+        min_binding_size: std::num::NonZeroU64::new(inner.size(module.to_ctx()) as u64),
+    };
+    match (global.space, inner) {
+        (naga::AddressSpace::Uniform, _) => Ok(buffer(wgpu::BufferBindingType::Uniform)),
+        (naga::AddressSpace::Storage { access }, _) => {
+            Ok(buffer(wgpu::BufferBindingType::Storage {
+                read_only: !access.contains(naga::StorageAccess::STORE),
+            }))
+        }
+        (naga::AddressSpace::Handle, naga::TypeInner::Sampler { comparison }) => {
+            Ok(wgpu::BindingType::Sampler(if *comparison {
+                wgpu::SamplerBindingType::Comparison
+            } else {
+                wgpu::SamplerBindingType::Filtering
+            }))
+        }
+        (
+            naga::AddressSpace::Handle,
+            naga::TypeInner::Image {
+                dim,
+                arrayed,
+                class,
+            },
+        ) => {
+            let view_dimension = match (dim, arrayed) {
+                (naga::ImageDimension::D1, false) => wgpu::TextureViewDimension::D1,
+                (naga::ImageDimension::D2, false) => wgpu::TextureViewDimension::D2,
+                (naga::ImageDimension::D2, true) => wgpu::TextureViewDimension::D2Array,
+                (naga::ImageDimension::D3, false) => wgpu::TextureViewDimension::D3,
+                (naga::ImageDimension::Cube, false) => wgpu::TextureViewDimension::Cube,
+                (naga::ImageDimension::Cube, true) => wgpu::TextureViewDimension::CubeArray,
+                (dim, arrayed) => {
+                    return Err(format!("{:?} textures with arrayed {}", dim, arrayed))
+                }
+            };
+            match *class {
+                naga::ImageClass::Sampled { kind, multi } => Ok(wgpu::BindingType::Texture {
+                    sample_type: match kind {
+                        naga::ScalarKind::Float => {
+                            wgpu::TextureSampleType::Float { filterable: !multi }
+                        }
+                        naga::ScalarKind::Sint => wgpu::TextureSampleType::Sint,
+                        naga::ScalarKind::Uint => wgpu::TextureSampleType::Uint,
+                        naga::ScalarKind::Bool => return Err("bool textures".to_string()),
+                    },
+                    view_dimension,
+                    multisampled: multi,
+                }),
+                naga::ImageClass::Depth { multi } => Ok(wgpu::BindingType::Texture {
+                    sample_type: wgpu::TextureSampleType::Depth,
+                    view_dimension,
+                    multisampled: multi,
+                }),
+                naga::ImageClass::Storage { format, access } => {
+                    Ok(wgpu::BindingType::StorageTexture {
+                        access: match (
+                            access.contains(naga::StorageAccess::LOAD),
+                            access.contains(naga::StorageAccess::STORE),
+                        ) {
+                            (true, true) => wgpu::StorageTextureAccess::ReadWrite,
+                            (true, false) => wgpu::StorageTextureAccess::ReadOnly,
+                            _ => wgpu::StorageTextureAccess::WriteOnly,
+                        },
+                        format: storage_format(format)?,
+                        view_dimension,
+                    })
+                }
+            }
+        }
+        (space, inner) => Err(format!("{:?} in the {:?} address space", inner, space)),
+    }
+}
+
+fn storage_format(format: naga::StorageFormat) -> Result<wgpu::TextureFormat, String> {
+    use naga::StorageFormat as S;
+    use wgpu::TextureFormat as T;
+    Ok(match format {
+        S::R32Uint => T::R32Uint,
+        S::R32Sint => T::R32Sint,
+        S::R32Float => T::R32Float,
+        S::Rg32Uint => T::Rg32Uint,
+        S::Rg32Sint => T::Rg32Sint,
+        S::Rg32Float => T::Rg32Float,
+        S::Rgba8Unorm => T::Rgba8Unorm,
+        S::Rgba8Snorm => T::Rgba8Snorm,
+        S::Rgba8Uint => T::Rgba8Uint,
+        S::Rgba8Sint => T::Rgba8Sint,
+        S::Rgba16Uint => T::Rgba16Uint,
+        S::Rgba16Sint => T::Rgba16Sint,
+        S::Rgba16Float => T::Rgba16Float,
+        S::Rgba32Uint => T::Rgba32Uint,
+        S::Rgba32Sint => T::Rgba32Sint,
+        S::Rgba32Float => T::Rgba32Float,
+        format => return Err(format!("storage textures of {:?}", format)),
+    })
+}
+
+/// Explicit layouts for a `ShaderLayout`, to share between pipelines and bind groups.
+///
+/// Made once and shared with a `HotPipeline`'s build function, e.g. in an `Rc`, the layout
+/// stays the same across reloads, so bind groups made from it do too. A reload that changes
+/// the shader's bindings fails to build the pipeline instead.
+pub struct PipelineLayout {
+    shader_layout: ShaderLayout,
+    bind_group_layouts: Vec<wgpu::BindGroupLayout>,
+    pipeline_layout: wgpu::PipelineLayout,
+}
+
+impl PipelineLayout {
+    pub fn new(device: &wgpu::Device, label: &str, shader_layout: ShaderLayout) -> Self {
+        let bind_group_layouts: Vec<wgpu::BindGroupLayout> = shader_layout
+            .groups
+            .iter()
+            .enumerate()
+            .map(|(group, entries)| {
+                crate::logging::create_bind_group_layout(
+                    device,
+                    &wgpu::BindGroupLayoutDescriptor {
+                        label: Some(&format!("{} group {}", label, group)),
+                        entries,
+                    },
+                )
+            })
+            .collect();
+        let pipeline_layout = crate::logging::create_pipeline_layout(
+            device,
+            &wgpu::PipelineLayoutDescriptor {
+                label: Some(label),
+                bind_group_layouts: &bind_group_layouts.iter().collect::<Vec<_>>(),
+                push_constant_ranges: &[],
+            },
+        );
+        Self {
+            shader_layout,
+            bind_group_layouts,
+            pipeline_layout,
+        }
+    }
+
+    /// Reflects `source`, see `ShaderLayout::reflect`.
+    pub fn from_wgsl(
+        device: &wgpu::Device,
+        label: &str,
+        source: &str,
+    ) -> Result<Self, ReflectError> {
+        Ok(Self::new(device, label, ShaderLayout::reflect(source)?))
+    }
+
+    /// For `layout` in pipeline descriptors.
+    pub fn layout(&self) -> &wgpu::PipelineLayout {
+        &self.pipeline_layout
+    }
+
+    pub fn shader_layout(&self) -> &ShaderLayout {
+        &self.shader_layout
+    }
+
+    pub fn bind_group_layout(&self, group: u32) -> Option<&wgpu::BindGroupLayout> {
+        self.bind_group_layouts.get(group as usize)
+    }
+
+    /// Starts a bind group for `group`. Add every binding, then `create` it.
+    pub fn bind_group(&self, group: u32) -> BindGroupBuilder<'_> {
+        BindGroupBuilder {
+            layout: self,
+            group,
+            label: None,
+            resources: Vec::new(),
+        }
+    }
+}
+
+enum Resource<'a> {
+    Buffer(wgpu::BufferBinding<'a>),
+    TextureView(&'a wgpu::TextureView),
+    Sampler(&'a wgpu::Sampler),
+}
+
+impl Resource<'_> {
+    fn name(&self) -> &'static str {
+        match self {
+            Resource::Buffer(_) => "a buffer",
+            Resource::TextureView(_) => "a texture view",
+            Resource::Sampler(_) => "a sampler",
+        }
+    }
+}
+
+/// Resources for one bind group, checked against its layout by `create`.
+pub struct BindGroupBuilder<'a> {
+    layout: &'a PipelineLayout,
+    group: u32,
+    label: Option<&'a str>,
+    resources: Vec<(u32, Resource<'a>)>,
+}
+
+impl<'a> BindGroupBuilder<'a> {
+    pub fn label(mut self, label: &'a str) -> Self {
+        self.label = Some(label);
+        self
+    }
+
+    /// Binds the whole buffer.
+    pub fn buffer(self, binding: u32, buffer: &'a wgpu::Buffer) -> Self {
+        self.buffer_binding(binding, buffer.as_entire_buffer_binding())
+    }
+
+    pub fn buffer_binding(mut self, binding: u32, buffer_binding: wgpu::BufferBinding<'a>) -> Self {
+        self.resources
+            .push((binding, Resource::Buffer(buffer_binding)));
+        self
+    }
+
+    pub fn texture_view(mut self, binding: u32, view: &'a wgpu::TextureView) -> Self {
+        self.resources.push((binding, Resource::TextureView(view)));
+        self
+    }
+
+    pub fn sampler(mut self, binding: u32, sampler: &'a wgpu::Sampler) -> Self {
+        self.resources.push((binding, Resource::Sampler(sampler)));
+        self
+    }
+
+    pub fn create(self, device: &wgpu::Device) -> Result<wgpu::BindGroup, BindingError> {
+        let group = self.group;
+        let Some(bind_group_layout) = self.layout.bind_group_layout(group) else {
+            return Err(BindingError::NoSuchGroup(group));
+        };
+        let entries = &self.layout.shader_layout.groups[group as usize];
+        for (i, (binding, resource)) in self.resources.iter().enumerate() {
+            let binding = *binding;
+            if self.resources[..i]
+                .iter()
+                .any(|(other, _)| *other == binding)
+            {
+                return Err(BindingError::Duplicate { group, binding });
+            }
+            let Some(entry) = entries.iter().find(|entry| entry.binding == binding) else {
+                return Err(BindingError::NoSuchBinding { group, binding });
+            };
+            check_resource(group, entry, resource)?;
+        }
+        if let Some(entry) = entries.iter().find(|entry| {
+            !self
+                .resources
+                .iter()
+                .any(|(binding, _)| *binding == entry.binding)
+        }) {
+            return Err(BindingError::Missing {
+                group,
+                binding: entry.binding,
+            });
+        }
+        let entries: Vec<wgpu::BindGroupEntry> = self
+            .resources
+            .into_iter()
+            .map(|(binding, resource)| wgpu::BindGroupEntry {
+                binding,
+                resource: match resource {
+                    Resource::Buffer(buffer_binding) => {
+                        wgpu::BindingResource::Buffer(buffer_binding)
+                    }
+                    Resource::TextureView(view) => wgpu::BindingResource::TextureView(view),
+                    Resource::Sampler(sampler) => wgpu::BindingResource::Sampler(sampler),
+                },
+            })
+            .collect();
+        Ok(device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: self.label,
+            layout: bind_group_layout,
+            entries: &entries,
+        }))
+    }
+}
+
+/// What wgpu can tell us about the resource without validating it itself.
+fn check_resource(
+    group: u32,
+    entry: &wgpu::BindGroupLayoutEntry,
+    resource: &Resource,
+) -> Result<(), BindingError> {
+    let binding = entry.binding;
+    match (&entry.ty, resource) {
+        (
+            wgpu::BindingType::Buffer {
+                ty,
+                min_binding_size,
+                ..
+            },
+            Resource::Buffer(buffer_binding),
+        ) => {
+            let usage = match ty {
+                wgpu::BufferBindingType::Uniform => wgpu::BufferUsages::UNIFORM,
+                wgpu::BufferBindingType::Storage { .. } => wgpu::BufferUsages::STORAGE,
+            };
+            if !buffer_binding.buffer.usage().contains(usage) {
+                return Err(BindingError::MissingUsage {
+                    group,
+                    binding,
+                    usage,
+                });
+            }
+            let buffer_size = buffer_binding.buffer.size();
+            let offset = buffer_binding.offset;
+            let size = match buffer_binding.size {
+                Some(size) => size.get(),
+                None => buffer_size.saturating_sub(offset),
+            };
+            // Saturating, so a range too big for a u64 is out of range rather than wrapping.
+            let end = offset.saturating_add(size);
+            if end > buffer_size {
+                return Err(BindingError::OutOfRange {
+                    group,
+                    binding,
+                    end,
+                    buffer_size,
+                });
+            }
+            let min_size = min_binding_size.map_or(0, |size| size.get());
+            if size < min_size {
+                return Err(BindingError::TooSmall {
+                    group,
+                    binding,
+                    size,
+                    min_size,
+                });
+            }
+            Ok(())
+        }
+        (
+            wgpu::BindingType::Texture { .. } | wgpu::BindingType::StorageTexture { .. },
+            Resource::TextureView(_),
+        )
+        | (wgpu::BindingType::Sampler(_), Resource::Sampler(_)) => Ok(()),
+        (expected, resource) => Err(BindingError::WrongType {
+            group,
+            binding,
+            expected: *expected,
+            found: resource.name(),
+        }),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BindingError {
+    NoSuchGroup(u32),
+    NoSuchBinding {
+        group: u32,
+        binding: u32,
+    },
+    Duplicate {
+        group: u32,
+        binding: u32,
+    },
+    Missing {
+        group: u32,
+        binding: u32,
+    },
+    WrongType {
+        group: u32,
+        binding: u32,
+        expected: wgpu::BindingType,
+        found: &'static str,
+    },
+    MissingUsage {
+        group: u32,
+        binding: u32,
+        usage: wgpu::BufferUsages,
+    },
+    /// The buffer, or the bound range of it, is smaller than the shader's type.
+    TooSmall {
+        group: u32,
+        binding: u32,
+        size: u64,
+        min_size: u64,
+    },
+    /// The bound range, `offset` plus `size`, goes past the end of the buffer.
+    OutOfRange {
+        group: u32,
+        binding: u32,
+        end: u64,
+        buffer_size: u64,
+    },
+}
+
+impl std::fmt::Display for BindingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BindingError::NoSuchGroup(group) => {
+                write!(f, "The shader has no @group({})", group)
+            }
+            BindingError::NoSuchBinding { group, binding } => write!(
+                f,
+                "The shader has no @group({}) @binding({})",
+                group, binding
+            ),
+            BindingError::Duplicate { group, binding } => write!(
+                f,
+                "@group({}) @binding({}) is bound more than once",
+                group, binding
+            ),
+            BindingError::Missing { group, binding } => {
+                write!(f, "@group({}) @binding({}) isn't bound", group, binding)
+            }
+            BindingError::WrongType {
+                group,
+                binding,
+                expected,
+                found,
+            } => write!(
+                f,
+                "@group({}) @binding({}) is {:?}, not {}",
+                group, binding, expected, found
+            ),
+            BindingError::MissingUsage {
+                group,
+                binding,
+                usage,
+            } => write!(
+                f,
+                "The buffer for @group({}) @binding({}) needs {:?} usage",
+                group, binding, usage
+            ),
+            BindingError::TooSmall {
+                group,
+                binding,
+                size,
+                min_size,
+            } => write!(
+                f,
+                "The buffer for @group({}) @binding({}) is {} bytes, the shader needs {}",
+                group, binding, size, min_size
+            ),
+            BindingError::OutOfRange {
+                group,
+                binding,
+                end,
+                buffer_size,
+            } => write!(
+                f,
+                "The buffer for @group({}) @binding({}) is {} bytes, but the bound range ends at {}",
+                group, binding, buffer_size, end
+            ),
+        }
+    }
+}
+
+impl std::error::Error for BindingError {}
@@ -0,0 +1,142 @@
+//! Scenes of objects, for demos to draw instead of random ones, so a run can be repeated,
+//! shared, or edited by hand.
+//!
+//! A scene file looks like this:
+//!
+//! ```ron
+//! (
+//!     objects: [
+//!         (color: (1.0, 0.5, 0.0), scale: (0.5, 0.25), offset: (-0.5, 0.0)),
+//!         (color: (0.0, 0.5, 1.0), scale: (0.25, 0.5), offset: (0.5, 0.0)),
+//!     ],
+//! )
+//! ```
+//!
+//! Demos that read one take `--scene PATH` to draw it, and `--dump-scene PATH` to save the
+//! scene they would draw, e.g. a random one from `--seed`, and exit.
+
+use crate::game::ArgsError;
+
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct SceneObject {
+    /// Linear RGB. Demos that color objects some other way ignore it.
+    pub color: glam::Vec3,
+    pub scale: glam::Vec2,
+    /// In clip space, so -1.0..1.0 is on screen.
+    pub offset: glam::Vec2,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Scene {
+    pub objects: Vec<SceneObject>,
+}
+
+impl std::str::FromStr for Scene {
+    type Err = SceneError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(ron::from_str(s)?)
+    }
+}
+
+impl Scene {
+    pub fn load(path: impl AsRef<std::path::Path>) -> Result<Self, SceneError> {
+        std::fs::read_to_string(path)?.parse()
+    }
+
+    pub fn save(&self, path: impl AsRef<std::path::Path>) -> Result<(), SceneError> {
+        Ok(std::fs::write(path, self.to_ron())?)
+    }
+
+    /// One object per line.
+    pub fn to_ron(&self) -> String {
+        let config = ron::ser::PrettyConfig::new().depth_limit(2);
+        // Plain structs of floats always serialize.
+        ron::ser::to_string_pretty(self, config).unwrap() + "\n"
+    }
+
+    /// The scene `--scene` names, or else `random`'s, which is given `rng` so it's seeded by
+    /// `--seed`. With `--dump-scene`, saves the scene and exits instead of returning.
+    ///
+    /// Exits if the options are invalid or the scene can't be loaded.
+    pub fn from_args<R>(rng: &mut R, random: impl FnOnce(&mut R) -> Scene) -> Scene {
+        let options = SceneOptions::from_args(std::env::args()).unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        });
+        let scene = match &options.scene {
+            Some(path) => Scene::load(path).unwrap_or_else(|e| {
+                eprintln!("{}: {}", path.display(), e);
+                std::process::exit(1);
+            }),
+            None => random(rng),
+        };
+        if let Some(path) = &options.dump {
+            if let Err(e) = scene.save(path) {
+                eprintln!("{}: {}", path.display(), e);
+                std::process::exit(1);
+            }
+            println!(
+                "Saved {} objects to {}",
+                scene.objects.len(),
+                path.display()
+            );
+            std::process::exit(0);
+        }
+        scene
+    }
+}
+
+/// `[--scene PATH] [--dump-scene PATH]`, see `Scene::from_args`.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct SceneOptions {
+    pub scene: Option<std::path::PathBuf>,
+    pub dump: Option<std::path::PathBuf>,
+}
+
+impl SceneOptions {
+    /// `args` starts with the program name, like `std::env::args`. Other arguments are
+    /// ignored.
+    pub fn from_args(args: impl IntoIterator<Item = String>) -> Result<Self, ArgsError> {
+        let mut args = args.into_iter().skip(1);
+        let mut options = Self::default();
+        while let Some(arg) = args.next() {
+            let (flag, option) = match arg.as_str() {
+                "--scene" => ("--scene", &mut options.scene),
+                "--dump-scene" => ("--dump-scene", &mut options.dump),
+                _ => continue,
+            };
+            *option = Some(args.next().ok_or(ArgsError::MissingValue(flag))?.into());
+        }
+        Ok(options)
+    }
+}
+
+#[derive(Debug)]
+pub enum SceneError {
+    Io(std::io::Error),
+    Parse(ron::error::SpannedError),
+}
+
+impl std::fmt::Display for SceneError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SceneError::Io(e) => write!(f, "could not read or write scene file: {}", e),
+            SceneError::Parse(e) => write!(f, "could not parse scene file: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for SceneError {}
+
+impl From<std::io::Error> for SceneError {
+    fn from(e: std::io::Error) -> Self {
+        SceneError::Io(e)
+    }
+}
+
+impl From<ron::error::SpannedError> for SceneError {
+    fn from(e: ron::error::SpannedError) -> Self {
+        SceneError::Parse(e)
+    }
+}
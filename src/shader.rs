@@ -0,0 +1,185 @@
+//! WGSL shaders from `shaders/`, reloaded from disk when they change.
+//!
+//! Debug builds read the file at runtime, and check it for changes at most every
+//! `POLL_INTERVAL`. Release builds use the source embedded at compile time, and never reload.
+
+/// How often debug builds check shader files for changes.
+pub const POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// A `ShaderFile` for `shaders/<name>`, embedding its source for release builds.
+#[macro_export]
+macro_rules! shader_file {
+    ($name:literal) => {
+        $crate::shader::ShaderFile::new(
+            concat!(env!("CARGO_MANIFEST_DIR"), "/shaders/", $name),
+            include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/shaders/", $name)),
+        )
+    };
+}
+
+/// Use `shader_file!` to create one.
+#[derive(Debug, Clone)]
+pub struct ShaderFile {
+    path: &'static str,
+    embedded: &'static str,
+    prelude: String,
+    modified: Option<std::time::SystemTime>,
+    last_poll: Option<std::time::Instant>,
+}
+
+impl ShaderFile {
+    pub fn new(path: &'static str, embedded: &'static str) -> Self {
+        Self {
+            path,
+            embedded,
+            prelude: String::new(),
+            modified: None,
+            last_poll: None,
+        }
+    }
+
+    /// Puts `prelude` before the source, e.g. struct declarations from `wgsl::Prelude`.
+    ///
+    /// Line numbers in compile errors count the prelude's lines too.
+    pub fn with_prelude(mut self, prelude: String) -> Self {
+        self.prelude = prelude;
+        self
+    }
+
+    pub fn path(&self) -> &'static str {
+        self.path
+    }
+
+    /// The prelude, then the source on disk in debug builds, falling back to the embedded
+    /// source if it's gone.
+    pub fn source(&mut self) -> std::borrow::Cow<'static, str> {
+        let source: std::borrow::Cow<'static, str> = if !cfg!(debug_assertions) {
+            self.embedded.into()
+        } else {
+            self.modified = self.read_modified();
+            match std::fs::read_to_string(self.path) {
+                Ok(source) => source.into(),
+                Err(e) => {
+                    log::warn!("Can't read {}, using the embedded source: {}", self.path, e);
+                    self.embedded.into()
+                }
+            }
+        };
+        if self.prelude.is_empty() {
+            source
+        } else {
+            format!("{}{}", self.prelude, source).into()
+        }
+    }
+
+    fn read_modified(&self) -> Option<std::time::SystemTime> {
+        std::fs::metadata(self.path).and_then(|m| m.modified()).ok()
+    }
+
+    /// True if the file changed since `source` was last called. Always false in release builds.
+    pub fn changed(&mut self) -> bool {
+        if !cfg!(debug_assertions) {
+            return false;
+        }
+        let now = std::time::Instant::now();
+        if self
+            .last_poll
+            .is_some_and(|last_poll| now - last_poll < POLL_INTERVAL)
+        {
+            return false;
+        }
+        self.last_poll = Some(now);
+        let modified = self.read_modified();
+        modified.is_some() && modified != self.modified
+    }
+
+    /// Compiles the current source, returning the validation error if it's invalid.
+    pub fn compile(&mut self, device: &wgpu::Device) -> Result<wgpu::ShaderModule, wgpu::Error> {
+        let source = self.source();
+        device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let module = crate::logging::create_shader_module(
+            device,
+            wgpu::ShaderModuleDescriptor {
+                label: Some(self.path),
+                source: wgpu::ShaderSource::Wgsl(source),
+            },
+        );
+        match pollster::block_on(device.pop_error_scope()) {
+            Some(error) => Err(error),
+            None => Ok(module),
+        }
+    }
+}
+
+type BuildPipeline<P> = Box<dyn Fn(&wgpu::Device, &wgpu::ShaderModule) -> P>;
+
+/// One or more pipelines built from a `ShaderFile`, rebuilt when the file changes.
+///
+/// `P` is whatever the build function returns, e.g. a `wgpu::RenderPipeline`,
+/// or a tuple of pipelines that share the shader.
+pub struct HotPipeline<P> {
+    shader: ShaderFile,
+    build: BuildPipeline<P>,
+    pipeline: P,
+}
+
+impl<P> HotPipeline<P> {
+    /// # Panics
+    ///
+    /// If the shader or the pipeline is invalid. After that, errors keep the last good pipeline.
+    pub fn new(
+        device: &wgpu::Device,
+        mut shader: ShaderFile,
+        build: impl Fn(&wgpu::Device, &wgpu::ShaderModule) -> P + 'static,
+    ) -> Self {
+        let pipeline = match Self::try_build(device, &mut shader, &build) {
+            Ok(pipeline) => pipeline,
+            Err(error) => panic!("{}: {}", shader.path(), error),
+        };
+        Self {
+            shader,
+            build: Box::new(build),
+            pipeline,
+        }
+    }
+
+    fn try_build(
+        device: &wgpu::Device,
+        shader: &mut ShaderFile,
+        build: &dyn Fn(&wgpu::Device, &wgpu::ShaderModule) -> P,
+    ) -> Result<P, wgpu::Error> {
+        let module = shader.compile(device)?;
+        device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let pipeline = build(device, &module);
+        match pollster::block_on(device.pop_error_scope()) {
+            Some(error) => Err(error),
+            None => Ok(pipeline),
+        }
+    }
+
+    pub fn get(&self) -> &P {
+        &self.pipeline
+    }
+
+    /// Call once a frame. Rebuilds the pipeline if the shader changed and still compiles,
+    /// and prints the error if it doesn't.
+    ///
+    /// Returns true if the pipeline was replaced. Bind groups created from its layout,
+    /// as with `get_bind_group_layout`, must be recreated.
+    pub fn reload_if_changed(&mut self, device: &wgpu::Device) -> bool {
+        if !self.shader.changed() {
+            return false;
+        }
+        match Self::try_build(device, &mut self.shader, &self.build) {
+            Ok(pipeline) => {
+                log::info!("Reloaded {}", self.shader.path());
+                self.pipeline = pipeline;
+                true
+            }
+            Err(error) => {
+                eprintln!("{}: {}", self.shader.path(), error);
+                false
+            }
+        }
+    }
+}
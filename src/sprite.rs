@@ -0,0 +1,513 @@
+//! Textured sprites, drawn in as few instanced draw calls as their textures allow.
+//!
+//! Sprites are in world units, y up, seen through a camera, see `Camera2D`. A sprite shows part
+//! of its texture, usually a region of a `TextureAtlas`, so many sprites share one texture and
+//! are drawn together.
+//!
+//! An atlas file names regions of its texture in pixels, top left first:
+//!
+//! ```ron
+//! (
+//!     regions: [
+//!         (name: "player", x: 0, y: 0, width: 16, height: 16),
+//!         (name: "coin", x: 16, y: 0, width: 8, height: 8),
+//!     ],
+//! )
+//! ```
+
+use crate::camera::CameraUniform;
+use crate::vertex::VertexLayout;
+use crate::wgsl::WgslLayout;
+
+/// A rectangle of a texture in UV coordinates, 0.0 to 1.0 with y down.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UvRect {
+    pub min: glam::Vec2,
+    pub max: glam::Vec2,
+}
+
+impl UvRect {
+    pub const FULL: Self = Self {
+        min: glam::Vec2::ZERO,
+        max: glam::Vec2::ONE,
+    };
+
+    pub fn from_pixels(region: &AtlasRegion, texture_size: (u32, u32)) -> Self {
+        let texture_size = glam::Vec2::new(texture_size.0 as f32, texture_size.1 as f32);
+        let min = glam::Vec2::new(region.x as f32, region.y as f32);
+        let size = glam::Vec2::new(region.width as f32, region.height as f32);
+        Self {
+            min: min / texture_size,
+            max: (min + size) / texture_size,
+        }
+    }
+}
+
+/// A region of an atlas's texture, in pixels.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize)]
+pub struct AtlasRegion {
+    /// Empty for regions only looked up by index, like a grid's.
+    #[serde(default)]
+    pub name: String,
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+#[derive(serde::Deserialize)]
+struct AtlasFile {
+    regions: Vec<AtlasRegion>,
+}
+
+/// Regions of one texture, looked up by index or name.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextureAtlas {
+    texture_size: (u32, u32),
+    regions: Vec<AtlasRegion>,
+    names: std::collections::HashMap<String, usize>,
+}
+
+impl TextureAtlas {
+    /// Fails if a region isn't inside the texture.
+    pub fn new(texture_size: (u32, u32), regions: Vec<AtlasRegion>) -> Result<Self, AtlasError> {
+        let names = regions
+            .iter()
+            .enumerate()
+            .filter(|(_, region)| !region.name.is_empty())
+            .map(|(index, region)| (region.name.clone(), index))
+            .collect();
+        let atlas = Self {
+            texture_size,
+            regions,
+            names,
+        };
+        // Checked, so regions near u32::MAX are out of bounds rather than wrapping around.
+        let fits = |start: u32, size: u32, limit: u32| {
+            start.checked_add(size).is_some_and(|end| end <= limit)
+        };
+        for (index, region) in atlas.regions.iter().enumerate() {
+            if !fits(region.x, region.width, texture_size.0)
+                || !fits(region.y, region.height, texture_size.1)
+            {
+                return Err(AtlasError::OutOfBounds {
+                    index,
+                    name: region.name.clone(),
+                });
+            }
+        }
+        Ok(atlas)
+    }
+
+    /// Cells of `cell_size` pixels, numbered across then down. Leftover pixels at the right and
+    /// bottom edges are left out.
+    ///
+    /// Fails if a cell is 0 pixels wide or high.
+    pub fn grid(texture_size: (u32, u32), cell_size: (u32, u32)) -> Result<Self, AtlasError> {
+        if cell_size.0 == 0 || cell_size.1 == 0 {
+            return Err(AtlasError::EmptyCell);
+        }
+        let (columns, rows) = (texture_size.0 / cell_size.0, texture_size.1 / cell_size.1);
+        let regions = (0..rows)
+            .flat_map(|row| {
+                (0..columns).map(move |column| AtlasRegion {
+                    name: String::new(),
+                    x: column * cell_size.0,
+                    y: row * cell_size.1,
+                    width: cell_size.0,
+                    height: cell_size.1,
+                })
+            })
+            .collect();
+        Self::new(texture_size, regions)
+    }
+
+    /// `texture_size` is the size of the atlas's image, which the file doesn't repeat.
+    pub fn from_ron(source: &str, texture_size: (u32, u32)) -> Result<Self, AtlasError> {
+        let file: AtlasFile = ron::from_str(source)?;
+        Self::new(texture_size, file.regions)
+    }
+
+    pub fn load(
+        path: impl AsRef<std::path::Path>,
+        texture_size: (u32, u32),
+    ) -> Result<Self, AtlasError> {
+        Self::from_ron(&std::fs::read_to_string(path)?, texture_size)
+    }
+
+    pub fn len(&self) -> usize {
+        self.regions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.regions.is_empty()
+    }
+
+    pub fn region(&self, index: usize) -> &AtlasRegion {
+        &self.regions[index]
+    }
+
+    pub fn index(&self, name: &str) -> Option<usize> {
+        self.names.get(name).copied()
+    }
+
+    /// # Panics
+    ///
+    /// If `index` isn't less than `len`.
+    pub fn uv_rect(&self, index: usize) -> UvRect {
+        UvRect::from_pixels(&self.regions[index], self.texture_size)
+    }
+
+    pub fn named_uv_rect(&self, name: &str) -> Option<UvRect> {
+        self.index(name).map(|index| self.uv_rect(index))
+    }
+}
+
+#[derive(Debug)]
+pub enum AtlasError {
+    Io(std::io::Error),
+    Parse(ron::error::SpannedError),
+    OutOfBounds {
+        index: usize,
+        name: String,
+    },
+    /// A grid's cells are 0 pixels wide or high.
+    EmptyCell,
+}
+
+impl std::fmt::Display for AtlasError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AtlasError::Io(e) => write!(f, "could not read atlas file: {}", e),
+            AtlasError::Parse(e) => write!(f, "could not parse atlas file: {}", e),
+            AtlasError::OutOfBounds { index, name } => {
+                write!(
+                    f,
+                    "atlas region {} {:?} is outside the texture",
+                    index, name
+                )
+            }
+            AtlasError::EmptyCell => write!(f, "atlas grid cells must be at least 1 pixel"),
+        }
+    }
+}
+
+impl std::error::Error for AtlasError {}
+
+impl From<std::io::Error> for AtlasError {
+    fn from(e: std::io::Error) -> Self {
+        AtlasError::Io(e)
+    }
+}
+
+impl From<ron::error::SpannedError> for AtlasError {
+    fn from(e: ron::error::SpannedError) -> Self {
+        AtlasError::Parse(e)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sprite {
+    /// Where `origin` goes, in world units.
+    pub position: glam::Vec2,
+    /// In world units.
+    pub size: glam::Vec2,
+    /// Counter-clockwise around `origin`, in radians.
+    pub rotation: f32,
+    /// The point of the sprite at `position`, as a fraction of its size from the bottom left.
+    pub origin: glam::Vec2,
+    /// The part of the texture shown, e.g. from `TextureAtlas::uv_rect`.
+    pub uv_rect: UvRect,
+    /// Multiplies the texture's linear RGBA.
+    pub tint: glam::Vec4,
+    pub flip_x: bool,
+    pub flip_y: bool,
+    /// Higher layers are drawn over lower ones.
+    pub layer: i32,
+}
+
+impl Sprite {
+    /// The whole texture, untinted, centered on `position`.
+    pub fn new(position: glam::Vec2, size: glam::Vec2) -> Self {
+        Self {
+            position,
+            size,
+            rotation: 0.0,
+            origin: glam::Vec2::splat(0.5),
+            uv_rect: UvRect::FULL,
+            tint: glam::Vec4::ONE,
+            flip_x: false,
+            flip_y: false,
+            layer: 0,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(PartialEq, Debug, Clone, Copy, bytemuck::Zeroable, bytemuck::Pod, VertexLayout)]
+#[vertex(step_mode = Instance)]
+struct SpriteInstance {
+    #[vertex(location = 1)]
+    position: glam::Vec2,
+    size: glam::Vec2,
+    origin: glam::Vec2,
+    /// The cosine and sine of the rotation, so the shader doesn't work them out per vertex.
+    rotation: glam::Vec2,
+    uv_rect: glam::Vec4,
+    tint: glam::Vec4,
+}
+
+impl From<&Sprite> for SpriteInstance {
+    fn from(sprite: &Sprite) -> Self {
+        let UvRect { mut min, mut max } = sprite.uv_rect;
+        if sprite.flip_x {
+            std::mem::swap(&mut min.x, &mut max.x);
+        }
+        if sprite.flip_y {
+            std::mem::swap(&mut min.y, &mut max.y);
+        }
+        Self {
+            position: sprite.position,
+            size: sprite.size,
+            origin: sprite.origin,
+            rotation: glam::Vec2::from_angle(sprite.rotation),
+            uv_rect: glam::Vec4::new(min.x, min.y, max.x, max.y),
+            tint: sprite.tint,
+        }
+    }
+}
+
+/// A texture added to a `SpriteBatch`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TextureId(usize);
+
+/// A sprite waiting for `prepare`.
+#[derive(Debug, Clone, Copy)]
+struct QueuedSprite {
+    layer: i32,
+    texture: TextureId,
+    instance: SpriteInstance,
+}
+
+/// Consecutive instances drawn with one texture.
+#[derive(Debug, Clone)]
+struct Batch {
+    texture: TextureId,
+    instances: std::ops::Range<u32>,
+}
+
+/// What the last `prepare` uploaded.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SpriteStats {
+    pub sprites: usize,
+    /// One per run of sprites with the same texture, after sorting.
+    pub draw_calls: usize,
+}
+
+/// Collects `draw` calls during a frame, then draws them sorted by layer and, within a layer,
+/// by texture, with one instanced draw call per texture per layer.
+///
+/// Within a layer, sprites with different textures don't overlap in the order they were drawn.
+/// Put sprites that must on different layers.
+pub struct SpriteBatch {
+    render_pipeline: crate::shader::HotPipeline<wgpu::RenderPipeline>,
+    layout: std::rc::Rc<crate::reflect::PipelineLayout>,
+    corner_buffer: wgpu::Buffer,
+    index_buffer: wgpu::Buffer,
+    camera_buffer: wgpu::Buffer,
+    camera_bind_group: wgpu::BindGroup,
+    texture_bind_groups: Vec<wgpu::BindGroup>,
+    instance_buffer: crate::buffer::DynamicBuffer<SpriteInstance>,
+    queued: Vec<QueuedSprite>,
+    instances: Vec<SpriteInstance>,
+    batches: Vec<Batch>,
+}
+
+impl SpriteBatch {
+    /// What `vertex_main` in sprite.wgsl takes: the corners, and a `SpriteInstance` per sprite.
+    pub const VERTEX_BUFFERS: &'static [wgpu::VertexBufferLayout<'static>] = &[
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<glam::Vec2>() as u64,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &wgpu::vertex_attr_array![0 => Float32x2],
+        },
+        SpriteInstance::LAYOUT,
+    ];
+
+    /// Declares `CameraUniform` for sprite.wgsl. tests/shaders.rs validates the shader with it.
+    pub fn shader_prelude() -> String {
+        crate::wgsl::Prelude::new()
+            .with::<CameraUniform>()
+            .to_wgsl()
+    }
+
+    pub fn new(device: &wgpu::Device, format: wgpu::TextureFormat, sample_count: u32) -> Self {
+        let mut shader = crate::shader_file!("sprite.wgsl").with_prelude(Self::shader_prelude());
+        let layout = std::rc::Rc::new(
+            crate::reflect::PipelineLayout::from_wgsl(device, "sprite", &shader.source())
+                .unwrap_or_else(|e| panic!("{}", e)),
+        );
+        let pipeline_layout = layout.clone();
+        let render_pipeline =
+            crate::shader::HotPipeline::new(device, shader, move |device, shader_module| {
+                crate::logging::create_render_pipeline(
+                    device,
+                    &wgpu::RenderPipelineDescriptor {
+                        label: Some("sprite render pipeline"),
+                        layout: Some(pipeline_layout.layout()),
+                        vertex: wgpu::VertexState {
+                            module: shader_module,
+                            entry_point: "vertex_main",
+                            buffers: Self::VERTEX_BUFFERS,
+                        },
+                        primitive: wgpu::PrimitiveState::default(),
+                        depth_stencil: None,
+                        multisample: wgpu::MultisampleState {
+                            count: sample_count,
+                            mask: !0,
+                            alpha_to_coverage_enabled: false,
+                        },
+                        fragment: Some(wgpu::FragmentState {
+                            module: shader_module,
+                            entry_point: "fragment_main",
+                            targets: &[Some(wgpu::ColorTargetState {
+                                format,
+                                blend: Some(wgpu::BlendState::ALPHA_BLENDING),
+                                write_mask: wgpu::ColorWrites::ALL,
+                            })],
+                        }),
+                        multiview: None,
+                    },
+                )
+            });
+        let corner_buffer = crate::logging::create_buffer_init(
+            device,
+            "sprite corner buffer",
+            wgpu::BufferUsages::VERTEX,
+            bytemuck::cast_slice(crate::vertex::QUAD_CORNERS),
+        );
+        let index_buffer = crate::logging::create_buffer_init(
+            device,
+            "sprite index buffer",
+            wgpu::BufferUsages::INDEX,
+            bytemuck::cast_slice(crate::vertex::QUAD_INDICES),
+        );
+        let camera_buffer = crate::logging::create_buffer(
+            device,
+            &wgpu::BufferDescriptor {
+                label: Some("sprite camera buffer"),
+                size: CameraUniform::SIZE,
+                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            },
+        );
+        let camera_bind_group = layout
+            .bind_group(0)
+            .label("sprite camera bind group")
+            .buffer(0, &camera_buffer)
+            .create(device)
+            .unwrap_or_else(|e| panic!("{}", e));
+        let instance_buffer = crate::buffer::DynamicBuffer::new(
+            device,
+            "sprite instance buffer",
+            wgpu::BufferUsages::VERTEX,
+            1024,
+        );
+        Self {
+            render_pipeline,
+            layout,
+            corner_buffer,
+            index_buffer,
+            camera_buffer,
+            camera_bind_group,
+            texture_bind_groups: Vec::new(),
+            instance_buffer,
+            queued: Vec::new(),
+            instances: Vec::new(),
+            batches: Vec::new(),
+        }
+    }
+
+    /// Makes `texture` available to `draw`. Its bind group keeps it alive, so it can be dropped.
+    pub fn add_texture(
+        &mut self,
+        device: &wgpu::Device,
+        texture: &crate::texture::Texture,
+    ) -> TextureId {
+        let bind_group = self
+            .layout
+            .bind_group(1)
+            .label("sprite texture bind group")
+            .texture_view(0, texture.view())
+            .sampler(1, texture.sampler())
+            .create(device)
+            .unwrap_or_else(|e| panic!("{}", e));
+        self.texture_bind_groups.push(bind_group);
+        TextureId(self.texture_bind_groups.len() - 1)
+    }
+
+    /// Queues a sprite for this frame.
+    ///
+    /// # Panics
+    ///
+    /// If `texture` came from a different `SpriteBatch`.
+    pub fn draw(&mut self, texture: TextureId, sprite: &Sprite) {
+        assert!(texture.0 < self.texture_bind_groups.len());
+        self.queued.push(QueuedSprite {
+            layer: sprite.layer,
+            texture,
+            instance: sprite.into(),
+        });
+    }
+
+    /// Sorts and uploads the sprites queued since the last `prepare`, and starts a new batch.
+    pub fn prepare(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, camera: &CameraUniform) {
+        self.render_pipeline.reload_if_changed(device);
+        queue.write_buffer(&self.camera_buffer, 0, &camera.to_bytes());
+        // Stable, so sprites with the same layer and texture keep the order they were drawn in.
+        self.queued
+            .sort_by_key(|sprite| (sprite.layer, sprite.texture));
+        self.instances.clear();
+        self.batches.clear();
+        for (index, sprite) in self.queued.iter().enumerate() {
+            let index = index as u32;
+            match self.batches.last_mut() {
+                Some(batch) if batch.texture == sprite.texture => batch.instances.end = index + 1,
+                _ => self.batches.push(Batch {
+                    texture: sprite.texture,
+                    instances: index..index + 1,
+                }),
+            }
+            self.instances.push(sprite.instance);
+        }
+        self.instance_buffer.write(device, queue, &self.instances);
+        self.queued.clear();
+    }
+
+    pub fn stats(&self) -> SpriteStats {
+        SpriteStats {
+            sprites: self.instances.len(),
+            draw_calls: self.batches.len(),
+        }
+    }
+
+    /// Draws everything uploaded by the last `prepare`, one draw call per batch.
+    pub fn render<'a>(&'a self, render_pass: &mut wgpu::RenderPass<'a>) {
+        if self.batches.is_empty() {
+            return;
+        }
+        render_pass.set_pipeline(self.render_pipeline.get());
+        render_pass.set_bind_group(0, &self.camera_bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.corner_buffer.slice(..));
+        render_pass.set_vertex_buffer(1, self.instance_buffer.slice());
+        render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+        for batch in &self.batches {
+            render_pass.set_bind_group(1, &self.texture_bind_groups[batch.texture.0], &[]);
+            render_pass.draw_indexed(
+                0..crate::vertex::QUAD_INDICES.len() as u32,
+                0,
+                batch.instances.clone(),
+            );
+        }
+    }
+}
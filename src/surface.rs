@@ -0,0 +1,213 @@
+//! Keeps a window surface configured through resizes, minimizes, and lost surfaces.
+
+/// Owns a window, its `wgpu::Surface`, and the surface configuration.
+///
+/// The surface must not outlive the window, so we keep them together.
+///
+/// `surface.get_current_texture().unwrap()` panics when the window is minimized,
+/// and whenever the surface is lost or outdated. `acquire` handles those cases instead.
+pub struct SurfaceManager {
+    // Fields are dropped in order, so the surface goes before the window.
+    surface: wgpu::Surface,
+    config: wgpu::SurfaceConfiguration,
+    window: winit::window::Window,
+}
+
+/// How many times to retry after a timeout before giving up on this frame.
+const TIMEOUT_RETRIES: u32 = 2;
+
+impl SurfaceManager {
+    /// `surface` must have been created from `window`.
+    pub fn new(
+        window: winit::window::Window,
+        surface: wgpu::Surface,
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+    ) -> Self {
+        let size = window.inner_size();
+        let result = Self {
+            surface,
+            config: wgpu::SurfaceConfiguration {
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+                format,
+                width: size.width,
+                height: size.height,
+                present_mode: wgpu::PresentMode::AutoNoVsync,
+                alpha_mode: wgpu::CompositeAlphaMode::Auto,
+                view_formats: vec![format],
+            },
+            window,
+        };
+        result.configure(device);
+        result
+    }
+
+    pub fn window(&self) -> &winit::window::Window {
+        &self.window
+    }
+
+    pub fn format(&self) -> wgpu::TextureFormat {
+        self.config.format
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        (self.config.width, self.config.height)
+    }
+
+    /// A minimized window has a size of zero, and a surface can't be configured with a size of zero.
+    pub fn is_zero_sized(&self) -> bool {
+        self.config.width == 0 || self.config.height == 0
+    }
+
+    fn configure(&self, device: &wgpu::Device) {
+        if !self.is_zero_sized() {
+            log::trace!("Configuring surface: {:?}", self.config);
+            self.surface.configure(device, &self.config);
+        }
+    }
+
+    /// Call on `WindowEvent::Resized`.
+    ///
+    /// Anything else sized to match the window, like a depth buffer, needs resizing too.
+    /// See `RenderTarget::resize`.
+    pub fn resize(&mut self, device: &wgpu::Device, size: winit::dpi::PhysicalSize<u32>) {
+        self.config.width = size.width;
+        self.config.height = size.height;
+        self.configure(device);
+    }
+
+    /// The next texture to render to, or `None` if this frame should be skipped.
+    ///
+    /// Frames are skipped while the window is zero sized,
+    /// and when the surface keeps failing after reconfiguring or retrying.
+    ///
+    /// # Panics
+    ///
+    /// If the GPU is out of memory. There's no recovering from that.
+    pub fn acquire(&mut self, device: &wgpu::Device) -> Option<wgpu::SurfaceTexture> {
+        if self.is_zero_sized() {
+            return None;
+        }
+        let mut timeouts = 0;
+        let mut reconfigured = false;
+        loop {
+            match self.surface.get_current_texture() {
+                Ok(surface_texture) => return Some(surface_texture),
+                Err(wgpu::SurfaceError::Lost | wgpu::SurfaceError::Outdated) => {
+                    // The window changed under us, or the GPU was reset.
+                    if reconfigured {
+                        return None;
+                    }
+                    self.configure(device);
+                    reconfigured = true;
+                }
+                Err(wgpu::SurfaceError::Timeout) => {
+                    if timeouts == TIMEOUT_RETRIES {
+                        return None;
+                    }
+                    timeouts += 1;
+                }
+                Err(wgpu::SurfaceError::OutOfMemory) => {
+                    panic!("The GPU ran out of memory while acquiring the next surface texture")
+                }
+            }
+        }
+    }
+}
+
+/// A texture we render into, which can be recreated at a new size.
+pub struct RenderTarget {
+    label: &'static str,
+    format: wgpu::TextureFormat,
+    sample_count: u32,
+    usage: wgpu::TextureUsages,
+    texture: wgpu::Texture,
+    view: wgpu::TextureView,
+}
+
+impl RenderTarget {
+    pub fn new(
+        device: &wgpu::Device,
+        label: &'static str,
+        size: (u32, u32),
+        format: wgpu::TextureFormat,
+        sample_count: u32,
+        usage: wgpu::TextureUsages,
+    ) -> Self {
+        let texture = Self::create_texture(device, label, size, format, sample_count, usage);
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        Self {
+            label,
+            format,
+            sample_count,
+            usage,
+            texture,
+            view,
+        }
+    }
+
+    fn create_texture(
+        device: &wgpu::Device,
+        label: &'static str,
+        size: (u32, u32),
+        format: wgpu::TextureFormat,
+        sample_count: u32,
+        usage: wgpu::TextureUsages,
+    ) -> wgpu::Texture {
+        crate::logging::create_texture(
+            device,
+            &wgpu::TextureDescriptor {
+                label: Some(label),
+                size: wgpu::Extent3d {
+                    width: size.0,
+                    height: size.1,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count,
+                dimension: wgpu::TextureDimension::D2,
+                format,
+                usage,
+                view_formats: &[format],
+            },
+        )
+    }
+
+    /// Recreates the texture if `size` differs from the current size.
+    ///
+    /// Returns true if it was recreated, so bind groups using the old view must be rebuilt.
+    /// Zero sizes are ignored, and the old texture is kept.
+    pub fn resize(&mut self, device: &wgpu::Device, size: (u32, u32)) -> bool {
+        if size.0 == 0 || size.1 == 0 || size == self.size() {
+            return false;
+        }
+        self.texture = Self::create_texture(
+            device,
+            self.label,
+            size,
+            self.format,
+            self.sample_count,
+            self.usage,
+        );
+        self.view = self
+            .texture
+            .create_view(&wgpu::TextureViewDescriptor::default());
+        true
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        (self.texture.width(), self.texture.height())
+    }
+
+    pub fn format(&self) -> wgpu::TextureFormat {
+        self.format
+    }
+
+    pub fn texture(&self) -> &wgpu::Texture {
+        &self.texture
+    }
+
+    pub fn view(&self) -> &wgpu::TextureView {
+        &self.view
+    }
+}
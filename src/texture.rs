@@ -0,0 +1,286 @@
+//! Textures loaded from PNG or QOI images, with their mip chains generated on the GPU.
+//!
+//! A `Texture` binds like the low res texture in rotating_msaa_triangles.wgsl:
+//!
+//! ```wgsl
+//! @group(0) @binding(0) var our_texture: texture_2d<f32>;
+//! @group(0) @binding(1) var our_sampler: sampler;
+//! ```
+//!
+//! with `.texture_view(0, texture.view())` and `.sampler(1, texture.sampler())` on a
+//! `BindGroupBuilder`.
+
+use crate::image::{Image, ImageError};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TextureOptions {
+    /// Generate the full mip chain, so the texture stays smooth when drawn smaller.
+    pub mipmaps: bool,
+    /// For drawing both bigger and smaller. `Nearest` keeps pixel art sharp.
+    pub filter: wgpu::FilterMode,
+    pub address_mode: wgpu::AddressMode,
+    /// Added to the usages the texture needs anyway, e.g. `COPY_SRC` to read it back.
+    pub usage: wgpu::TextureUsages,
+}
+
+impl Default for TextureOptions {
+    fn default() -> Self {
+        Self {
+            mipmaps: true,
+            filter: wgpu::FilterMode::Linear,
+            address_mode: wgpu::AddressMode::ClampToEdge,
+            usage: wgpu::TextureUsages::empty(),
+        }
+    }
+}
+
+/// A sampled texture, with a view of all its mip levels and a sampler to go with it.
+pub struct Texture {
+    texture: wgpu::Texture,
+    view: wgpu::TextureView,
+    sampler: wgpu::Sampler,
+}
+
+impl Texture {
+    /// Images are sRGB, so sampling returns linear colors.
+    pub const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8UnormSrgb;
+
+    /// Loads a PNG or QOI, see `Image::load`.
+    pub fn load(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        path: impl AsRef<std::path::Path>,
+        options: &TextureOptions,
+    ) -> Result<Self, ImageError> {
+        let path = path.as_ref();
+        let image = Image::load(path)?;
+        Ok(Self::from_image(
+            device,
+            queue,
+            &path.to_string_lossy(),
+            &image,
+            options,
+        ))
+    }
+
+    pub fn from_image(
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        label: &str,
+        image: &Image,
+        options: &TextureOptions,
+    ) -> Self {
+        let size = wgpu::Extent3d {
+            width: image.width,
+            height: image.height,
+            depth_or_array_layers: 1,
+        };
+        let mip_level_count = if options.mipmaps {
+            size.max_mips(wgpu::TextureDimension::D2)
+        } else {
+            1
+        };
+        let texture = crate::logging::create_texture(
+            device,
+            &wgpu::TextureDescriptor {
+                label: Some(label),
+                size,
+                mip_level_count,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: Self::FORMAT,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING
+                    | wgpu::TextureUsages::COPY_DST
+                    | options.usage,
+                view_formats: &[Self::FORMAT],
+            },
+        );
+        write_image(queue, &texture, image);
+        if options.mipmaps {
+            generate_mipmaps(device, queue, &texture);
+        }
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let sampler = crate::logging::create_sampler(
+            device,
+            &wgpu::SamplerDescriptor {
+                label: Some(label),
+                address_mode_u: options.address_mode,
+                address_mode_v: options.address_mode,
+                address_mode_w: options.address_mode,
+                mag_filter: options.filter,
+                min_filter: options.filter,
+                mipmap_filter: options.filter,
+                ..Default::default()
+            },
+        );
+        Self {
+            texture,
+            view,
+            sampler,
+        }
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        (self.texture.width(), self.texture.height())
+    }
+
+    pub fn texture(&self) -> &wgpu::Texture {
+        &self.texture
+    }
+
+    pub fn view(&self) -> &wgpu::TextureView {
+        &self.view
+    }
+
+    pub fn sampler(&self) -> &wgpu::Sampler {
+        &self.sampler
+    }
+}
+
+/// Writes `image` to mip level 0 of `texture`, which must be the same size.
+pub fn write_image(queue: &wgpu::Queue, texture: &wgpu::Texture, image: &Image) {
+    // write_texture takes rows of any length, but copies them into a staging buffer whose rows
+    // are aligned. Rows that already are go in one copy instead of one per row.
+    let unpadded_bytes_per_row = image.width * 4;
+    let padded_bytes_per_row =
+        unpadded_bytes_per_row.next_multiple_of(wgpu::COPY_BYTES_PER_ROW_ALIGNMENT);
+    let pixels: std::borrow::Cow<[u8]> = if padded_bytes_per_row == unpadded_bytes_per_row {
+        image.pixels.as_slice().into()
+    } else {
+        let mut padded = vec![0; padded_bytes_per_row as usize * image.height as usize];
+        for (padded_row, row) in padded
+            .chunks_exact_mut(padded_bytes_per_row as usize)
+            .zip(image.pixels.chunks_exact(unpadded_bytes_per_row as usize))
+        {
+            padded_row[..row.len()].copy_from_slice(row);
+        }
+        padded.into()
+    };
+    queue.write_texture(
+        texture.as_image_copy(),
+        &pixels,
+        wgpu::ImageDataLayout {
+            offset: 0,
+            bytes_per_row: Some(padded_bytes_per_row),
+            rows_per_image: Some(image.height),
+        },
+        wgpu::Extent3d {
+            width: image.width,
+            height: image.height,
+            depth_or_array_layers: 1,
+        },
+    );
+}
+
+/// Draws each mip level of `texture` after the first by averaging 2x2 pixels of the one
+/// before it.
+///
+/// `texture` needs `TEXTURE_BINDING` and `COPY_DST` usage, and a format that can be rendered
+/// to. Builds its pipeline on every call, so it suits textures loaded up front.
+pub fn generate_mipmaps(device: &wgpu::Device, queue: &wgpu::Queue, texture: &wgpu::Texture) {
+    if texture.mip_level_count() == 1 {
+        return;
+    }
+    let format = texture.format();
+    let shader_module = crate::shader_file!("mipmap.wgsl")
+        .compile(device)
+        .unwrap_or_else(|e| panic!("mipmap.wgsl: {}", e));
+    let render_pipeline = crate::logging::create_render_pipeline(
+        device,
+        &wgpu::RenderPipelineDescriptor {
+            label: Some("mipmap render pipeline"),
+            layout: None,
+            vertex: wgpu::VertexState {
+                module: &shader_module,
+                entry_point: "vertex_main",
+                buffers: &[],
+            },
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            fragment: Some(wgpu::FragmentState {
+                module: &shader_module,
+                entry_point: "fragment_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            multiview: None,
+        },
+    );
+    // wgpu's GL backend ignores which levels a view has when sampling, so drawing into one
+    // level of `texture` while reading another would be a feedback loop. Each level is drawn
+    // here instead, in the top left corner, and copied over.
+    let scratch_size = texture.size().mip_level_size(1, wgpu::TextureDimension::D2);
+    let scratch = crate::logging::create_texture(
+        device,
+        &wgpu::TextureDescriptor {
+            label: Some("mipmap scratch texture"),
+            size: scratch_size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[format],
+        },
+    );
+    let scratch_view = scratch.create_view(&wgpu::TextureViewDescriptor::default());
+    let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+    let mut command_encoder =
+        device.create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+    for mip_level in 1..texture.mip_level_count() {
+        let source_level_buffer = crate::logging::create_buffer_init(
+            device,
+            "mipmap source level buffer",
+            wgpu::BufferUsages::UNIFORM,
+            bytemuck::bytes_of(&(mip_level - 1)),
+        );
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("mipmap bind group"),
+            layout: &render_pipeline.get_bind_group_layout(0),
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: source_level_buffer.as_entire_binding(),
+                },
+            ],
+        });
+        let size = texture
+            .size()
+            .mip_level_size(mip_level, wgpu::TextureDimension::D2);
+        {
+            let mut render_pass = command_encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("mipmap render pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &scratch_view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                        store: true,
+                    },
+                })],
+                depth_stencil_attachment: None,
+            });
+            render_pass.set_pipeline(&render_pipeline);
+            render_pass.set_bind_group(0, &bind_group, &[]);
+            render_pass.set_viewport(0.0, 0.0, size.width as f32, size.height as f32, 0.0, 1.0);
+            render_pass.draw(0..3, 0..1);
+        }
+        command_encoder.copy_texture_to_texture(
+            scratch.as_image_copy(),
+            wgpu::ImageCopyTexture {
+                mip_level,
+                ..texture.as_image_copy()
+            },
+            size,
+        );
+    }
+    queue.submit([command_encoder.finish()]);
+}
@@ -0,0 +1,97 @@
+//! A fixed-timestep accumulator, so simulation speed doesn't depend on frame rate.
+//!
+//! See: https://gafferongames.com/post/fix_your_timestep/
+
+use crate::game::Game;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimestepConfig {
+    pub ticks_per_second: u32,
+    /// The most updates we'll run for a single frame before dropping time.
+    pub max_steps_per_frame: u32,
+    /// Frame times longer than this are clamped, e.g. after the window was dragged or a debugger paused us.
+    pub max_frame_time: std::time::Duration,
+}
+
+impl Default for TimestepConfig {
+    fn default() -> Self {
+        Self {
+            ticks_per_second: 60,
+            max_steps_per_frame: 5,
+            max_frame_time: std::time::Duration::from_millis(250),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FixedTimestep {
+    config: TimestepConfig,
+    tick: std::time::Duration,
+    accumulator: std::time::Duration,
+    total_ticks: u64,
+}
+
+impl FixedTimestep {
+    pub fn new(config: TimestepConfig) -> Self {
+        assert!(config.ticks_per_second > 0);
+        assert!(config.max_steps_per_frame > 0);
+        Self {
+            config,
+            tick: std::time::Duration::from_secs(1) / config.ticks_per_second,
+            accumulator: std::time::Duration::ZERO,
+            total_ticks: 0,
+        }
+    }
+
+    /// The constant dt passed to every update.
+    pub fn dt(&self) -> std::time::Duration {
+        self.tick
+    }
+
+    /// How many ticks have been run since creation.
+    pub fn total_ticks(&self) -> u64 {
+        self.total_ticks
+    }
+
+    /// How far we are between the last tick and the next, in 0.0..1.0.
+    ///
+    /// Render with `previous_state.lerp(current_state, alpha)`.
+    pub fn alpha(&self) -> f32 {
+        self.accumulator.as_secs_f32() / self.tick.as_secs_f32()
+    }
+
+    /// Adds `frame_time` to the accumulator and returns how many ticks should be run.
+    ///
+    /// If we fall more than `max_steps_per_frame` ticks behind, the excess time is dropped.
+    /// Otherwise each frame would take longer to simulate than the last; the spiral of death.
+    pub fn advance(&mut self, frame_time: std::time::Duration) -> u32 {
+        self.accumulator += frame_time.min(self.config.max_frame_time);
+        let mut steps = 0;
+        while self.accumulator >= self.tick && steps < self.config.max_steps_per_frame {
+            self.accumulator -= self.tick;
+            steps += 1;
+        }
+        if self.accumulator >= self.tick {
+            // Keep the fractional part so alpha still makes sense.
+            self.accumulator = std::time::Duration::from_nanos(
+                (self.accumulator.as_nanos() % self.tick.as_nanos()) as u64,
+            );
+        }
+        self.total_ticks += steps as u64;
+        steps
+    }
+
+    /// Runs one frame of `game`: zero or more fixed updates, then a single render.
+    pub fn frame<G: Game + ?Sized>(&mut self, game: &mut G, frame_time: std::time::Duration) {
+        for _ in 0..self.advance(frame_time) {
+            game.update(self.tick);
+        }
+        game.render(self.alpha());
+    }
+}
+
+impl Default for FixedTimestep {
+    fn default() -> Self {
+        Self::new(TimestepConfig::default())
+    }
+}
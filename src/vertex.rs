@@ -0,0 +1,92 @@
+//! Vertex buffer layouts derived from `#[repr(C)]` structs, so offsets aren't counted by hand.
+//!
+//! `#[derive(VertexLayout)]` makes each field an attribute, at its `offset_of!` offset.
+//! Its format comes from `AttributeFormat`, unless the field has `#[vertex(format = Unorm8x4)]`,
+//! and must be the field's size. Locations count up from 0, or from the last field with
+//! `#[vertex(location = 2)]`. Put `#[vertex(step_mode = Instance)]` on per instance structs.
+
+pub use wgpu_experiments_derive::VertexLayout;
+
+/// The corners of a square from (0, 0) to (1, 1), for quads drawn as instances of it.
+pub const QUAD_CORNERS: &[glam::Vec2] = &[
+    glam::Vec2::new(0.0, 0.0),
+    glam::Vec2::new(0.0, 1.0),
+    glam::Vec2::new(1.0, 1.0),
+    glam::Vec2::new(1.0, 0.0),
+];
+/// `QUAD_CORNERS` as two triangles.
+pub const QUAD_INDICES: &[u32] = &[0, 1, 3, 2, 3, 1];
+
+/// A struct that's one vertex, or one instance, in a vertex buffer. Derive it.
+pub trait VertexLayout {
+    const ATTRIBUTES: &'static [wgpu::VertexAttribute];
+    /// Use as one of `wgpu::VertexState::buffers`.
+    const LAYOUT: wgpu::VertexBufferLayout<'static>;
+}
+
+/// The vertex format a field of this type has by default.
+pub trait AttributeFormat {
+    const FORMAT: wgpu::VertexFormat;
+}
+
+/// An attribute for a field of `size` bytes, for the derive macro.
+///
+/// Panics, at compile time when used in a constant, if `format` isn't `size` bytes.
+pub const fn attribute(
+    format: wgpu::VertexFormat,
+    offset: usize,
+    shader_location: u32,
+    size: usize,
+) -> wgpu::VertexAttribute {
+    assert!(
+        format.size() == size as u64,
+        "the vertex format must be the same size as the field"
+    );
+    wgpu::VertexAttribute {
+        format,
+        offset: offset as u64,
+        shader_location,
+    }
+}
+
+macro_rules! impl_attribute_format {
+    ($($ty:ty => $format:ident;)*) => {
+        $(
+            impl AttributeFormat for $ty {
+                const FORMAT: wgpu::VertexFormat = wgpu::VertexFormat::$format;
+            }
+        )*
+    };
+}
+
+impl_attribute_format! {
+    f32 => Float32;
+    [f32; 2] => Float32x2;
+    [f32; 3] => Float32x3;
+    [f32; 4] => Float32x4;
+    u32 => Uint32;
+    [u32; 2] => Uint32x2;
+    [u32; 3] => Uint32x3;
+    [u32; 4] => Uint32x4;
+    i32 => Sint32;
+    [i32; 2] => Sint32x2;
+    [i32; 3] => Sint32x3;
+    [i32; 4] => Sint32x4;
+    [u8; 2] => Uint8x2;
+    [u8; 4] => Uint8x4;
+    [i8; 2] => Sint8x2;
+    [i8; 4] => Sint8x4;
+    [u16; 2] => Uint16x2;
+    [u16; 4] => Uint16x4;
+    [i16; 2] => Sint16x2;
+    [i16; 4] => Sint16x4;
+    glam::Vec2 => Float32x2;
+    glam::Vec3 => Float32x3;
+    glam::Vec4 => Float32x4;
+    glam::UVec2 => Uint32x2;
+    glam::UVec3 => Uint32x3;
+    glam::UVec4 => Uint32x4;
+    glam::IVec2 => Sint32x2;
+    glam::IVec3 => Sint32x3;
+    glam::IVec4 => Sint32x4;
+}
@@ -0,0 +1,260 @@
+//! Rust values laid out the way WGSL lays them out, for uniform and storage buffers.
+//!
+//! `#[derive(WgslLayout)]` places each field at the offset WGSL gives the matching struct
+//! member, padding with zeroes, so structs don't need hand written `_padding` fields.
+//! A `glam::Vec3` followed by a `glam::Vec2` puts the `Vec2` at offset 16, as WGSL does.
+//!
+//! WGSL lays structs out the same way in the uniform and storage address spaces. Uniform
+//! buffers add requirements, e.g. that a struct member is 16 byte aligned, which the shader
+//! meets with `@align(16)` and naga checks when it compiles. Mirror those on the Rust side
+//! with `#[wgsl(align = 16)]` or `#[wgsl(size = 32)]` on the field.
+//!
+//! Shaders don't declare these structs themselves. `Prelude` generates the WGSL declarations,
+//! and `ShaderFile::with_prelude` puts them before the shader's source, so adding a field to
+//! the Rust struct adds it to the shader too.
+//!
+//! tests/shaders.rs compares derived layouts with naga's layout of the matching WGSL struct.
+
+pub use wgpu_experiments_derive::WgslLayout;
+
+/// A type with a WGSL memory layout. Derive it for structs.
+pub trait WgslLayout {
+    /// AlignOf in the WGSL spec.
+    const ALIGN: u64;
+    /// SizeOf in the WGSL spec.
+    const SIZE: u64;
+    /// For structs, the members in order. Empty for other types.
+    const MEMBERS: &'static [Member] = &[];
+
+    /// The type's name in WGSL, e.g. `vec3f`, `array<f32, 3>` or a struct's name.
+    fn wgsl_type() -> String;
+
+    /// Appends the WGSL declarations of the structs this type is or contains, in an order
+    /// WGSL accepts, skipping any already there.
+    fn wgsl_structs(_declarations: &mut Vec<String>) {}
+
+    /// Appends exactly `SIZE` bytes, with zeroes for padding.
+    fn write_to(&self, bytes: &mut Vec<u8>);
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(Self::SIZE as usize);
+        self.write_to(&mut bytes);
+        bytes
+    }
+}
+
+/// A member of a struct, laid out. See `WgslLayout::MEMBERS`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Member {
+    pub name: &'static str,
+    pub offset: u64,
+    pub align: u64,
+    pub size: u64,
+}
+
+/// A member of a struct before it's laid out, for the derive macro.
+#[derive(Debug, Clone, Copy)]
+pub struct MemberLayout {
+    pub name: &'static str,
+    pub type_align: u64,
+    pub type_size: u64,
+    /// `type_align`, unless overridden like `@align`.
+    pub align: u64,
+    /// `type_size`, unless overridden like `@size`.
+    pub size: u64,
+}
+
+pub const fn round_up(align: u64, n: u64) -> u64 {
+    n.div_ceil(align) * align
+}
+
+/// Places each member after the previous one, at the next multiple of its alignment.
+///
+/// Panics, at compile time when used in a constant, if an `align` or `size` override is one
+/// WGSL would reject.
+pub const fn struct_members<const N: usize>(members: [MemberLayout; N]) -> [Member; N] {
+    let mut laid_out = [Member {
+        name: "",
+        offset: 0,
+        align: 1,
+        size: 0,
+    }; N];
+    let mut end = 0;
+    let mut i = 0;
+    while i < N {
+        let member = members[i];
+        assert!(
+            member.align.is_power_of_two() && member.align.is_multiple_of(member.type_align),
+            "align must be a power of two, and a multiple of the type's alignment"
+        );
+        assert!(
+            member.size >= member.type_size,
+            "size must be at least the type's size"
+        );
+        let offset = round_up(member.align, end);
+        laid_out[i] = Member {
+            name: member.name,
+            offset,
+            align: member.align,
+            size: member.size,
+        };
+        end = offset + member.size;
+        i += 1;
+    }
+    laid_out
+}
+
+/// The largest alignment of any member.
+pub const fn struct_align(members: &[Member]) -> u64 {
+    let mut align = 1;
+    let mut i = 0;
+    while i < members.len() {
+        if members[i].align > align {
+            align = members[i].align;
+        }
+        i += 1;
+    }
+    align
+}
+
+/// The end of the last member, rounded up to the struct's alignment.
+pub const fn struct_size(members: &[Member]) -> u64 {
+    let last = members[members.len() - 1];
+    round_up(struct_align(members), last.offset + last.size)
+}
+
+/// The WGSL declaration of a struct, for the derive macro.
+///
+/// Each member is its name, with any `@align` or `@size` attributes, and its type.
+pub fn struct_declaration(name: &str, members: &[(&str, String)]) -> String {
+    let mut declaration = format!("struct {} {{\n", name);
+    for (member, ty) in members {
+        declaration += &format!("    {}: {},\n", member, ty);
+    }
+    declaration + "};\n"
+}
+
+/// Appends `declaration` unless it's already there.
+pub fn push_declaration(declarations: &mut Vec<String>, declaration: String) {
+    if !declarations.contains(&declaration) {
+        declarations.push(declaration);
+    }
+}
+
+/// WGSL struct declarations generated from Rust types, to put before a shader's source.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Prelude {
+    declarations: Vec<String>,
+}
+
+impl Prelude {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Declares `T`, and the structs it contains.
+    pub fn with<T: WgslLayout>(mut self) -> Self {
+        T::wgsl_structs(&mut self.declarations);
+        self
+    }
+
+    pub fn to_wgsl(&self) -> String {
+        let mut wgsl = String::from("// Generated from Rust types by wgpu_experiments::wgsl.\n");
+        for declaration in &self.declarations {
+            wgsl += "\n";
+            wgsl += declaration;
+        }
+        wgsl + "\n"
+    }
+}
+
+/// Appends zeroes until `bytes` is `len` long.
+pub fn pad(bytes: &mut Vec<u8>, len: usize) {
+    debug_assert!(bytes.len() <= len);
+    bytes.resize(len, 0);
+}
+
+/// The distance between elements of an `array<T>`.
+pub const fn array_stride<T: WgslLayout>() -> u64 {
+    round_up(T::ALIGN, T::SIZE)
+}
+
+/// The bytes of an `array<T>`, e.g. a runtime sized array in a storage buffer.
+pub fn slice_to_bytes<T: WgslLayout>(values: &[T]) -> Vec<u8> {
+    let stride = array_stride::<T>() as usize;
+    let mut bytes = Vec::with_capacity(stride * values.len());
+    for value in values {
+        let start = bytes.len();
+        value.write_to(&mut bytes);
+        pad(&mut bytes, start + stride);
+    }
+    bytes
+}
+
+impl<T: WgslLayout, const N: usize> WgslLayout for [T; N] {
+    const ALIGN: u64 = T::ALIGN;
+    const SIZE: u64 = array_stride::<T>() * N as u64;
+
+    fn wgsl_type() -> String {
+        format!("array<{}, {}>", T::wgsl_type(), N)
+    }
+
+    fn wgsl_structs(declarations: &mut Vec<String>) {
+        T::wgsl_structs(declarations);
+    }
+
+    fn write_to(&self, bytes: &mut Vec<u8>) {
+        bytes.extend_from_slice(&slice_to_bytes(self));
+    }
+}
+
+/// Types whose bytes are already laid out as in WGSL.
+macro_rules! impl_wgsl_layout_for_pod {
+    ($($ty:ty => $wgsl:literal, $align:literal, $size:literal;)*) => {
+        $(
+            impl WgslLayout for $ty {
+                const ALIGN: u64 = $align;
+                const SIZE: u64 = $size;
+
+                fn wgsl_type() -> String {
+                    $wgsl.to_string()
+                }
+
+                fn write_to(&self, bytes: &mut Vec<u8>) {
+                    bytes.extend_from_slice(bytemuck::bytes_of(self));
+                }
+            }
+        )*
+    };
+}
+
+impl_wgsl_layout_for_pod! {
+    f32 => "f32", 4, 4;
+    i32 => "i32", 4, 4;
+    u32 => "u32", 4, 4;
+    glam::Vec2 => "vec2f", 8, 8;
+    glam::Vec3 => "vec3f", 16, 12;
+    glam::Vec4 => "vec4f", 16, 16;
+    glam::IVec2 => "vec2i", 8, 8;
+    glam::IVec3 => "vec3i", 16, 12;
+    glam::IVec4 => "vec4i", 16, 16;
+    glam::UVec2 => "vec2u", 8, 8;
+    glam::UVec3 => "vec3u", 16, 12;
+    glam::UVec4 => "vec4u", 16, 16;
+    glam::Mat2 => "mat2x2f", 8, 16;
+    glam::Mat4 => "mat4x4f", 16, 64;
+}
+
+/// Each column is a `vec3f`, so it's padded to 16 bytes.
+impl WgslLayout for glam::Mat3 {
+    const ALIGN: u64 = 16;
+    const SIZE: u64 = 48;
+
+    fn wgsl_type() -> String {
+        "mat3x3f".to_string()
+    }
+
+    fn write_to(&self, bytes: &mut Vec<u8>) {
+        [self.x_axis, self.y_axis, self.z_axis].write_to(bytes);
+    }
+}
@@ -0,0 +1,141 @@
+use wgpu_experiments::actions::{ActionMap, Binding, Bindings};
+use wgpu_experiments::input::{Button, InputState};
+use winit::event::{ElementState, ModifiersState, MouseButton, VirtualKeyCode};
+
+mod common;
+use common::{key, modifiers, mouse};
+
+const BINDINGS: &str = r#"
+(
+    actions: {
+        "fire": ["Space", "MouseLeft"],
+        "save": ["Ctrl+S"],
+        "quit": ["Escape", "Ctrl+Shift+Q"],
+    },
+    axes: {
+        "move_y": [(negative: "S", positive: "W"), (negative: "Down", positive: "Up")],
+    },
+)
+"#;
+
+fn action_map() -> ActionMap {
+    ActionMap::new(BINDINGS.parse().unwrap())
+}
+
+#[test]
+fn parse_bindings() {
+    assert_eq!(
+        "Ctrl+Shift+Q".parse::<Binding>().unwrap(),
+        Binding {
+            button: Button::Key(VirtualKeyCode::Q),
+            modifiers: ModifiersState::CTRL | ModifiersState::SHIFT,
+        }
+    );
+    assert_eq!(
+        "Mouse4".parse::<Binding>().unwrap().button,
+        Button::Mouse(MouseButton::Other(4))
+    );
+    assert!("Hyper+A".parse::<Binding>().is_err());
+    assert!("NotAKey".parse::<Binding>().is_err());
+    for name in ["Ctrl+Shift+Q", "MouseLeft", "Mouse4", "F5", "Alt+Key1"] {
+        assert_eq!(name.parse::<Binding>().unwrap().to_string(), name);
+    }
+}
+
+#[test]
+fn bad_files_report_the_problem() {
+    let error = r#"(actions: {"fire": ["Spacebar"]})"#.parse::<Bindings>().unwrap_err();
+    assert!(error.to_string().contains("Spacebar"), "{}", error);
+}
+
+#[test]
+fn multiple_bindings_per_action() {
+    let actions = action_map();
+    let mut input = InputState::new();
+    assert!(!actions.is_active(&input, "fire"));
+    input.handle_window_event(&mouse(MouseButton::Left, ElementState::Pressed));
+    assert!(actions.is_active(&input, "fire"));
+    assert!(actions.just_activated(&input, "fire"));
+    input.end_frame();
+    input.handle_window_event(&key(VirtualKeyCode::Space, ElementState::Pressed));
+    assert!(actions.is_active(&input, "fire"));
+    assert!(actions.just_activated(&input, "fire"));
+    assert!(!actions.is_active(&input, "no such action"));
+}
+
+#[test]
+fn chords_need_their_modifiers() {
+    let actions = action_map();
+    let mut input = InputState::new();
+    input.handle_window_event(&key(VirtualKeyCode::S, ElementState::Pressed));
+    assert!(!actions.is_active(&input, "save"));
+    assert_eq!(actions.axis(&input, "move_y"), -1.0);
+
+    input.handle_window_event(&modifiers(ModifiersState::CTRL));
+    assert!(actions.is_active(&input, "save"));
+    // The more specific chord wins, so Ctrl+S doesn't also move.
+    assert_eq!(actions.axis(&input, "move_y"), 0.0);
+
+    input.handle_window_event(&key(VirtualKeyCode::Q, ElementState::Pressed));
+    assert!(!actions.is_active(&input, "quit"));
+    input.handle_window_event(&modifiers(ModifiersState::CTRL | ModifiersState::SHIFT));
+    assert!(actions.is_active(&input, "quit"));
+}
+
+#[test]
+fn axes() {
+    let actions = action_map();
+    let mut input = InputState::new();
+    assert_eq!(actions.axis(&input, "move_y"), 0.0);
+    input.handle_window_event(&key(VirtualKeyCode::Up, ElementState::Pressed));
+    assert_eq!(actions.axis(&input, "move_y"), 1.0);
+    input.handle_window_event(&key(VirtualKeyCode::S, ElementState::Pressed));
+    assert_eq!(actions.axis(&input, "move_y"), 0.0);
+    input.handle_window_event(&key(VirtualKeyCode::Up, ElementState::Released));
+    assert_eq!(actions.axis(&input, "move_y"), -1.0);
+    assert_eq!(actions.axis(&input, "no such axis"), 0.0);
+}
+
+#[test]
+fn conflicts() {
+    assert!(action_map().conflicts().is_empty());
+    let actions = ActionMap::new(
+        r#"(
+            actions: {"fire": ["Space", "Ctrl+S"], "jump": ["Space"], "save": ["Ctrl+S"]},
+            axes: {"move_x": [(negative: "A", positive: "Space")]},
+        )"#
+        .parse()
+        .unwrap(),
+    );
+    let conflicts: Vec<String> = actions.conflicts().iter().map(|c| c.to_string()).collect();
+    assert_eq!(
+        conflicts,
+        [
+            "Ctrl+S is bound to fire, save",
+            "Space is bound to fire, jump, move_x+",
+        ]
+    );
+}
+
+#[test]
+fn reload_from_disk() {
+    let path = std::env::temp_dir().join(format!(
+        "wgpu_experiments_bindings_{}.ron",
+        std::process::id()
+    ));
+    std::fs::write(&path, r#"(actions: {"fire": ["Space"]})"#).unwrap();
+    let mut actions = ActionMap::load(&path).unwrap();
+    let mut input = InputState::new();
+    input.handle_window_event(&key(VirtualKeyCode::Return, ElementState::Pressed));
+    assert!(!actions.is_active(&input, "fire"));
+
+    std::fs::write(&path, r#"(actions: {"fire": ["Return"]})"#).unwrap();
+    actions.reload().unwrap();
+    assert!(actions.is_active(&input, "fire"));
+
+    // A broken file keeps the previous bindings.
+    std::fs::write(&path, r#"(actions: {"fire": ["#).unwrap();
+    assert!(actions.reload().is_err());
+    assert!(actions.is_active(&input, "fire"));
+    std::fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,218 @@
+use wgpu_experiments::audio::{Audio, AudioError, Mixer, NullOutput, PlayParams, Sound};
+
+fn constant(channels: u16, frames: usize, value: f32) -> Sound {
+    Sound::from_samples(channels, 100, vec![value; frames * channels as usize]).unwrap()
+}
+
+fn ramp(frames: usize) -> Sound {
+    Sound::from_samples(1, 100, (0..frames).map(|i| i as f32).collect()).unwrap()
+}
+
+fn render(mixer: &mut Mixer, frames: usize) -> Vec<f32> {
+    let mut out = vec![0.0; frames * 2];
+    mixer.mix(&mut out);
+    out
+}
+
+#[test]
+fn decode_wav() {
+    let mut bytes = std::io::Cursor::new(Vec::new());
+    {
+        let mut writer = hound::WavWriter::new(
+            &mut bytes,
+            hound::WavSpec {
+                channels: 2,
+                sample_rate: 22_050,
+                bits_per_sample: 16,
+                sample_format: hound::SampleFormat::Int,
+            },
+        )
+        .unwrap();
+        for sample in [0_i16, i16::MIN, 16384, -16384] {
+            writer.write_sample(sample).unwrap();
+        }
+        writer.finalize().unwrap();
+    }
+    let sound = Sound::from_wav_bytes(bytes.get_ref()).unwrap();
+    assert_eq!(sound.channels(), 2);
+    assert_eq!(sound.sample_rate(), 22_050);
+    assert_eq!(sound.frames(), 2);
+
+    let mut mixer = Mixer::new(22_050);
+    mixer.play(&sound);
+    assert_eq!(render(&mut mixer, 3), [0.0, -1.0, 0.5, -0.5, 0.0, 0.0]);
+}
+
+#[test]
+fn bad_wav_is_an_error() {
+    assert!(Sound::from_wav_bytes(b"RIFF nonsense").is_err());
+}
+
+/// A 16 bit PCM WAV written by hand, as hound won't write broken ones. `data_len` is what the
+/// header claims, which needn't be `data.len()`.
+fn pcm16_wav(channels: u16, sample_rate: u32, data_len: u32, data: &[u8]) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(b"RIFF");
+    bytes.extend_from_slice(&(36 + data_len).to_le_bytes());
+    bytes.extend_from_slice(b"WAVEfmt ");
+    bytes.extend_from_slice(&16_u32.to_le_bytes());
+    bytes.extend_from_slice(&1_u16.to_le_bytes()); // PCM
+    bytes.extend_from_slice(&channels.to_le_bytes());
+    bytes.extend_from_slice(&sample_rate.to_le_bytes());
+    bytes.extend_from_slice(&(sample_rate * channels as u32 * 2).to_le_bytes()); // bytes per second
+    bytes.extend_from_slice(&(channels * 2).to_le_bytes()); // bytes per frame
+    bytes.extend_from_slice(&16_u16.to_le_bytes()); // bits per sample
+    bytes.extend_from_slice(b"data");
+    bytes.extend_from_slice(&data_len.to_le_bytes());
+    bytes.extend_from_slice(data);
+    bytes
+}
+
+#[test]
+fn wav_ending_mid_frame_is_an_error() {
+    // A stereo WAV cut off after 3 samples, so the second frame has no right sample.
+    let bytes = pcm16_wav(2, 100, 8, &[0; 6]);
+    assert!(Sound::from_wav_bytes(&bytes).is_err());
+}
+
+#[test]
+fn zero_sample_rate_is_an_error() {
+    assert!(matches!(
+        Sound::from_samples(1, 0, vec![0.0; 4]),
+        Err(AudioError::ZeroSampleRate)
+    ));
+    assert!(matches!(
+        Sound::from_wav_bytes(&pcm16_wav(1, 0, 2, &[0; 2])),
+        Err(AudioError::ZeroSampleRate)
+    ));
+}
+
+#[test]
+fn voices_are_summed_and_finish() {
+    let mut mixer = Mixer::new(100);
+    let a = mixer.play(&constant(1, 2, 0.25));
+    let b = mixer.play(&constant(2, 4, 0.5));
+    assert_eq!(mixer.voice_count(), 2);
+    assert_eq!(render(&mut mixer, 3), [0.75, 0.75, 0.75, 0.75, 0.5, 0.5]);
+    assert!(!mixer.is_playing(a));
+    assert!(mixer.is_playing(b));
+    render(&mut mixer, 10);
+    assert_eq!(mixer.voice_count(), 0);
+    assert_eq!(render(&mut mixer, 2), [0.0; 4]);
+}
+
+#[test]
+fn volume_and_pan() {
+    let mut mixer = Mixer::new(100);
+    let sound = constant(1, 10, 1.0);
+    let handle = mixer.play_with(
+        &sound,
+        PlayParams {
+            volume: 0.5,
+            ..PlayParams::default()
+        },
+    );
+    assert_eq!(render(&mut mixer, 1), [0.5, 0.5]);
+    mixer.set_params(
+        handle,
+        PlayParams {
+            pan: -1.0,
+            ..PlayParams::default()
+        },
+    );
+    assert_eq!(render(&mut mixer, 1), [1.0, 0.0]);
+    mixer.set_params(
+        handle,
+        PlayParams {
+            pan: 0.5,
+            ..PlayParams::default()
+        },
+    );
+    assert_eq!(render(&mut mixer, 1), [0.5, 1.0]);
+}
+
+#[test]
+fn pitch_resamples() {
+    let mut mixer = Mixer::new(100);
+    mixer.play_with(
+        &ramp(8),
+        PlayParams {
+            pitch: 2.0,
+            ..PlayParams::default()
+        },
+    );
+    let out = render(&mut mixer, 5);
+    let left: Vec<f32> = out.iter().step_by(2).copied().collect();
+    assert_eq!(left, [0.0, 2.0, 4.0, 6.0, 0.0]);
+
+    // Half speed interpolates between frames.
+    mixer.play_with(
+        &ramp(8),
+        PlayParams {
+            pitch: 0.5,
+            ..PlayParams::default()
+        },
+    );
+    let out = render(&mut mixer, 4);
+    let left: Vec<f32> = out.iter().step_by(2).copied().collect();
+    assert_eq!(left, [0.0, 0.5, 1.0, 1.5]);
+}
+
+#[test]
+fn pitch_below_the_minimum_still_finishes() {
+    let mut mixer = Mixer::new(100);
+    let handles = [0.0, -1.0, f32::NAN].map(|pitch| {
+        mixer.play_with(
+            &ramp(2),
+            PlayParams {
+                pitch,
+                ..PlayParams::default()
+            },
+        )
+    });
+    // 2 frames, MIN_PITCH of a frame at a time.
+    let frames = (2.0 / PlayParams::MIN_PITCH) as usize;
+    render(&mut mixer, frames - 1);
+    assert!(handles.iter().all(|&handle| mixer.is_playing(handle)));
+    render(&mut mixer, 10);
+    assert_eq!(mixer.voice_count(), 0);
+}
+
+#[test]
+fn sample_rate_conversion() {
+    // A 100 Hz sound played by a 200 Hz mixer lasts twice as many output frames.
+    let mut mixer = Mixer::new(200);
+    let handle = mixer.play(&constant(1, 10, 1.0));
+    render(&mut mixer, 19);
+    assert!(mixer.is_playing(handle));
+    render(&mut mixer, 2);
+    assert!(!mixer.is_playing(handle));
+}
+
+#[test]
+fn looping_and_stop() {
+    let mut mixer = Mixer::new(100);
+    let handle = mixer.play_with(
+        &ramp(3),
+        PlayParams {
+            looping: true,
+            ..PlayParams::default()
+        },
+    );
+    let out = render(&mut mixer, 7);
+    let left: Vec<f32> = out.iter().step_by(2).copied().collect();
+    assert_eq!(left, [0.0, 1.0, 2.0, 0.0, 1.0, 2.0, 0.0]);
+    assert!(mixer.is_playing(handle));
+    assert!(mixer.stop(handle));
+    assert!(!mixer.stop(handle));
+    assert_eq!(render(&mut mixer, 1), [0.0, 0.0]);
+}
+
+#[test]
+fn audio_with_null_output() {
+    let audio = Audio::new(Box::new(NullOutput::default()));
+    let handle = audio.play(&constant(1, 10, 1.0));
+    assert!(audio.is_playing(handle));
+    assert!(audio.stop(handle));
+    assert!(!audio.is_playing(handle));
+}
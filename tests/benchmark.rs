@@ -0,0 +1,173 @@
+mod common;
+
+use std::time::Duration;
+use wgpu_experiments::benchmark::{
+    median_times, BenchmarkOptions, FrameTimes, Measurement, Report,
+};
+use wgpu_experiments::game::ArgsError;
+
+#[test]
+fn options() {
+    assert_eq!(
+        BenchmarkOptions::from_args(common::args(&["benchmark", "--seed", "3"])),
+        Ok(BenchmarkOptions::default())
+    );
+    assert_eq!(
+        BenchmarkOptions::from_args(common::args(&[
+            "benchmark",
+            "--counts",
+            "10, 200",
+            "--frames",
+            "3",
+            "--output",
+            "out",
+        ])),
+        Ok(BenchmarkOptions {
+            counts: vec![10, 200],
+            frames: 3,
+            output: "out".into(),
+        })
+    );
+    for (flag, value) in [("--counts", "10,x"), ("--counts", "0"), ("--frames", "0")] {
+        assert_eq!(
+            BenchmarkOptions::from_args(common::args(&["benchmark", flag, value])),
+            Err(ArgsError::InvalidValue {
+                flag,
+                value: value.to_string()
+            })
+        );
+    }
+    assert_eq!(
+        BenchmarkOptions::from_args(common::args(&["benchmark", "--frames"])),
+        Err(ArgsError::MissingValue("--frames"))
+    );
+}
+
+fn times(encode: u64, submit: u64, gpu: Option<u64>) -> FrameTimes {
+    FrameTimes {
+        encode: Duration::from_micros(encode),
+        submit: Duration::from_micros(submit),
+        gpu: gpu.map(Duration::from_micros),
+    }
+}
+
+#[test]
+fn medians_are_taken_separately() {
+    assert_eq!(median_times(&[]), None);
+    assert_eq!(
+        median_times(&[
+            times(5, 1, Some(7)),
+            times(1, 900, Some(8)),
+            times(3, 2, Some(9)),
+        ]),
+        Some(times(3, 2, Some(8)))
+    );
+    // A GPU time from some frames but not others isn't comparable, so there's none.
+    assert_eq!(
+        median_times(&[times(1, 1, Some(1)), times(1, 1, None)]),
+        Some(times(1, 1, None))
+    );
+}
+
+fn report() -> Report {
+    Report {
+        adapter: "Test adapter".to_string(),
+        frames: 3,
+        measurements: vec![
+            Measurement {
+                strategy: "a",
+                objects: 100,
+                times: Some(times(12, 3, Some(40))),
+                note: String::new(),
+            },
+            Measurement {
+                strategy: "b",
+                objects: 100,
+                times: Some(times(5, 2, None)),
+                note: String::new(),
+            },
+            Measurement {
+                strategy: "a",
+                objects: 1000,
+                times: None,
+                note: "skipped, too many".to_string(),
+            },
+        ],
+    }
+}
+
+#[test]
+fn csv_report() {
+    assert_eq!(
+        report().to_csv(),
+        "strategy,objects,encode_us,submit_us,gpu_us,note\n\
+        a,100,12.0,3.0,40.0,\n\
+        b,100,5.0,2.0,,\n\
+        a,1000,,,,skipped; too many\n"
+    );
+}
+
+#[test]
+fn markdown_report() {
+    assert_eq!(
+        report().to_markdown(),
+        "# Benchmark\n\
+        \n\
+        Test adapter, median of 3 frames, times in microseconds.\n\
+        \n\
+        ## 100 objects\n\
+        \n\
+        | Strategy | Encode | Submit | GPU | Note |\n\
+        | --- | ---: | ---: | ---: | --- |\n\
+        | a | 12.0 | 3.0 | 40.0 |  |\n\
+        | b | 5.0 | 2.0 | - |  |\n\
+        \n\
+        ## 1000 objects\n\
+        \n\
+        | Strategy | Encode | Submit | GPU | Note |\n\
+        | --- | ---: | ---: | ---: | --- |\n\
+        | a | - | - | - | skipped, too many |\n"
+    );
+}
+
+#[test]
+fn markdown_report_has_one_table_per_count() {
+    let mut report = report();
+    // Out of order, as the measurements of a count needn't be next to each other.
+    report.measurements.rotate_left(1);
+    let markdown = report.to_markdown();
+    assert_eq!(markdown.matches("## 100 objects").count(), 1);
+    assert!(markdown.find("## 100 objects") < markdown.find("## 1000 objects"));
+}
+
+#[test]
+fn benchmark_writes_reports() {
+    if !common::has_adapter() {
+        eprintln!("Skipping, no adapter");
+        return;
+    }
+    let output = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("benchmark");
+    let result = std::process::Command::new(env!("CARGO_BIN_EXE_benchmark"))
+        .args([
+            "--counts", "10,20", "--frames", "2", "--seed", "1", "--output",
+        ])
+        .arg(&output)
+        .output()
+        .unwrap();
+    assert!(
+        result.status.success(),
+        "{}",
+        String::from_utf8_lossy(&result.stderr)
+    );
+    let csv = std::fs::read_to_string(output.join("report.csv")).unwrap();
+    // A header, then every strategy at both counts, all timed.
+    let rows: Vec<&str> = csv.lines().skip(1).collect();
+    assert_eq!(rows.len(), 8);
+    for row in rows {
+        let fields: Vec<&str> = row.split(',').collect();
+        assert!(fields[2].parse::<f64>().is_ok(), "{}", row);
+        assert!(fields[3].parse::<f64>().is_ok(), "{}", row);
+    }
+    let markdown = std::fs::read_to_string(output.join("report.md")).unwrap();
+    assert_eq!(String::from_utf8_lossy(&result.stdout), markdown);
+}
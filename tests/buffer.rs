@@ -0,0 +1,199 @@
+mod common;
+
+use rand::{Rng, SeedableRng};
+use wgpu_experiments::buffer::{DynamicBuffer, UniformArena};
+use wgpu_experiments::context::RenderContext;
+
+/// Copies `buffer`'s elements back from the GPU. It needs `COPY_SRC` usage.
+fn read_buffer<T: bytemuck::Pod>(context: &RenderContext, buffer: &wgpu::Buffer) -> Vec<T> {
+    let size = buffer.size();
+    let staging = context.device.create_buffer(&wgpu::BufferDescriptor {
+        label: None,
+        size,
+        usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+    let mut command_encoder = context
+        .device
+        .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+    command_encoder.copy_buffer_to_buffer(buffer, 0, &staging, 0, size);
+    context.queue.submit([command_encoder.finish()]);
+    let slice = staging.slice(..);
+    slice.map_async(wgpu::MapMode::Read, |result| result.unwrap());
+    context.device.poll(wgpu::Maintain::Wait);
+    let bytes = slice.get_mapped_range();
+    bytes
+        .chunks_exact(std::mem::size_of::<T>())
+        .map(bytemuck::pod_read_unaligned)
+        .collect()
+}
+
+/// The elements of the last write.
+fn read_back<T: bytemuck::Pod>(context: &RenderContext, buffer: &DynamicBuffer<T>) -> Vec<T> {
+    let mut elements = read_buffer(context, buffer.buffer());
+    elements.truncate(buffer.len());
+    elements
+}
+
+#[test]
+fn uploads_of_varying_sizes_read_back() {
+    if !common::has_adapter() {
+        eprintln!("Skipping, no adapter");
+        return;
+    }
+    let context = RenderContext::offscreen((1, 1));
+    let mut rng = rand::rngs::StdRng::seed_from_u64(19);
+    // u16, like indices, so odd lengths aren't a multiple of 4 bytes.
+    let mut buffer = DynamicBuffer::<u16>::new(
+        &context.device,
+        "stress",
+        wgpu::BufferUsages::INDEX | wgpu::BufferUsages::COPY_SRC,
+        4,
+    );
+    let mut replaced = 0;
+    let mut max_len = 0;
+    for _ in 0..200 {
+        let len = rng.gen_range(0..5000);
+        let data: Vec<u16> = (0..len).map(|_| rng.gen()).collect();
+        let capacity = buffer.capacity();
+        if buffer.write(&context.device, &context.queue, &data) {
+            replaced += 1;
+            assert!(len > capacity);
+            assert_eq!(buffer.capacity(), len.next_power_of_two());
+        } else {
+            assert_eq!(buffer.capacity(), capacity);
+        }
+        max_len = max_len.max(len);
+        assert!(buffer.capacity() >= max_len);
+        assert_eq!(buffer.len(), len);
+        assert_eq!(read_back(&context, &buffer), data);
+    }
+    // Growing to powers of two replaces the buffer at most once per power of two.
+    assert!(replaced <= 13, "replaced {} times", replaced);
+}
+
+#[test]
+fn grows_to_powers_of_two_and_never_shrinks() {
+    if !common::has_adapter() {
+        eprintln!("Skipping, no adapter");
+        return;
+    }
+    let context = RenderContext::offscreen((1, 1));
+    let mut buffer =
+        DynamicBuffer::<glam::Vec2>::new(&context.device, "grow", wgpu::BufferUsages::VERTEX, 0);
+    assert!(buffer.is_empty());
+    assert_eq!(buffer.capacity(), 1);
+    let grown = buffer.write(
+        &context.device,
+        &context.queue,
+        &[glam::Vec2::ZERO, glam::Vec2::ONE, glam::Vec2::X],
+    );
+    assert!(grown);
+    assert_eq!(buffer.capacity(), 4);
+    assert_eq!(buffer.buffer().size(), 32);
+    assert!(!buffer.write(&context.device, &context.queue, &[glam::Vec2::Y]));
+    assert_eq!(buffer.len(), 1);
+    assert_eq!(buffer.capacity(), 4);
+}
+
+#[derive(wgpu_experiments::wgsl::WgslLayout)]
+struct Object {
+    index: u32,
+    value: f32,
+}
+
+#[test]
+fn arena_offsets_are_aligned() {
+    if !common::has_adapter() {
+        eprintln!("Skipping, no adapter");
+        return;
+    }
+    let context = RenderContext::offscreen((1, 1));
+    let alignment = context.device.limits().min_uniform_buffer_offset_alignment;
+    let mut arena = UniformArena::<Object>::new(&context.device, "arena", 0);
+    assert_eq!(arena.stride(), alignment as u64);
+    let offsets: Vec<u32> = (0..3)
+        .map(|index| arena.push(&Object { index, value: 0.0 }))
+        .collect();
+    assert_eq!(offsets, [0, alignment, 2 * alignment]);
+    assert_eq!(arena.len(), 3);
+    assert!(arena.write(&context.device, &context.queue));
+    arena.clear();
+    assert!(arena.is_empty());
+    assert_eq!(
+        arena.push(&Object {
+            index: 0,
+            value: 0.0
+        }),
+        0
+    );
+    assert!(!arena.write(&context.device, &context.queue));
+}
+
+/// Copies each object's value to `values[index]`, one dispatch per dynamic offset.
+#[test]
+fn arena_objects_are_bound_by_offset() {
+    if !common::has_adapter() {
+        eprintln!("Skipping, no adapter");
+        return;
+    }
+    let context = RenderContext::offscreen((1, 1));
+    let device = &context.device;
+    let source = wgpu_experiments::wgsl::Prelude::new()
+        .with::<Object>()
+        .to_wgsl()
+        + "@group(0) @binding(0) var<uniform> object: Object;
+        @group(0) @binding(1) var<storage, read_write> values: array<f32>;
+        @compute @workgroup_size(1) fn main() { values[object.index] = object.value; }";
+    let shader_layout = wgpu_experiments::reflect::ShaderLayout::reflect(&source)
+        .unwrap()
+        .dynamic_offset(0, 0);
+    let layout = wgpu_experiments::reflect::PipelineLayout::new(device, "arena", shader_layout);
+    let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+        label: None,
+        layout: Some(layout.layout()),
+        module: &device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: None,
+            source: wgpu::ShaderSource::Wgsl(source.into()),
+        }),
+        entry_point: "main",
+    });
+    let mut arena = UniformArena::new(device, "arena", 1);
+    let expected = [3.0, 1.5, -2.0, 8.0, 0.25];
+    let offsets: Vec<u32> = expected
+        .iter()
+        .enumerate()
+        .map(|(index, &value)| {
+            arena.push(&Object {
+                index: index as u32,
+                value,
+            })
+        })
+        .collect();
+    arena.write(device, &context.queue);
+    let values = device.create_buffer(&wgpu::BufferDescriptor {
+        label: None,
+        size: std::mem::size_of_val(&expected) as u64,
+        usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+        mapped_at_creation: false,
+    });
+    let bind_group = layout
+        .bind_group(0)
+        .buffer_binding(0, arena.binding())
+        .buffer(1, &values)
+        .create(device)
+        .unwrap();
+    let mut command_encoder =
+        device.create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+    {
+        let mut compute_pass =
+            command_encoder.begin_compute_pass(&wgpu::ComputePassDescriptor::default());
+        compute_pass.set_pipeline(&pipeline);
+        for &offset in &offsets {
+            compute_pass.set_bind_group(0, &bind_group, &[offset]);
+            compute_pass.dispatch_workgroups(1, 1, 1);
+        }
+    }
+    context.queue.submit([command_encoder.finish()]);
+    assert_eq!(read_buffer::<f32>(&context, &values), expected);
+}
@@ -0,0 +1,115 @@
+use glam::Vec2;
+use wgpu_experiments::camera::{Camera2D, CameraUniform};
+use wgpu_experiments::wgsl::WgslLayout;
+
+fn assert_near(actual: Vec2, expected: Vec2) {
+    assert!(
+        actual.abs_diff_eq(expected, 1e-3),
+        "{} is not near {}",
+        actual,
+        expected
+    );
+}
+
+/// Off center, zoomed, turned, and in a wide window, so every part of the transform matters.
+fn camera() -> Camera2D {
+    let mut camera = Camera2D::new((800, 600));
+    camera.position = Vec2::new(3.0, -2.0);
+    camera.zoom = 40.0;
+    camera.rotation = 0.5;
+    camera
+}
+
+#[test]
+fn the_viewport_center_is_the_camera_position() {
+    let camera = camera();
+    assert_near(
+        camera.world_to_screen(camera.position),
+        Vec2::new(400.0, 300.0),
+    );
+    assert_near(
+        camera.screen_to_world(Vec2::new(400.0, 300.0)),
+        camera.position,
+    );
+}
+
+#[test]
+fn world_y_is_up_and_units_are_square() {
+    let mut camera = Camera2D::new((800, 600));
+    camera.zoom = 10.0;
+    assert_near(
+        camera.world_to_screen(Vec2::new(0.0, 1.0)),
+        Vec2::new(400.0, 290.0),
+    );
+    assert_near(
+        camera.world_to_screen(Vec2::new(1.0, 0.0)),
+        Vec2::new(410.0, 300.0),
+    );
+    assert_near(camera.screen_to_world(Vec2::ZERO), Vec2::new(-40.0, 30.0));
+    assert_near(
+        camera.screen_to_world(Vec2::new(800.0, 600.0)),
+        Vec2::new(40.0, -30.0),
+    );
+}
+
+#[test]
+fn rotating_the_camera_turns_the_world_the_other_way() {
+    let mut camera = Camera2D::new((100, 100));
+    camera.rotation = std::f32::consts::FRAC_PI_2;
+    // A point to the camera's right is, in the world, straight up.
+    assert_near(
+        camera.world_to_screen(Vec2::new(0.0, 10.0)),
+        Vec2::new(60.0, 50.0),
+    );
+}
+
+#[test]
+fn screen_to_world_undoes_world_to_screen() {
+    let camera = camera();
+    for world in [Vec2::ZERO, Vec2::new(7.5, 1.25), Vec2::new(-100.0, 42.0)] {
+        assert_near(camera.screen_to_world(camera.world_to_screen(world)), world);
+    }
+}
+
+#[test]
+fn view_projection_matches_world_to_screen() {
+    let camera = camera();
+    let world = Vec2::new(5.0, 1.0);
+    let clip = camera
+        .uniform()
+        .view_projection
+        .project_point3(world.extend(0.0))
+        .truncate();
+    let screen = (clip * Vec2::new(0.5, -0.5) + 0.5) * camera.viewport_size();
+    assert_near(screen, camera.world_to_screen(world));
+    assert_eq!(CameraUniform::SIZE, 64);
+}
+
+#[test]
+fn panning_keeps_the_world_under_the_cursor() {
+    let mut camera = camera();
+    let cursor = Vec2::new(120.0, 450.0);
+    let grabbed = camera.screen_to_world(cursor);
+    let delta = Vec2::new(35.0, -80.0);
+    camera.pan(delta);
+    assert_near(camera.screen_to_world(cursor + delta), grabbed);
+}
+
+#[test]
+fn zooming_keeps_the_world_under_the_cursor() {
+    let mut camera = camera();
+    let cursor = Vec2::new(700.0, 100.0);
+    let under_cursor = camera.screen_to_world(cursor);
+    camera.zoom_at(cursor, 2.5);
+    assert_eq!(camera.zoom, 100.0);
+    assert_near(camera.screen_to_world(cursor), under_cursor);
+    assert_near(camera.world_to_screen(under_cursor), cursor);
+}
+
+#[test]
+fn minimized_windows_dont_divide_by_zero() {
+    let mut camera = camera();
+    camera.resize((0, 0));
+    assert!(camera.view_projection().is_finite());
+    assert!(camera.screen_to_world(Vec2::ZERO).is_finite());
+}
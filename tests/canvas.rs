@@ -0,0 +1,65 @@
+use glam::Vec2;
+use wgpu_experiments::canvas::{CanvasLayout, VirtualCanvas};
+
+#[test]
+fn integer_scale_with_pillarbox() {
+    // 320x180 fits 4 times into 1920x1080 exactly; a wider window gets bars on the sides.
+    let layout = CanvasLayout::new((320, 180), (2000, 1080));
+    assert_eq!(layout.scale, 6.0);
+    assert_eq!(layout.scaled_size(), Vec2::new(1920.0, 1080.0));
+    assert_eq!(layout.viewport(), (40.0, 0.0, 1920.0, 1080.0));
+}
+
+#[test]
+fn integer_scale_with_letterbox() {
+    let layout = CanvasLayout::new((100, 100), (800, 650));
+    assert_eq!(layout.scale, 6.0);
+    assert_eq!(layout.viewport(), (100.0, 25.0, 600.0, 600.0));
+}
+
+#[test]
+fn scale_rounds_down() {
+    let layout = CanvasLayout::new((100, 100), (299, 1000));
+    assert_eq!(layout.scale, 2.0);
+    // Odd leftover space is rounded so canvas pixels stay aligned to window pixels.
+    assert_eq!(layout.offset, Vec2::new(49.0, 400.0));
+}
+
+#[test]
+fn window_smaller_than_canvas_shrinks() {
+    let layout = CanvasLayout::new((200, 100), (100, 100));
+    assert_eq!(layout.scale, 0.5);
+    assert_eq!(layout.viewport(), (0.0, 25.0, 100.0, 50.0));
+}
+
+#[test]
+fn window_to_canvas() {
+    let layout = CanvasLayout::new((100, 100), (800, 650));
+    assert_eq!(
+        layout.window_to_canvas(Vec2::new(100.0, 25.0)),
+        Some(Vec2::ZERO)
+    );
+    assert_eq!(
+        layout.window_to_canvas(Vec2::new(403.0, 331.0)),
+        Some(Vec2::new(50.5, 51.0))
+    );
+    // In the bars
+    assert_eq!(layout.window_to_canvas(Vec2::new(99.0, 300.0)), None);
+    assert_eq!(layout.window_to_canvas(Vec2::new(700.0, 300.0)), None);
+    assert_eq!(layout.window_to_canvas(Vec2::new(300.0, 10.0)), None);
+    let canvas_position = Vec2::new(12.0, 34.0);
+    assert_eq!(
+        layout.window_to_canvas(layout.canvas_to_window(canvas_position)),
+        Some(canvas_position)
+    );
+}
+
+#[test]
+fn resize_recomputes_layout() {
+    let mut canvas = VirtualCanvas::new((100, 100), wgpu::Color::BLACK, (100, 100));
+    assert_eq!(canvas.layout().scale, 1.0);
+    canvas.resize((450, 300));
+    assert_eq!(canvas.size(), (100, 100));
+    assert_eq!(canvas.layout().scale, 3.0);
+    assert_eq!(canvas.layout().viewport(), (75.0, 0.0, 300.0, 300.0));
+}
@@ -0,0 +1,76 @@
+//! Synthetic winit events for feeding input code without a window, command lines for the
+//! argument parsers, and a check for whether GPU tests can run.
+#![allow(dead_code)]
+
+use winit::event::{
+    ElementState, MouseButton, MouseScrollDelta, TouchPhase, VirtualKeyCode, WindowEvent,
+};
+
+/// A command line, as `std::env::args` would return it.
+pub fn args(args: &[&str]) -> Vec<String> {
+    args.iter().map(|arg| arg.to_string()).collect()
+}
+
+pub fn device_id() -> winit::event::DeviceId {
+    // SAFETY: The dummy id is only compared, never passed back to winit.
+    unsafe { winit::event::DeviceId::dummy() }
+}
+
+#[allow(deprecated)]
+pub fn key(key: VirtualKeyCode, state: ElementState) -> WindowEvent<'static> {
+    WindowEvent::KeyboardInput {
+        device_id: device_id(),
+        input: winit::event::KeyboardInput {
+            scancode: 0,
+            state,
+            virtual_keycode: Some(key),
+            modifiers: winit::event::ModifiersState::empty(),
+        },
+        is_synthetic: false,
+    }
+}
+
+#[allow(deprecated)]
+pub fn mouse(button: MouseButton, state: ElementState) -> WindowEvent<'static> {
+    WindowEvent::MouseInput {
+        device_id: device_id(),
+        state,
+        button,
+        modifiers: winit::event::ModifiersState::empty(),
+    }
+}
+
+#[allow(deprecated)]
+pub fn cursor(x: f64, y: f64) -> WindowEvent<'static> {
+    WindowEvent::CursorMoved {
+        device_id: device_id(),
+        position: winit::dpi::PhysicalPosition::new(x, y),
+        modifiers: winit::event::ModifiersState::empty(),
+    }
+}
+
+#[allow(deprecated)]
+pub fn wheel(delta: MouseScrollDelta) -> WindowEvent<'static> {
+    WindowEvent::MouseWheel {
+        device_id: device_id(),
+        delta,
+        phase: TouchPhase::Moved,
+        modifiers: winit::event::ModifiersState::empty(),
+    }
+}
+
+pub fn modifiers(modifiers: winit::event::ModifiersState) -> WindowEvent<'static> {
+    WindowEvent::ModifiersChanged(modifiers)
+}
+
+/// Tests that render skip themselves without an adapter, not even a software one.
+pub fn has_adapter() -> bool {
+    let instance = wgpu::Instance::default();
+    [false, true].into_iter().any(|force_fallback_adapter| {
+        pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions {
+            force_fallback_adapter,
+            ..Default::default()
+        }))
+        .is_some()
+    })
+}
@@ -0,0 +1,95 @@
+use std::time::Duration;
+use wgpu_experiments::frame_stats::{CsvReporter, FrameReport, FrameStats};
+
+fn ms(milliseconds: u64) -> Duration {
+    Duration::from_millis(milliseconds)
+}
+
+#[test]
+fn no_frames_no_report() {
+    assert_eq!(FrameStats::default().report(), None);
+}
+
+#[test]
+fn percentiles_of_1_to_100() {
+    let mut stats = FrameStats::new(100, 0.1);
+    // Out of order, to check we sort.
+    for i in (1..=100).rev() {
+        stats.record(ms(i));
+    }
+    let report = stats.report().unwrap();
+    assert_eq!(report.samples, 100);
+    assert_eq!(report.min, ms(1));
+    assert_eq!(report.max, ms(100));
+    assert_eq!(report.p50, ms(50));
+    assert_eq!(report.p95, ms(95));
+    assert_eq!(report.p99, ms(99));
+    // The slowest 1% of 100 frames is just the slowest frame.
+    assert_eq!(report.low_1_percent, ms(100));
+    assert_eq!(report.low_0_1_percent, ms(100));
+}
+
+#[test]
+fn lows_average_the_slowest_frames() {
+    let mut stats = FrameStats::new(1000, 0.1);
+    for _ in 0..990 {
+        stats.record(ms(10));
+    }
+    // A stutter of ten slow frames, one of them very slow.
+    for _ in 0..9 {
+        stats.record(ms(20));
+    }
+    stats.record(ms(110));
+    let report = stats.report().unwrap();
+    assert_eq!(report.p50, ms(10));
+    assert_eq!(report.p99, ms(10));
+    assert_eq!(report.low_1_percent, ms(29));
+    assert_eq!(report.low_0_1_percent, ms(110));
+    assert_eq!(FrameReport::fps(report.p50), 100.0);
+}
+
+#[test]
+fn ring_buffer_forgets_old_frames() {
+    let mut stats = FrameStats::new(3, 0.5);
+    for i in 1..=5 {
+        stats.record(ms(i));
+    }
+    assert_eq!(stats.total_frames(), 5);
+    assert_eq!(
+        stats.frame_times().collect::<Vec<_>>(),
+        [ms(3), ms(4), ms(5)]
+    );
+    let report = stats.report().unwrap();
+    assert_eq!(report.min, ms(3));
+    assert_eq!(report.max, ms(5));
+}
+
+#[test]
+fn ema_converges() {
+    let mut stats = FrameStats::new(10, 0.5);
+    stats.record(ms(10));
+    assert_eq!(stats.ema(), Some(ms(10)));
+    stats.record(ms(20));
+    assert_eq!(stats.ema(), Some(ms(15)));
+    for _ in 0..50 {
+        stats.record(ms(20));
+    }
+    let difference = stats.ema().unwrap().as_secs_f64() - 0.020;
+    assert!(difference.abs() < 1e-9);
+}
+
+#[test]
+fn csv_rows() {
+    let mut stats = FrameStats::new(10, 1.0);
+    stats.record(ms(16));
+    let mut reporter = CsvReporter::new(Vec::new()).unwrap();
+    reporter.write_row(&stats.report().unwrap()).unwrap();
+    let csv = String::from_utf8(reporter.into_inner()).unwrap();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines[0], FrameReport::CSV_HEADER);
+    assert_eq!(
+        lines[1],
+        "1,1,16.000,16.000,16.000,16.000,16.000,16.000,16.000,16.000"
+    );
+    assert_eq!(lines[0].split(',').count(), lines[1].split(',').count());
+}
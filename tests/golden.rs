@@ -0,0 +1,156 @@
+//! Renders each demo headless, and compares the last frame to `tests/golden/<demo>.png`.
+//!
+//! Run with `UPDATE_GOLDENS=1` to save the current frames as the new goldens instead.
+//! When a frame doesn't match, its actual, expected and diff images are written to
+//! `target/tmp/golden/`.
+
+mod common;
+
+use wgpu_experiments::image::Image;
+
+/// How much a channel may differ before the pixel counts as different.
+/// Rasterizers disagree slightly about gradients and edges.
+const TOLERANCE: u8 = 8;
+/// The fraction of pixels that may differ before the test fails.
+const MAX_DIFFERING_PIXELS: f64 = 0.005;
+const SIZE: &str = "200x150";
+const SEED: &str = "1";
+
+fn check(demo: &str, executable: &str, frames: u32) {
+    if !common::has_adapter() {
+        eprintln!("Skipping, no adapter");
+        return;
+    }
+    let out_dir = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("golden");
+    std::fs::create_dir_all(&out_dir).unwrap();
+    let actual_path = out_dir.join(format!("{}.actual.png", demo));
+    let output = std::process::Command::new(executable)
+        .args(["--headless", "--frames", &frames.to_string()])
+        .args(["--size", SIZE, "--seed", SEED, "--output"])
+        .arg(&actual_path)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{} failed:\n{}",
+        demo,
+        String::from_utf8_lossy(&output.stderr)
+    );
+    let actual = Image::load_png(&actual_path).unwrap();
+    let golden_path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden")
+        .join(format!("{}.png", demo));
+    if std::env::var_os("UPDATE_GOLDENS").is_some() {
+        actual.save_png(&golden_path).unwrap();
+        return;
+    }
+    let expected = Image::load_png(&golden_path).unwrap_or_else(|e| {
+        panic!(
+            "Can't load {}: {}. Run with UPDATE_GOLDENS=1 to create it.",
+            golden_path.display(),
+            e
+        )
+    });
+    let diff = actual.compare(&expected, TOLERANCE);
+    let max_differing_pixels =
+        (MAX_DIFFERING_PIXELS * (expected.width * expected.height) as f64) as usize;
+    if diff
+        .as_ref()
+        .is_some_and(|diff| diff.differing_pixels <= max_differing_pixels)
+    {
+        return;
+    }
+    expected
+        .save_png(out_dir.join(format!("{}.expected.png", demo)))
+        .unwrap();
+    match diff {
+        Some(diff) => {
+            let diff_path = out_dir.join(format!("{}.diff.png", demo));
+            diff.image.save_png(&diff_path).unwrap();
+            panic!(
+                "{} differs from its golden in {} pixels, at most {} are allowed. \
+                The largest channel difference is {}. See {}",
+                demo,
+                diff.differing_pixels,
+                max_differing_pixels,
+                diff.max_difference,
+                diff_path.display()
+            );
+        }
+        None => panic!(
+            "{} is {}x{}, but its golden is {}x{}",
+            demo, actual.width, actual.height, expected.width, expected.height
+        ),
+    }
+}
+
+#[test]
+fn camera() {
+    check("camera", env!("CARGO_BIN_EXE_camera"), 1);
+}
+
+#[test]
+fn fundamentals() {
+    check("fundamentals", env!("CARGO_BIN_EXE_fundamentals"), 1);
+}
+
+#[test]
+fn inter_stage_variables() {
+    check(
+        "inter_stage_variables",
+        env!("CARGO_BIN_EXE_inter_stage_variables"),
+        1,
+    );
+}
+
+#[test]
+fn uniforms() {
+    check("uniforms", env!("CARGO_BIN_EXE_uniforms"), 1);
+}
+
+#[test]
+fn storage() {
+    check("storage", env!("CARGO_BIN_EXE_storage"), 1);
+}
+
+#[test]
+fn vertex() {
+    check("vertex", env!("CARGO_BIN_EXE_vertex"), 1);
+}
+
+#[test]
+fn rotating_msaa_triangles() {
+    // 45 updates at 60 per second puts the triangles three quarters of a second in.
+    check(
+        "rotating_msaa_triangles",
+        env!("CARGO_BIN_EXE_rotating_msaa_triangles"),
+        45,
+    );
+}
+
+#[test]
+fn sprites() {
+    // A second in, so the sprites have drifted and spun from where they started.
+    check("sprites", env!("CARGO_BIN_EXE_sprites"), 60);
+}
+
+#[test]
+fn compare_counts_pixels_over_tolerance() {
+    let expected = Image::new(3, 1, [100, 100, 100, 255].repeat(3));
+    let actual = Image::new(
+        3,
+        1,
+        [
+            [100, 100, 100, 255],
+            [104, 100, 100, 255],
+            [100, 90, 100, 255],
+        ]
+        .concat(),
+    );
+    let diff = actual.compare(&expected, 4).unwrap();
+    assert_eq!(diff.differing_pixels, 1);
+    assert_eq!(diff.max_difference, 10);
+    assert_eq!(diff.image.pixel(2, 0), [255, 0, 0, 255]);
+    assert_ne!(diff.image.pixel(1, 0), [255, 0, 0, 255]);
+    assert!(actual.compare(&Image::new(1, 1, vec![0; 4]), 4).is_none());
+}
@@ -0,0 +1,137 @@
+mod common;
+
+use wgpu_experiments::context::RenderContext;
+use wgpu_experiments::game::{render_headless, ArgsError, Game, HeadlessOptions};
+use wgpu_experiments::image::Image;
+
+#[test]
+fn not_headless_without_flag() {
+    assert_eq!(
+        HeadlessOptions::from_args(common::args(&["demo", "--frames", "10"])),
+        Ok(None)
+    );
+}
+
+#[test]
+fn headless_defaults() {
+    let options = HeadlessOptions::from_args(common::args(&["target/debug/demo", "--headless"]))
+        .unwrap()
+        .unwrap();
+    assert_eq!(options.frames, 1);
+    assert_eq!(options.size, (800, 600));
+    assert_eq!(options.output, std::path::PathBuf::from("demo.png"));
+}
+
+#[test]
+fn headless_options() {
+    let options = HeadlessOptions::from_args(common::args(&[
+        "demo",
+        "--frames",
+        "30",
+        "--seed",
+        "7",
+        "--headless",
+        "--size",
+        "64x32",
+        "--output",
+        "out/frame.png",
+    ]))
+    .unwrap()
+    .unwrap();
+    assert_eq!(options.frames, 30);
+    assert_eq!(options.size, (64, 32));
+    assert_eq!(options.output, std::path::PathBuf::from("out/frame.png"));
+}
+
+#[test]
+fn bad_headless_options() {
+    assert_eq!(
+        HeadlessOptions::from_args(common::args(&["demo", "--headless", "--frames"])),
+        Err(ArgsError::MissingValue("--frames"))
+    );
+    for (flag, value) in [("--frames", "0"), ("--frames", "x"), ("--size", "64")] {
+        assert_eq!(
+            HeadlessOptions::from_args(common::args(&["demo", "--headless", flag, value])),
+            Err(ArgsError::InvalidValue {
+                flag,
+                value: value.to_string()
+            })
+        );
+    }
+}
+
+#[test]
+fn png_round_trip() {
+    let image = Image::new(2, 1, vec![255, 0, 0, 255, 0, 128, 255, 64]);
+    let path = std::env::temp_dir().join("wgpu_experiments_png_round_trip.png");
+    image.save_png(&path).unwrap();
+    assert_eq!(Image::load_png(&path).unwrap(), image);
+    std::fs::remove_file(path).unwrap();
+}
+
+/// Clears to a different color every update.
+struct Clear {
+    context: RenderContext,
+    updates: u32,
+}
+
+impl Game for Clear {
+    fn update(&mut self, _dt: std::time::Duration) {
+        self.updates += 1;
+    }
+
+    fn render(&mut self, _alpha: f32) {
+        let Some(frame) = self.context.acquire() else {
+            return;
+        };
+        let mut command_encoder = self
+            .context
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        command_encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: None,
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: frame.view(),
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color {
+                        r: 1.0,
+                        g: 0.0,
+                        b: self.updates as f64 / 4.0,
+                        a: 1.0,
+                    }),
+                    store: true,
+                },
+            })],
+            depth_stencil_attachment: None,
+        });
+        self.context.queue.submit([command_encoder.finish()]);
+        frame.present();
+    }
+
+    fn resize(&mut self, _new_size: winit::dpi::PhysicalSize<u32>) {}
+
+    fn render_context(&self) -> Option<&RenderContext> {
+        Some(&self.context)
+    }
+}
+
+#[test]
+fn render_headless_returns_last_frame() {
+    if !common::has_adapter() {
+        eprintln!("Skipping, no adapter");
+        return;
+    }
+    // An odd width, so rows need padding when they're read back.
+    let image = render_headless(
+        |context| Clear {
+            context,
+            updates: 0,
+        },
+        4,
+        (33, 7),
+    );
+    assert_eq!((image.width, image.height), (33, 7));
+    assert_eq!(image.pixel(0, 0), [255, 0, 255, 255]);
+    assert_eq!(image.pixel(32, 6), [255, 0, 255, 255]);
+}
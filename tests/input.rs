@@ -0,0 +1,99 @@
+use wgpu_experiments::input::InputState;
+use winit::event::{ElementState, MouseButton, MouseScrollDelta, VirtualKeyCode, WindowEvent};
+
+mod common;
+use common::{cursor, device_id, key, mouse, wheel};
+
+#[test]
+fn press_hold_release() {
+    let mut input = InputState::new();
+    input.handle_window_event(&key(VirtualKeyCode::A, ElementState::Pressed));
+    assert!(input.is_down(VirtualKeyCode::A));
+    assert!(input.just_pressed(VirtualKeyCode::A));
+    assert!(!input.just_released(VirtualKeyCode::A));
+
+    input.end_frame();
+    // Key repeat sends more Pressed events while the key is held.
+    input.handle_window_event(&key(VirtualKeyCode::A, ElementState::Pressed));
+    assert!(input.is_down(VirtualKeyCode::A));
+    assert!(!input.just_pressed(VirtualKeyCode::A));
+
+    input.end_frame();
+    input.handle_window_event(&key(VirtualKeyCode::A, ElementState::Released));
+    assert!(!input.is_down(VirtualKeyCode::A));
+    assert!(input.just_released(VirtualKeyCode::A));
+
+    input.end_frame();
+    assert!(!input.just_released(VirtualKeyCode::A));
+}
+
+#[test]
+fn tap_within_one_frame() {
+    let mut input = InputState::new();
+    input.handle_window_event(&key(VirtualKeyCode::Space, ElementState::Pressed));
+    input.handle_window_event(&key(VirtualKeyCode::Space, ElementState::Released));
+    assert!(!input.is_down(VirtualKeyCode::Space));
+    assert!(input.just_pressed(VirtualKeyCode::Space));
+    assert!(input.just_released(VirtualKeyCode::Space));
+}
+
+#[test]
+fn mouse_buttons() {
+    let mut input = InputState::new();
+    input.handle_window_event(&mouse(MouseButton::Left, ElementState::Pressed));
+    assert!(input.is_down(MouseButton::Left));
+    assert!(input.just_pressed(MouseButton::Left));
+    assert!(!input.is_down(MouseButton::Right));
+    input.end_frame();
+    input.handle_window_event(&mouse(MouseButton::Left, ElementState::Released));
+    assert!(input.just_released(MouseButton::Left));
+}
+
+#[test]
+fn cursor_and_scroll() {
+    let mut input = InputState::new();
+    assert_eq!(input.cursor_position(), None);
+    input.handle_window_event(&cursor(10.0, 20.0));
+    input.handle_window_event(&cursor(15.0, 18.0));
+    assert_eq!(input.cursor_position(), Some(glam::Vec2::new(15.0, 18.0)));
+    assert_eq!(input.cursor_delta(), glam::Vec2::new(5.0, -2.0));
+
+    input.handle_window_event(&wheel(MouseScrollDelta::LineDelta(0.0, 1.0)));
+    input.handle_window_event(&wheel(MouseScrollDelta::LineDelta(0.0, 2.0)));
+    input.handle_window_event(&wheel(MouseScrollDelta::PixelDelta(
+        winit::dpi::PhysicalPosition::new(
+            0.0,
+            wgpu_experiments::input::PIXELS_PER_SCROLL_LINE as f64,
+        ),
+    )));
+    assert_eq!(input.scroll_delta(), glam::Vec2::new(0.0, 4.0));
+
+    input.end_frame();
+    assert_eq!(input.cursor_delta(), glam::Vec2::ZERO);
+    assert_eq!(input.scroll_delta(), glam::Vec2::ZERO);
+    assert_eq!(input.cursor_position(), Some(glam::Vec2::new(15.0, 18.0)));
+
+    input.handle_window_event(&WindowEvent::CursorLeft {
+        device_id: device_id(),
+    });
+    assert_eq!(input.cursor_position(), None);
+}
+
+#[test]
+fn focus_loss_releases_everything() {
+    let mut input = InputState::new();
+    input.handle_window_event(&key(VirtualKeyCode::W, ElementState::Pressed));
+    input.handle_window_event(&mouse(MouseButton::Right, ElementState::Pressed));
+    input.end_frame();
+
+    input.handle_window_event(&WindowEvent::Focused(false));
+    assert!(!input.is_down(VirtualKeyCode::W));
+    assert!(!input.is_down(MouseButton::Right));
+    assert!(input.just_released(VirtualKeyCode::W));
+    assert!(input.just_released(MouseButton::Right));
+
+    // The release arrives after we've already let go; it shouldn't count twice.
+    input.end_frame();
+    input.handle_window_event(&key(VirtualKeyCode::W, ElementState::Released));
+    assert!(!input.just_released(VirtualKeyCode::W));
+}
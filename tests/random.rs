@@ -0,0 +1,24 @@
+mod common;
+
+use wgpu_experiments::game::ArgsError;
+use wgpu_experiments::random::seed_from_args;
+
+#[test]
+fn seed_option() {
+    assert_eq!(seed_from_args(common::args(&["demo"])), Ok(None));
+    assert_eq!(
+        seed_from_args(common::args(&["demo", "--headless", "--seed", "42"])),
+        Ok(Some(42))
+    );
+    assert_eq!(
+        seed_from_args(common::args(&["demo", "--seed"])),
+        Err(ArgsError::MissingValue("--seed"))
+    );
+    assert_eq!(
+        seed_from_args(common::args(&["demo", "--seed", "-1"])),
+        Err(ArgsError::InvalidValue {
+            flag: "--seed",
+            value: "-1".to_string()
+        })
+    );
+}
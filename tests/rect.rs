@@ -0,0 +1,58 @@
+mod common;
+
+use wgpu_experiments::context::RenderContext;
+use wgpu_experiments::rect::RectRenderer;
+
+const SIZE: (u32, u32) = (8, 4);
+const RED: [f32; 4] = [1.0, 0.0, 0.0, 1.0];
+const BLUE: [f32; 4] = [0.0, 0.0, 1.0, 1.0];
+
+#[test]
+fn grows_past_the_initial_capacity() {
+    if !common::has_adapter() {
+        eprintln!("Skipping, no adapter");
+        return;
+    }
+    let mut context = RenderContext::offscreen(SIZE);
+    let mut rect_renderer = RectRenderer::new(&context.device, context.format(), 1);
+    let initial_capacity = rect_renderer.instance_capacity();
+    // Red stripes down the whole output, then a blue square over the left half, each
+    // drawn many times over.
+    let count = initial_capacity + 100;
+    for i in 0..count {
+        let x = (i % SIZE.0 as usize) as f32;
+        rect_renderer.draw_rect(x, 0.0, 1.0, SIZE.1 as f32, RED);
+    }
+    for _ in 0..count {
+        rect_renderer.draw_rect(0.0, 0.0, 4.0, 4.0, BLUE);
+    }
+    rect_renderer.prepare(&context.device, &context.queue, SIZE);
+    assert!(rect_renderer.instance_capacity() >= count * 2);
+
+    let frame = context.acquire().unwrap();
+    let mut command_encoder = context
+        .device
+        .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+    {
+        let mut render_pass = command_encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: None,
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: frame.view(),
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                    store: true,
+                },
+            })],
+            depth_stencil_attachment: None,
+        });
+        rect_renderer.render(&mut render_pass);
+    }
+    context.queue.submit([command_encoder.finish()]);
+    frame.present();
+    let image = context.read_pixels().unwrap();
+    assert_eq!(image.pixel(0, 0), [0, 0, 255, 255]);
+    assert_eq!(image.pixel(3, 3), [0, 0, 255, 255]);
+    assert_eq!(image.pixel(4, 0), [255, 0, 0, 255]);
+    assert_eq!(image.pixel(7, 3), [255, 0, 0, 255]);
+}
@@ -0,0 +1,262 @@
+mod common;
+
+use wgpu_experiments::context::RenderContext;
+use wgpu_experiments::reflect::{BindingError, PipelineLayout, ShaderLayout};
+
+fn reflect(name: &str, prelude: &str) -> ShaderLayout {
+    let path = format!("{}/shaders/{}", env!("CARGO_MANIFEST_DIR"), name);
+    let source = std::fs::read_to_string(&path).unwrap();
+    ShaderLayout::reflect(&(prelude.to_string() + &source)).unwrap_or_else(|e| panic!("{}", e))
+}
+
+const OUR_STRUCT: &str = "struct OurStruct { color: vec3f, scale: vec2f, offset: vec2f };\n";
+
+fn buffer_type(entry: &wgpu::BindGroupLayoutEntry) -> (wgpu::BufferBindingType, u64) {
+    match entry.ty {
+        wgpu::BindingType::Buffer {
+            ty,
+            min_binding_size,
+            ..
+        } => (ty, min_binding_size.unwrap().get()),
+        ty => panic!("{:?} isn't a buffer", ty),
+    }
+}
+
+#[test]
+fn uniform_buffers_are_sized_by_their_type() {
+    let layout = reflect("uniforms.wgsl", OUR_STRUCT);
+    assert_eq!(layout.groups().len(), 1);
+    let entry = layout.entry(0, 0).unwrap();
+    assert_eq!(
+        entry.visibility,
+        wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT
+    );
+    assert_eq!(buffer_type(entry), (wgpu::BufferBindingType::Uniform, 32));
+}
+
+#[test]
+fn storage_buffers_are_read_only_unless_written() {
+    let layout = reflect("storage.wgsl", OUR_STRUCT);
+    let read_only = wgpu::BufferBindingType::Storage { read_only: true };
+    let our_structs = layout.entry(0, 0).unwrap();
+    assert_eq!(our_structs.visibility, wgpu::ShaderStages::VERTEX);
+    // A runtime sized array needs room for at least one element.
+    assert_eq!(buffer_type(our_structs), (read_only, 32));
+    assert_eq!(buffer_type(layout.entry(0, 1).unwrap()), (read_only, 8));
+
+    let layout = ShaderLayout::reflect(
+        "@group(0) @binding(0) var<storage, read_write> data: array<u32>;
+        @compute @workgroup_size(1) fn main() { data[0] = 1u; }",
+    )
+    .unwrap();
+    let entry = layout.entry(0, 0).unwrap();
+    assert_eq!(entry.visibility, wgpu::ShaderStages::COMPUTE);
+    assert_eq!(
+        buffer_type(entry).0,
+        wgpu::BufferBindingType::Storage { read_only: false }
+    );
+}
+
+#[test]
+fn textures_and_samplers() {
+    let layout = reflect("rotating_msaa_triangles.wgsl", "");
+    assert_eq!(
+        layout.groups()[0],
+        [
+            wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Texture {
+                    sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                    view_dimension: wgpu::TextureViewDimension::D2,
+                    multisampled: false,
+                },
+                count: None,
+            },
+            wgpu::BindGroupLayoutEntry {
+                binding: 1,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                count: None,
+            },
+        ]
+    );
+}
+
+#[test]
+fn skipped_groups_are_empty_and_unused_bindings_left_out() {
+    let layout = ShaderLayout::reflect(
+        "@group(0) @binding(0) var<uniform> unused: f32;
+        @group(2) @binding(3) var<uniform> used: vec4f;
+        @fragment fn main() -> @location(0) vec4f { return used; }",
+    )
+    .unwrap();
+    assert_eq!(layout.groups().len(), 3);
+    assert!(layout.groups()[0].is_empty());
+    assert!(layout.groups()[1].is_empty());
+    assert_eq!(
+        buffer_type(layout.entry(2, 3).unwrap()),
+        (wgpu::BufferBindingType::Uniform, 16)
+    );
+}
+
+#[test]
+fn invalid_shaders_are_errors() {
+    assert!(ShaderLayout::reflect("@group(0) @binding(0) var<uniform> x: f3;").is_err());
+}
+
+#[test]
+fn bind_groups_are_checked_against_the_layout() {
+    if !common::has_adapter() {
+        eprintln!("Skipping, no adapter");
+        return;
+    }
+    let context = RenderContext::offscreen((1, 1));
+    let device = &context.device;
+    let layout = PipelineLayout::from_wgsl(
+        device,
+        "test",
+        "@group(0) @binding(0) var<uniform> color: vec4f;
+        @group(0) @binding(1) var<storage> values: array<f32>;
+        @fragment fn main() -> @location(0) vec4f { return color * values[0]; }",
+    )
+    .unwrap();
+    let buffer = |size, usage| {
+        device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size,
+            usage,
+            mapped_at_creation: false,
+        })
+    };
+    let uniform = buffer(16, wgpu::BufferUsages::UNIFORM);
+    let small_uniform = buffer(8, wgpu::BufferUsages::UNIFORM);
+    let storage = buffer(64, wgpu::BufferUsages::STORAGE);
+    let sampler = device.create_sampler(&wgpu::SamplerDescriptor::default());
+
+    assert!(layout
+        .bind_group(0)
+        .buffer(0, &uniform)
+        .buffer(1, &storage)
+        .create(device)
+        .is_ok());
+    let error = |builder: wgpu_experiments::reflect::BindGroupBuilder| builder.create(device).err();
+    assert_eq!(
+        error(layout.bind_group(1)),
+        Some(BindingError::NoSuchGroup(1))
+    );
+    assert_eq!(
+        error(layout.bind_group(0).buffer(0, &uniform)),
+        Some(BindingError::Missing {
+            group: 0,
+            binding: 1
+        })
+    );
+    assert_eq!(
+        error(
+            layout
+                .bind_group(0)
+                .buffer(0, &uniform)
+                .buffer(1, &storage)
+                .buffer(2, &storage)
+        ),
+        Some(BindingError::NoSuchBinding {
+            group: 0,
+            binding: 2
+        })
+    );
+    assert_eq!(
+        error(layout.bind_group(0).buffer(0, &uniform).buffer(0, &uniform)),
+        Some(BindingError::Duplicate {
+            group: 0,
+            binding: 0
+        })
+    );
+    assert_eq!(
+        error(
+            layout
+                .bind_group(0)
+                .buffer(0, &small_uniform)
+                .buffer(1, &storage)
+        ),
+        Some(BindingError::TooSmall {
+            group: 0,
+            binding: 0,
+            size: 8,
+            min_size: 16
+        })
+    );
+    assert_eq!(
+        error(
+            layout
+                .bind_group(0)
+                .buffer_binding(
+                    0,
+                    wgpu::BufferBinding {
+                        buffer: &uniform,
+                        offset: 32,
+                        size: None,
+                    }
+                )
+                .buffer(1, &storage)
+        ),
+        Some(BindingError::OutOfRange {
+            group: 0,
+            binding: 0,
+            end: 32,
+            buffer_size: 16
+        })
+    );
+    // Starts inside the buffer, but runs past its end.
+    assert_eq!(
+        error(
+            layout
+                .bind_group(0)
+                .buffer_binding(
+                    0,
+                    wgpu::BufferBinding {
+                        buffer: &uniform,
+                        offset: 8,
+                        size: std::num::NonZeroU64::new(16),
+                    }
+                )
+                .buffer(1, &storage)
+        ),
+        Some(BindingError::OutOfRange {
+            group: 0,
+            binding: 0,
+            end: 24,
+            buffer_size: 16
+        })
+    );
+    assert_eq!(
+        error(layout.bind_group(0).buffer(0, &storage).buffer(1, &storage)),
+        Some(BindingError::MissingUsage {
+            group: 0,
+            binding: 0,
+            usage: wgpu::BufferUsages::UNIFORM
+        })
+    );
+    let Some(BindingError::WrongType { binding, found, .. }) = error(
+        layout
+            .bind_group(0)
+            .buffer(0, &uniform)
+            .sampler(1, &sampler),
+    ) else {
+        panic!("expected a WrongType error");
+    };
+    assert_eq!((binding, found), (1, "a sampler"));
+}
+
+#[test]
+fn dynamic_offsets_are_opted_into() {
+    let layout = reflect("uniforms.wgsl", OUR_STRUCT);
+    let dynamic = |layout: &ShaderLayout| match layout.entry(0, 0).unwrap().ty {
+        wgpu::BindingType::Buffer {
+            has_dynamic_offset, ..
+        } => has_dynamic_offset,
+        ty => panic!("{:?} isn't a buffer", ty),
+    };
+    assert!(!dynamic(&layout));
+    assert!(dynamic(&layout.dynamic_offset(0, 0)));
+}
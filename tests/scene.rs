@@ -0,0 +1,191 @@
+mod common;
+
+use wgpu_experiments::game::ArgsError;
+use wgpu_experiments::image::Image;
+use wgpu_experiments::scene::{Scene, SceneError, SceneObject, SceneOptions};
+
+#[test]
+fn options() {
+    assert_eq!(
+        SceneOptions::from_args(common::args(&["storage", "--seed", "3"])),
+        Ok(SceneOptions::default())
+    );
+    assert_eq!(
+        SceneOptions::from_args(common::args(&[
+            "storage",
+            "--scene",
+            "in.ron",
+            "--headless",
+            "--dump-scene",
+            "out.ron",
+        ])),
+        Ok(SceneOptions {
+            scene: Some("in.ron".into()),
+            dump: Some("out.ron".into()),
+        })
+    );
+    assert_eq!(
+        SceneOptions::from_args(common::args(&["storage", "--dump-scene"])),
+        Err(ArgsError::MissingValue("--dump-scene"))
+    );
+}
+
+#[test]
+fn round_trips_exactly() {
+    let scene = Scene {
+        objects: vec![
+            SceneObject {
+                color: glam::Vec3::new(0.1, 0.2, 0.3),
+                scale: glam::Vec2::new(1.0 / 3.0, 0.8249526),
+                offset: glam::Vec2::new(-0.1440506, f32::MIN_POSITIVE),
+            },
+            SceneObject {
+                color: glam::Vec3::ONE,
+                scale: glam::Vec2::ZERO,
+                offset: glam::Vec2::new(0.5, -1.0),
+            },
+        ],
+    };
+    let ron = scene.to_ron();
+    assert_eq!(ron.lines().filter(|line| line.contains("color")).count(), 2);
+    assert_eq!(ron.parse::<Scene>().unwrap(), scene);
+}
+
+#[test]
+fn parses_the_documented_example() {
+    let scene: Scene = "(
+        objects: [
+            (color: (1.0, 0.5, 0.0), scale: (0.5, 0.25), offset: (-0.5, 0.0)),
+            (color: (0.0, 0.5, 1.0), scale: (0.25, 0.5), offset: (0.5, 0.0)),
+        ],
+    )"
+    .parse()
+    .unwrap();
+    assert_eq!(scene.objects.len(), 2);
+    assert_eq!(scene.objects[1].scale, glam::Vec2::new(0.25, 0.5));
+}
+
+#[test]
+fn reports_where_parsing_failed() {
+    let error = "(objects: [(color: (1.0, 0.5), scale: (0.5, 0.25), offset: (0.0, 0.0))])"
+        .parse::<Scene>()
+        .unwrap_err();
+    assert!(matches!(error, SceneError::Parse(_)));
+    let message = error.to_string();
+    assert!(
+        message.starts_with("could not parse scene file: 1:"),
+        "{}",
+        message
+    );
+    assert!(matches!(
+        Scene::load("/nonexistent/scene.ron"),
+        Err(SceneError::Io(_))
+    ));
+}
+
+fn run(executable: &str, args: &[&std::ffi::OsStr]) {
+    let output = std::process::Command::new(executable)
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{} {:?} failed:\n{}",
+        executable,
+        args,
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+fn run_storage(args: &[&std::ffi::OsStr]) {
+    run(env!("CARGO_BIN_EXE_storage"), args);
+}
+
+/// A dumped random scene draws the same as the seed it came from.
+#[test]
+fn dumped_scene_draws_like_its_seed() {
+    if !common::has_adapter() {
+        eprintln!("Skipping, no adapter");
+        return;
+    }
+    let out_dir = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("scene");
+    std::fs::create_dir_all(&out_dir).unwrap();
+    let scene_path = out_dir.join("storage.ron");
+    let seeded_path = out_dir.join("seeded.png");
+    let loaded_path = out_dir.join("loaded.png");
+    let arg = std::ffi::OsStr::new;
+    let headless = [
+        arg("--headless"),
+        arg("--size"),
+        arg("200x150"),
+        arg("--output"),
+    ];
+    run_storage(&[
+        arg("--seed"),
+        arg("1"),
+        arg("--dump-scene"),
+        scene_path.as_os_str(),
+    ]);
+    run_storage(
+        &[
+            &[arg("--seed"), arg("1")],
+            &headless[..],
+            &[seeded_path.as_os_str()],
+        ]
+        .concat(),
+    );
+    run_storage(
+        &[
+            &[arg("--scene"), scene_path.as_os_str()],
+            &headless[..],
+            &[loaded_path.as_os_str()],
+        ]
+        .concat(),
+    );
+    assert_eq!(Scene::load(&scene_path).unwrap().objects.len(), 100);
+    assert_eq!(
+        Image::load_png(&loaded_path).unwrap(),
+        Image::load_png(&seeded_path).unwrap()
+    );
+}
+
+/// Scenes with no objects are valid, and draw just the background.
+#[test]
+fn empty_scene_draws_nothing() {
+    if !common::has_adapter() {
+        eprintln!("Skipping, no adapter");
+        return;
+    }
+    let out_dir = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("scene");
+    std::fs::create_dir_all(&out_dir).unwrap();
+    let scene_path = out_dir.join("empty.ron");
+    std::fs::write(&scene_path, "(objects: [])").unwrap();
+    assert_eq!(Scene::load(&scene_path).unwrap(), Scene::default());
+    let arg = std::ffi::OsStr::new;
+    for (demo, executable) in [
+        ("storage", env!("CARGO_BIN_EXE_storage")),
+        ("uniforms", env!("CARGO_BIN_EXE_uniforms")),
+        ("vertex", env!("CARGO_BIN_EXE_vertex")),
+    ] {
+        let image_path = out_dir.join(format!("empty_{}.png", demo));
+        run(
+            executable,
+            &[
+                arg("--scene"),
+                scene_path.as_os_str(),
+                arg("--headless"),
+                arg("--size"),
+                arg("8x4"),
+                arg("--output"),
+                image_path.as_os_str(),
+            ],
+        );
+        let image = Image::load_png(&image_path).unwrap();
+        let background = image.pixel(0, 0);
+        assert!(
+            image.pixels.chunks(4).all(|pixel| pixel == background),
+            "{} drew something",
+            demo
+        );
+    }
+}
@@ -0,0 +1,104 @@
+mod common;
+
+use wgpu_experiments::context::RenderContext;
+use wgpu_experiments::shader::{HotPipeline, ShaderFile, POLL_INTERVAL};
+
+const VALID: &str = "@compute @workgroup_size(1) fn main() {}";
+const ALSO_VALID: &str = "@compute @workgroup_size(2) fn main() {}";
+const INVALID: &str = "@compute @workgroup_size(1) fn main() { let x: f32 = true; }";
+
+/// A shader file in target/tmp/shader, with `source` on disk and `VALID` embedded.
+fn shader_file(name: &str, source: &str) -> ShaderFile {
+    let dir = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("shader");
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(name);
+    std::fs::write(&path, source).unwrap();
+    ShaderFile::new(Box::leak(path.to_str().unwrap().into()), VALID)
+}
+
+/// Waits out the poll interval, and bumps the mtime in case the file system is coarse.
+fn edit(path: &str, source: &str, edits: &mut u64) {
+    std::thread::sleep(POLL_INTERVAL);
+    std::fs::write(path, source).unwrap();
+    *edits += 1;
+    let modified = std::time::SystemTime::now() + std::time::Duration::from_secs(*edits);
+    std::fs::File::options()
+        .write(true)
+        .open(path)
+        .unwrap()
+        .set_modified(modified)
+        .unwrap();
+}
+
+#[test]
+fn source_is_read_from_disk() {
+    let mut shader = shader_file("source.wgsl", ALSO_VALID);
+    assert_eq!(shader.source(), ALSO_VALID);
+    assert!(!shader.changed());
+}
+
+#[test]
+fn missing_file_falls_back_to_embedded_source() {
+    let mut shader = ShaderFile::new("no/such/shader.wgsl", VALID);
+    assert_eq!(shader.source(), VALID);
+    assert!(!shader.changed());
+}
+
+#[test]
+fn changed_after_edit() {
+    let mut shader = shader_file("changed.wgsl", VALID);
+    shader.source();
+    assert!(!shader.changed());
+    let mut edits = 0;
+    edit(shader.path(), ALSO_VALID, &mut edits);
+    assert!(shader.changed());
+    assert_eq!(shader.source(), ALSO_VALID);
+    std::thread::sleep(POLL_INTERVAL);
+    assert!(!shader.changed());
+}
+
+#[test]
+fn invalid_edit_keeps_last_good_pipeline() {
+    if !common::has_adapter() {
+        eprintln!("Skipping, no adapter");
+        return;
+    }
+    let context = RenderContext::offscreen((1, 1));
+    let device = &context.device;
+    let builds = std::rc::Rc::new(std::cell::Cell::new(0));
+    let shader = shader_file("reload.wgsl", VALID);
+    let path = shader.path();
+    let mut pipeline = HotPipeline::new(device, shader, {
+        let builds = builds.clone();
+        move |device, shader_module| {
+            builds.set(builds.get() + 1);
+            device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: None,
+                layout: None,
+                module: shader_module,
+                entry_point: "main",
+            })
+        }
+    });
+    assert_eq!(builds.get(), 1);
+    assert!(!pipeline.reload_if_changed(device));
+
+    let mut edits = 0;
+    edit(path, INVALID, &mut edits);
+    assert!(!pipeline.reload_if_changed(device));
+    assert_eq!(
+        builds.get(),
+        1,
+        "an invalid shader must not reach the build function"
+    );
+
+    edit(path, ALSO_VALID, &mut edits);
+    assert!(pipeline.reload_if_changed(device));
+    assert_eq!(builds.get(), 2);
+}
+
+#[test]
+fn prelude_comes_before_the_source() {
+    let mut shader = shader_file("prelude.wgsl", VALID).with_prelude("const x = 1;\n".to_string());
+    assert_eq!(shader.source(), format!("const x = 1;\n{}", VALID));
+}
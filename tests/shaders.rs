@@ -0,0 +1,551 @@
+//! Parses and validates every shader in shaders/ with naga, so no GPU is needed, and checks the
+//! entry points, vertex inputs and struct layouts the Rust code expects of each.
+
+use naga::{ScalarKind, ShaderStage};
+use wgpu_experiments::demo::{self, camera, rotating_msaa_triangles};
+use wgpu_experiments::vertex::VertexLayout;
+use wgpu_experiments::wgsl::WgslLayout;
+
+struct EntryPoint {
+    name: &'static str,
+    stage: ShaderStage,
+    /// Only for vertex entry points.
+    vertex_buffers: &'static [wgpu::VertexBufferLayout<'static>],
+}
+
+fn vertex(name: &'static str, buffers: &'static [wgpu::VertexBufferLayout<'static>]) -> EntryPoint {
+    EntryPoint {
+        name,
+        stage: ShaderStage::Vertex,
+        vertex_buffers: buffers,
+    }
+}
+
+fn fragment(name: &'static str) -> EntryPoint {
+    EntryPoint {
+        name,
+        stage: ShaderStage::Fragment,
+        vertex_buffers: &[],
+    }
+}
+
+fn compute(name: &'static str) -> EntryPoint {
+    EntryPoint {
+        name,
+        stage: ShaderStage::Compute,
+        vertex_buffers: &[],
+    }
+}
+
+/// Every file in shaders/, and the entry points the Rust code that uses it asks for.
+fn expected() -> Vec<(&'static str, Vec<EntryPoint>)> {
+    vec![
+        (
+            "camera.wgsl",
+            vec![
+                vertex("vertex_main", camera::VERTEX_BUFFERS),
+                fragment("fragment_main"),
+            ],
+        ),
+        (
+            "fundamentals.wgsl",
+            vec![vertex("vertex_main", &[]), fragment("fragment_main")],
+        ),
+        ("fundamentals_compute.wgsl", vec![compute("compute_main")]),
+        (
+            "inter_stage_variables.wgsl",
+            vec![vertex("vertex_main", &[]), fragment("fragment_main")],
+        ),
+        (
+            "mipmap.wgsl",
+            vec![vertex("vertex_main", &[]), fragment("fragment_main")],
+        ),
+        (
+            "rect.wgsl",
+            vec![
+                vertex(
+                    "vertex_main",
+                    wgpu_experiments::rect::RectRenderer::VERTEX_BUFFERS,
+                ),
+                fragment("fragment_main"),
+            ],
+        ),
+        (
+            "rotating_msaa_triangles.wgsl",
+            vec![
+                vertex("vertex_main", &[rotating_msaa_triangles::Vertex::LAYOUT]),
+                fragment("fragment_main"),
+                vertex(
+                    "texture_to_texture_vertex_main",
+                    &[rotating_msaa_triangles::TextureVertex::LAYOUT],
+                ),
+                fragment("texture_to_texture_fragment_main"),
+            ],
+        ),
+        (
+            "sprite.wgsl",
+            vec![
+                vertex(
+                    "vertex_main",
+                    wgpu_experiments::sprite::SpriteBatch::VERTEX_BUFFERS,
+                ),
+                fragment("fragment_main"),
+            ],
+        ),
+        (
+            "storage.wgsl",
+            vec![vertex("vertex_main", &[]), fragment("fragment_main")],
+        ),
+        (
+            "uniforms.wgsl",
+            vec![vertex("vertex_main", &[]), fragment("fragment_main")],
+        ),
+        (
+            "vertex.wgsl",
+            vec![
+                vertex("vertex_main", demo::vertex::VERTEX_BUFFERS),
+                fragment("fragment_main"),
+            ],
+        ),
+    ]
+}
+
+fn shader_path(name: &str) -> std::path::PathBuf {
+    std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("shaders")
+        .join(name)
+}
+
+/// Generated WGSL the demos put before the shader, see `ShaderFile::with_prelude`.
+fn prelude(name: &str) -> String {
+    match name {
+        "camera.wgsl" => camera::shader_prelude(),
+        "sprite.wgsl" => wgpu_experiments::sprite::SpriteBatch::shader_prelude(),
+        "storage.wgsl" | "uniforms.wgsl" => demo::shader_prelude(),
+        _ => String::new(),
+    }
+}
+
+/// Parses and validates, with the prelude, returning the error rendered with its source spans.
+fn load(name: &str) -> Result<(String, naga::Module), String> {
+    let path = shader_path(name);
+    let path_str = path.to_string_lossy();
+    let source = std::fs::read_to_string(&path).map_err(|e| format!("{}: {}", path_str, e))?;
+    let source = prelude(name) + &source;
+    let module = naga::front::wgsl::parse_str(&source)
+        .map_err(|e| e.emit_to_string_with_path(&source, &path_str))?;
+    naga::valid::Validator::new(
+        naga::valid::ValidationFlags::all(),
+        naga::valid::Capabilities::default(),
+    )
+    .validate(&module)
+    .map_err(|e| e.emit_to_string_with_path(&source, &path_str))?;
+    Ok((source, module))
+}
+
+/// A `@location` input of a vertex entry point, either an argument or a struct member.
+struct VertexInput<'a> {
+    location: u32,
+    ty: &'a naga::TypeInner,
+    /// The argument's type, or the member's name.
+    span: naga::Span,
+}
+
+/// Where the member `name` of the struct at `struct_span` is declared. naga doesn't keep spans
+/// for members, so this looks for `name:` in the struct's source.
+fn member_span(source: &str, struct_span: naga::Span, name: &str) -> naga::Span {
+    let Some(range) = struct_span.to_range() else {
+        return struct_span;
+    };
+    let is_ident = |c: char| c.is_alphanumeric() || c == '_';
+    let declaration = source[range.clone()].match_indices(name).find(|&(i, _)| {
+        let before = source[range.start..range.start + i].chars().next_back();
+        let after = source[range.start + i + name.len()..].trim_start();
+        !before.is_some_and(is_ident) && after.starts_with(':')
+    });
+    match declaration {
+        Some((i, _)) => {
+            let start = (range.start + i) as u32;
+            naga::Span::new(start, start + name.len() as u32)
+        }
+        None => struct_span,
+    }
+}
+
+fn vertex_inputs<'a>(
+    source: &str,
+    module: &'a naga::Module,
+    function: &naga::Function,
+) -> Vec<VertexInput<'a>> {
+    let mut inputs = Vec::new();
+    for argument in &function.arguments {
+        let ty = &module.types[argument.ty];
+        let span = module.types.get_span(argument.ty);
+        match (&argument.binding, &ty.inner) {
+            (Some(naga::Binding::Location { location, .. }), inner) => inputs.push(VertexInput {
+                location: *location,
+                ty: inner,
+                span,
+            }),
+            (None, naga::TypeInner::Struct { members, .. }) => {
+                for member in members {
+                    if let Some(naga::Binding::Location { location, .. }) = member.binding {
+                        inputs.push(VertexInput {
+                            location,
+                            ty: &module.types[member.ty].inner,
+                            span: member
+                                .name
+                                .as_deref()
+                                .map_or(span, |name| member_span(source, span, name)),
+                        });
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    inputs
+}
+
+/// The scalar kind and component count a vertex format reaches the shader as.
+fn format_shader_type(format: wgpu::VertexFormat) -> (ScalarKind, u32) {
+    use wgpu::VertexFormat as F;
+    match format {
+        F::Uint32 => (ScalarKind::Uint, 1),
+        F::Uint8x2 | F::Uint16x2 | F::Uint32x2 => (ScalarKind::Uint, 2),
+        F::Uint32x3 => (ScalarKind::Uint, 3),
+        F::Uint8x4 | F::Uint16x4 | F::Uint32x4 => (ScalarKind::Uint, 4),
+        F::Sint32 => (ScalarKind::Sint, 1),
+        F::Sint8x2 | F::Sint16x2 | F::Sint32x2 => (ScalarKind::Sint, 2),
+        F::Sint32x3 => (ScalarKind::Sint, 3),
+        F::Sint8x4 | F::Sint16x4 | F::Sint32x4 => (ScalarKind::Sint, 4),
+        F::Float32 => (ScalarKind::Float, 1),
+        F::Unorm8x2 | F::Snorm8x2 | F::Unorm16x2 | F::Snorm16x2 | F::Float16x2 | F::Float32x2 => {
+            (ScalarKind::Float, 2)
+        }
+        F::Float32x3 => (ScalarKind::Float, 3),
+        F::Unorm8x4 | F::Snorm8x4 | F::Unorm16x4 | F::Snorm16x4 | F::Float16x4 | F::Float32x4 => {
+            (ScalarKind::Float, 4)
+        }
+        F::Float64 | F::Float64x2 | F::Float64x3 | F::Float64x4 => {
+            panic!(
+                "{:?} needs VERTEX_ATTRIBUTE_64BIT, which nothing here uses",
+                format
+            )
+        }
+    }
+}
+
+fn shader_type(ty: &naga::TypeInner) -> Option<(ScalarKind, u32)> {
+    match *ty {
+        naga::TypeInner::Scalar { kind, .. } => Some((kind, 1)),
+        naga::TypeInner::Vector { size, kind, .. } => Some((kind, size as u32)),
+        _ => None,
+    }
+}
+
+/// Every mismatch between a vertex entry point's inputs and the buffers the pipeline gives it.
+///
+/// wgpu fills in components the buffer doesn't have, but here that's more likely a mistake,
+/// so shader inputs must have the format's kind and at most its number of components.
+fn vertex_input_errors(
+    source: &str,
+    module: &naga::Module,
+    function: &naga::Function,
+    buffers: &[wgpu::VertexBufferLayout],
+) -> Vec<String> {
+    let mut errors = Vec::new();
+    let attributes: Vec<&wgpu::VertexAttribute> = buffers
+        .iter()
+        .flat_map(|buffer| buffer.attributes)
+        .collect();
+    for (i, attribute) in attributes.iter().enumerate() {
+        if attributes[..i]
+            .iter()
+            .any(|other| other.shader_location == attribute.shader_location)
+        {
+            errors.push(format!(
+                "location {} is in more than one VertexAttribute",
+                attribute.shader_location
+            ));
+        }
+    }
+    for input in vertex_inputs(source, module, function) {
+        let location = input.span.location(source);
+        let at = format!(
+            "location {} (declared at {}:{})",
+            input.location, location.line_number, location.line_position
+        );
+        let Some(attribute) = attributes
+            .iter()
+            .find(|attribute| attribute.shader_location == input.location)
+        else {
+            errors.push(format!("{at} has no VertexAttribute"));
+            continue;
+        };
+        let (kind, components) = format_shader_type(attribute.format);
+        match shader_type(input.ty) {
+            Some((input_kind, input_components))
+                if input_kind == kind && input_components <= components => {}
+            _ => errors.push(format!(
+                "{at} is {:?}, which a {:?} attribute can't provide",
+                input.ty, attribute.format
+            )),
+        }
+    }
+    errors
+}
+
+/// A Rust type's `WgslLayout`, to compare with naga's layout of the WGSL struct of that name.
+struct RustStruct {
+    name: &'static str,
+    align: u64,
+    size: u64,
+    members: &'static [wgpu_experiments::wgsl::Member],
+}
+
+fn rust_struct<T: WgslLayout>(name: &'static str) -> RustStruct {
+    RustStruct {
+        name,
+        align: T::ALIGN,
+        size: T::SIZE,
+        members: T::MEMBERS,
+    }
+}
+
+/// The shaders with structs that Rust code writes into buffers.
+fn expected_structs() -> Vec<(&'static str, RustStruct)> {
+    vec![
+        (
+            "camera.wgsl",
+            rust_struct::<wgpu_experiments::camera::CameraUniform>("CameraUniform"),
+        ),
+        (
+            "sprite.wgsl",
+            rust_struct::<wgpu_experiments::camera::CameraUniform>("CameraUniform"),
+        ),
+        ("storage.wgsl", rust_struct::<demo::OurStruct>("OurStruct")),
+        ("uniforms.wgsl", rust_struct::<demo::OurStruct>("OurStruct")),
+    ]
+}
+
+/// naga's `Alignment` only says whether numbers are aligned to it.
+fn alignment_value(alignment: naga::proc::Alignment) -> u64 {
+    (0..)
+        .map(|power| 1 << power)
+        .find(|n| alignment.is_aligned(*n))
+        .unwrap() as u64
+}
+
+/// Every difference between `expected` and the WGSL struct of the same name.
+fn struct_layout_errors(source: &str, module: &naga::Module, expected: &RustStruct) -> Vec<String> {
+    let Some((handle, ty)) = module
+        .types
+        .iter()
+        .find(|(_, ty)| ty.name.as_deref() == Some(expected.name))
+    else {
+        return vec![format!("no struct {}", expected.name)];
+    };
+    let naga::TypeInner::Struct { members, span } = &ty.inner else {
+        return vec![format!("{} isn't a struct", expected.name)];
+    };
+    let mut layouter = naga::proc::Layouter::default();
+    layouter.update(module.to_ctx()).unwrap();
+    let location = module.types.get_span(handle).location(source);
+    let at = format!(
+        "{} (declared at {}:{})",
+        expected.name, location.line_number, location.line_position
+    );
+    let mut errors = Vec::new();
+    let align = alignment_value(layouter[handle].alignment);
+    if align != expected.align {
+        errors.push(format!(
+            "{at} has alignment {align} in WGSL, {} in Rust",
+            expected.align
+        ));
+    }
+    if *span as u64 != expected.size {
+        errors.push(format!(
+            "{at} has size {span} in WGSL, {} in Rust",
+            expected.size
+        ));
+    }
+    let wgsl: Vec<(&str, u64)> = members
+        .iter()
+        .map(|member| (member.name.as_deref().unwrap_or(""), member.offset as u64))
+        .collect();
+    let rust: Vec<(&str, u64)> = expected
+        .members
+        .iter()
+        .map(|member| (member.name, member.offset))
+        .collect();
+    if wgsl != rust {
+        errors.push(format!(
+            "{at} has members and offsets {:?} in WGSL, {:?} in Rust",
+            wgsl, rust
+        ));
+    }
+    errors
+}
+
+#[test]
+fn every_shader_is_listed() {
+    let mut on_disk: Vec<String> = std::fs::read_dir(shader_path(""))
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .filter(|name| name.ends_with(".wgsl"))
+        .collect();
+    on_disk.sort();
+    let listed: Vec<&str> = expected().iter().map(|(name, _)| *name).collect();
+    assert_eq!(on_disk, listed, "add new shaders to `expected`");
+}
+
+#[test]
+fn shaders_are_valid() {
+    let errors: Vec<String> = expected()
+        .iter()
+        .filter_map(|(name, _)| load(name).err())
+        .collect();
+    assert!(errors.is_empty(), "\n{}", errors.join("\n"));
+}
+
+#[test]
+fn entry_points_match_the_rust_code() {
+    let mut errors = Vec::new();
+    for (name, entry_points) in expected() {
+        let Ok((source, module)) = load(name) else {
+            // shaders_are_valid reports these.
+            continue;
+        };
+        for expected in entry_points {
+            let Some(entry_point) = module
+                .entry_points
+                .iter()
+                .find(|entry_point| entry_point.name == expected.name)
+            else {
+                let names: Vec<&str> = module
+                    .entry_points
+                    .iter()
+                    .map(|entry_point| entry_point.name.as_str())
+                    .collect();
+                errors.push(format!(
+                    "{}: no entry point {}, only {:?}",
+                    name, expected.name, names
+                ));
+                continue;
+            };
+            if entry_point.stage != expected.stage {
+                errors.push(format!(
+                    "{}: {} is a {:?} entry point, expected {:?}",
+                    name, expected.name, entry_point.stage, expected.stage
+                ));
+                continue;
+            }
+            if expected.stage == ShaderStage::Vertex {
+                for error in vertex_input_errors(
+                    &source,
+                    &module,
+                    &entry_point.function,
+                    expected.vertex_buffers,
+                ) {
+                    errors.push(format!("{}: {}: {}", name, expected.name, error));
+                }
+            }
+        }
+    }
+    assert!(errors.is_empty(), "\n{}", errors.join("\n"));
+}
+
+#[test]
+fn invalid_shader_errors_point_at_the_source() {
+    let source = "@fragment\nfn main() -> @location(0) vec4f {\n    return vec3f(1.0);\n}\n";
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+    let error = naga::valid::Validator::new(
+        naga::valid::ValidationFlags::all(),
+        naga::valid::Capabilities::default(),
+    )
+    .validate(&module)
+    .unwrap_err()
+    .emit_to_string_with_path(source, "broken.wgsl");
+    assert!(error.contains("broken.wgsl:"), "{}", error);
+    assert!(error.contains("return vec3f(1.0);"), "{}", error);
+}
+
+#[test]
+fn vertex_inputs_are_checked_against_formats() {
+    let source = "struct In {\n    @location(0) position: vec2f,\n    @location(1) id: u32,\n};\n\
+        @vertex\nfn main(vertex: In) -> @builtin(position) vec4f {\n    \
+        return vec4f(vertex.position, 0.0, 1.0);\n}\n";
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+    let function = &module.entry_points[0].function;
+    let buffer = |attributes| wgpu::VertexBufferLayout {
+        array_stride: 0,
+        step_mode: wgpu::VertexStepMode::Vertex,
+        attributes,
+    };
+    let matching = wgpu::vertex_attr_array![0 => Float32x2, 1 => Uint32];
+    assert!(vertex_input_errors(source, &module, function, &[buffer(&matching)]).is_empty());
+
+    let wrong = wgpu::vertex_attr_array![0 => Float32, 1 => Float32];
+    let errors = vertex_input_errors(source, &module, function, &[buffer(&wrong)]);
+    assert_eq!(errors.len(), 2, "{:?}", errors);
+    assert!(
+        errors[0].starts_with("location 0 (declared at 2:18)"),
+        "{:?}",
+        errors
+    );
+
+    let missing = wgpu::vertex_attr_array![0 => Float32x2];
+    let errors = vertex_input_errors(source, &module, function, &[buffer(&missing)]);
+    assert_eq!(
+        errors,
+        ["location 1 (declared at 3:18) has no VertexAttribute"]
+    );
+}
+
+#[test]
+fn struct_layouts_match_the_rust_code() {
+    let mut errors = Vec::new();
+    for (name, expected) in expected_structs() {
+        let Ok((source, module)) = load(name) else {
+            continue;
+        };
+        for error in struct_layout_errors(&source, &module, &expected) {
+            errors.push(format!("{}: {}", name, error));
+        }
+    }
+    assert!(errors.is_empty(), "\n{}", errors.join("\n"));
+}
+
+#[test]
+fn struct_layout_differences_are_reported() {
+    let source = "struct Padded {\n    color: vec3f,\n    scale: vec2f,\n};\n";
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+    let members = &[
+        wgpu_experiments::wgsl::Member {
+            name: "color",
+            offset: 0,
+            align: 16,
+            size: 12,
+        },
+        wgpu_experiments::wgsl::Member {
+            name: "scale",
+            offset: 12,
+            align: 8,
+            size: 8,
+        },
+    ];
+    let packed = RustStruct {
+        name: "Padded",
+        align: 16,
+        size: 32,
+        members,
+    };
+    let errors = struct_layout_errors(source, &module, &packed);
+    assert_eq!(errors.len(), 1, "{:?}", errors);
+    assert!(
+        errors[0].starts_with("Padded (declared at 1:1) has members and offsets"),
+        "{:?}",
+        errors
+    );
+}
@@ -0,0 +1,299 @@
+mod common;
+
+use wgpu_experiments::camera::Camera2D;
+use wgpu_experiments::context::RenderContext;
+use wgpu_experiments::image::Image;
+use wgpu_experiments::sprite::{
+    AtlasError, AtlasRegion, Sprite, SpriteBatch, SpriteStats, TextureAtlas, TextureId, UvRect,
+};
+use wgpu_experiments::texture::{Texture, TextureOptions};
+
+const ATLAS: &str = r#"(
+    regions: [
+        (name: "player", x: 0, y: 0, width: 16, height: 16),
+        (x: 16, y: 0, width: 8, height: 8),
+        (name: "coin", x: 24, y: 0, width: 8, height: 8),
+    ],
+)"#;
+
+#[test]
+fn grid_numbers_cells_across_then_down() {
+    // The last 8 pixels of each row don't make a whole cell.
+    let atlas = TextureAtlas::grid((40, 20), (16, 10)).unwrap();
+    assert_eq!(atlas.len(), 4);
+    assert_eq!(
+        atlas.region(1),
+        &AtlasRegion {
+            name: String::new(),
+            x: 16,
+            y: 0,
+            width: 16,
+            height: 10,
+        }
+    );
+    assert_eq!(
+        atlas.uv_rect(2),
+        UvRect {
+            min: glam::Vec2::new(0.0, 0.5),
+            max: glam::Vec2::new(0.4, 1.0),
+        }
+    );
+}
+
+#[test]
+fn named_regions_from_ron() {
+    let atlas = TextureAtlas::from_ron(ATLAS, (32, 16)).unwrap();
+    assert_eq!(atlas.len(), 3);
+    assert_eq!(atlas.index("coin"), Some(2));
+    assert_eq!(atlas.index(""), None);
+    assert_eq!(atlas.named_uv_rect("missing"), None);
+    assert_eq!(
+        atlas.named_uv_rect("player"),
+        Some(UvRect {
+            min: glam::Vec2::ZERO,
+            max: glam::Vec2::new(0.5, 1.0),
+        })
+    );
+}
+
+#[test]
+fn bad_atlases_are_rejected() {
+    assert!(matches!(
+        TextureAtlas::from_ron(ATLAS, (28, 16)),
+        Err(AtlasError::OutOfBounds { index: 2, name }) if name == "coin"
+    ));
+    assert!(matches!(
+        TextureAtlas::from_ron("(regions: [(x: 0)])", (32, 16)),
+        Err(AtlasError::Parse(_))
+    ));
+    assert!(matches!(
+        TextureAtlas::load("missing.ron", (32, 16)),
+        Err(AtlasError::Io(_))
+    ));
+    // Far enough right that x + width overflows.
+    assert!(matches!(
+        TextureAtlas::from_ron(
+            "(regions: [(x: 4294967295, y: 0, width: 2, height: 1)])",
+            (32, 16)
+        ),
+        Err(AtlasError::OutOfBounds { index: 0, .. })
+    ));
+    assert!(matches!(
+        TextureAtlas::grid((32, 16), (0, 8)),
+        Err(AtlasError::EmptyCell)
+    ));
+}
+
+const SIZE: (u32, u32) = (8, 4);
+const RED: [u8; 4] = [255, 0, 0, 255];
+const WHITE: [u8; 4] = [255, 255, 255, 255];
+const BLUE: [u8; 4] = [0, 0, 255, 255];
+const BLACK: [u8; 4] = [0, 0, 0, 255];
+
+fn add_texture(
+    context: &RenderContext,
+    sprite_batch: &mut SpriteBatch,
+    image: &Image,
+) -> TextureId {
+    let texture = Texture::from_image(
+        &context.device,
+        &context.queue,
+        "test",
+        image,
+        &TextureOptions {
+            mipmaps: false,
+            filter: wgpu::FilterMode::Nearest,
+            ..Default::default()
+        },
+    );
+    sprite_batch.add_texture(&context.device, &texture)
+}
+
+/// A sprite covering the whole output, with the camera at 1 pixel per world unit.
+fn fullscreen() -> Sprite {
+    Sprite::new(
+        glam::Vec2::ZERO,
+        glam::Vec2::new(SIZE.0 as f32, SIZE.1 as f32),
+    )
+}
+
+/// Renders what's been drawn to `sprite_batch` and reads it back.
+fn render(context: &mut RenderContext, sprite_batch: &mut SpriteBatch) -> Image {
+    sprite_batch.prepare(
+        &context.device,
+        &context.queue,
+        &Camera2D::new(SIZE).uniform(),
+    );
+    let frame = context.acquire().unwrap();
+    let mut command_encoder = context
+        .device
+        .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+    {
+        let mut render_pass = command_encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: None,
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: frame.view(),
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                    store: true,
+                },
+            })],
+            depth_stencil_attachment: None,
+        });
+        sprite_batch.render(&mut render_pass);
+    }
+    context.queue.submit([command_encoder.finish()]);
+    frame.present();
+    context.read_pixels().unwrap()
+}
+
+#[test]
+fn draws_atlas_regions_flipped_and_tinted() {
+    if !common::has_adapter() {
+        eprintln!("Skipping, no adapter");
+        return;
+    }
+    let mut context = RenderContext::offscreen(SIZE);
+    let mut sprite_batch = SpriteBatch::new(&context.device, context.format(), 1);
+    let texture = add_texture(
+        &context,
+        &mut sprite_batch,
+        &Image::new(2, 1, [RED, WHITE].concat()),
+    );
+    let atlas = TextureAtlas::grid((2, 1), (1, 1)).unwrap();
+
+    sprite_batch.draw(texture, &fullscreen());
+    let image = render(&mut context, &mut sprite_batch);
+    assert_eq!((image.pixel(1, 1), image.pixel(6, 2)), (RED, WHITE));
+
+    sprite_batch.draw(
+        texture,
+        &Sprite {
+            flip_x: true,
+            ..fullscreen()
+        },
+    );
+    let image = render(&mut context, &mut sprite_batch);
+    assert_eq!((image.pixel(1, 1), image.pixel(6, 2)), (WHITE, RED));
+
+    sprite_batch.draw(
+        texture,
+        &Sprite {
+            uv_rect: atlas.uv_rect(1),
+            tint: glam::Vec4::new(0.0, 0.0, 1.0, 1.0),
+            ..fullscreen()
+        },
+    );
+    let image = render(&mut context, &mut sprite_batch);
+    assert_eq!((image.pixel(1, 1), image.pixel(6, 2)), (BLUE, BLUE));
+}
+
+#[test]
+fn rotates_around_the_origin() {
+    if !common::has_adapter() {
+        eprintln!("Skipping, no adapter");
+        return;
+    }
+    let mut context = RenderContext::offscreen(SIZE);
+    let mut sprite_batch = SpriteBatch::new(&context.device, context.format(), 1);
+    let texture = add_texture(
+        &context,
+        &mut sprite_batch,
+        &Image::new(1, 1, WHITE.to_vec()),
+    );
+    // A 2x2 square with its bottom left corner at the center, turned a quarter to the left, so
+    // it ends up left of the center rather than right.
+    sprite_batch.draw(
+        texture,
+        &Sprite {
+            origin: glam::Vec2::ZERO,
+            rotation: std::f32::consts::FRAC_PI_2,
+            ..Sprite::new(glam::Vec2::ZERO, glam::Vec2::splat(2.0))
+        },
+    );
+    let image = render(&mut context, &mut sprite_batch);
+    // The center is between pixels 3 and 4 across, 1 and 2 down.
+    assert_eq!((image.pixel(2, 0), image.pixel(3, 1)), (WHITE, WHITE));
+    assert_eq!((image.pixel(4, 1), image.pixel(3, 2)), (BLACK, BLACK));
+}
+
+#[test]
+fn higher_layers_draw_over_lower_ones() {
+    if !common::has_adapter() {
+        eprintln!("Skipping, no adapter");
+        return;
+    }
+    let mut context = RenderContext::offscreen(SIZE);
+    let mut sprite_batch = SpriteBatch::new(&context.device, context.format(), 1);
+    let red = add_texture(&context, &mut sprite_batch, &Image::new(1, 1, RED.to_vec()));
+    let blue = add_texture(
+        &context,
+        &mut sprite_batch,
+        &Image::new(1, 1, BLUE.to_vec()),
+    );
+    sprite_batch.draw(
+        blue,
+        &Sprite {
+            layer: 1,
+            ..fullscreen()
+        },
+    );
+    sprite_batch.draw(red, &fullscreen());
+    let image = render(&mut context, &mut sprite_batch);
+    assert_eq!(image.pixel(4, 2), BLUE);
+}
+
+#[test]
+fn batches_by_layer_then_texture() {
+    if !common::has_adapter() {
+        eprintln!("Skipping, no adapter");
+        return;
+    }
+    let context = RenderContext::offscreen(SIZE);
+    let mut sprite_batch = SpriteBatch::new(&context.device, context.format(), 1);
+    let red = add_texture(&context, &mut sprite_batch, &Image::new(1, 1, RED.to_vec()));
+    let blue = add_texture(
+        &context,
+        &mut sprite_batch,
+        &Image::new(1, 1, BLUE.to_vec()),
+    );
+    let camera = Camera2D::new(SIZE).uniform();
+    let mut prepare = |sprites: &[(TextureId, i32)]| {
+        for &(texture, layer) in sprites {
+            sprite_batch.draw(
+                texture,
+                &Sprite {
+                    layer,
+                    ..fullscreen()
+                },
+            );
+        }
+        sprite_batch.prepare(&context.device, &context.queue, &camera);
+        sprite_batch.stats()
+    };
+    assert_eq!(prepare(&[]), SpriteStats::default());
+    // Interleaved textures in one layer are sorted into one run each.
+    assert_eq!(
+        prepare(&[(red, 0), (blue, 0), (red, 0), (blue, 0)]),
+        SpriteStats {
+            sprites: 4,
+            draw_calls: 2,
+        }
+    );
+    assert_eq!(
+        prepare(&[(red, 1), (blue, 0), (red, 0), (blue, 1), (red, 1)]),
+        SpriteStats {
+            sprites: 5,
+            draw_calls: 4,
+        }
+    );
+    // Layers with only one texture between them still share a draw call.
+    assert_eq!(
+        prepare(&[(red, 2), (red, 0), (red, 1)]),
+        SpriteStats {
+            sprites: 3,
+            draw_calls: 1,
+        }
+    );
+}
@@ -0,0 +1,249 @@
+mod common;
+
+use wgpu_experiments::context::{read_texture, read_texture_level, RenderContext};
+use wgpu_experiments::image::{Image, ImageError};
+use wgpu_experiments::texture::{Texture, TextureOptions};
+
+/// 5 pixels wide, so rows are 20 bytes and need padding to upload.
+fn gradient() -> Image {
+    let (width, height) = (5, 3);
+    let pixels = (0..height)
+        .flat_map(|y| (0..width).flat_map(move |x| [x * 50, y * 100, 200, 255 - x * 10]))
+        .map(|channel| channel as u8)
+        .collect();
+    Image::new(width, height, pixels)
+}
+
+#[test]
+fn png_and_qoi_decode_the_same() {
+    let image = gradient();
+    let png_path = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("gradient.png");
+    image.save_png(&png_path).unwrap();
+    let qoi_bytes = qoi::encode_to_vec(&image.pixels, image.width, image.height).unwrap();
+    assert_eq!(Image::load(&png_path).unwrap(), image);
+    assert_eq!(Image::from_bytes(&qoi_bytes).unwrap(), image);
+}
+
+#[test]
+fn rgb_qoi_is_made_opaque() {
+    let qoi_bytes = qoi::encode_to_vec([10, 20, 30, 40, 50, 60], 2, 1).unwrap();
+    assert_eq!(
+        Image::from_qoi_bytes(&qoi_bytes).unwrap().pixels,
+        [10, 20, 30, 255, 40, 50, 60, 255]
+    );
+}
+
+fn encode_png(
+    width: u32,
+    color_type: png::ColorType,
+    bit_depth: png::BitDepth,
+    data: &[u8],
+    configure: impl FnOnce(&mut png::Encoder<&mut Vec<u8>>),
+) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    let mut encoder = png::Encoder::new(&mut bytes, width, 1);
+    encoder.set_color(color_type);
+    encoder.set_depth(bit_depth);
+    configure(&mut encoder);
+    encoder
+        .write_header()
+        .unwrap()
+        .write_image_data(data)
+        .unwrap();
+    bytes
+}
+
+#[test]
+fn palette_png_is_expanded() {
+    // Two bits per index, and only the first palette entry is transparent.
+    let bytes = encode_png(
+        3,
+        png::ColorType::Indexed,
+        png::BitDepth::Two,
+        &[0b00_01_10_00],
+        |encoder| {
+            encoder.set_palette(vec![255, 0, 0, 0, 255, 0, 0, 0, 255]);
+            encoder.set_trns(vec![128]);
+        },
+    );
+    assert_eq!(
+        Image::from_png_bytes(&bytes).unwrap().pixels,
+        [255, 0, 0, 128, 0, 255, 0, 255, 0, 0, 255, 255]
+    );
+}
+
+#[test]
+fn sixteen_bit_png_keeps_the_high_bytes() {
+    let bytes = encode_png(
+        1,
+        png::ColorType::Rgba,
+        png::BitDepth::Sixteen,
+        &[0x12, 0x34, 0x56, 0x78, 0x9a, 0xbc, 0xde, 0xf0],
+        |_| {},
+    );
+    assert_eq!(
+        Image::from_png_bytes(&bytes).unwrap().pixels,
+        [0x12, 0x56, 0x9a, 0xde]
+    );
+}
+
+#[test]
+fn grayscale_png_is_made_rgba() {
+    let gray = encode_png(
+        2,
+        png::ColorType::Grayscale,
+        png::BitDepth::Eight,
+        &[10, 20],
+        |_| {},
+    );
+    assert_eq!(
+        Image::from_png_bytes(&gray).unwrap().pixels,
+        [10, 10, 10, 255, 20, 20, 20, 255]
+    );
+    let gray_alpha = encode_png(
+        1,
+        png::ColorType::GrayscaleAlpha,
+        png::BitDepth::Eight,
+        &[30, 40],
+        |_| {},
+    );
+    assert_eq!(
+        Image::from_png_bytes(&gray_alpha).unwrap().pixels,
+        [30, 30, 30, 40]
+    );
+}
+
+#[test]
+fn unknown_formats_are_rejected() {
+    assert!(matches!(
+        Image::from_bytes(b"GIF89a"),
+        Err(ImageError::UnknownFormat)
+    ));
+    assert!(matches!(
+        Image::from_bytes(b"qoif"),
+        Err(ImageError::Qoi(_))
+    ));
+}
+
+fn readable(mipmaps: bool) -> TextureOptions {
+    TextureOptions {
+        mipmaps,
+        usage: wgpu::TextureUsages::COPY_SRC,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn uploads_unaligned_rows() {
+    if !common::has_adapter() {
+        eprintln!("Skipping, no adapter");
+        return;
+    }
+    let context = RenderContext::offscreen((1, 1));
+    let image = gradient();
+    let texture = Texture::from_image(
+        &context.device,
+        &context.queue,
+        "gradient",
+        &image,
+        &readable(false),
+    );
+    assert_eq!(texture.texture().mip_level_count(), 1);
+    assert_eq!(texture.texture().format(), Texture::FORMAT);
+    assert_eq!(
+        read_texture(&context.device, &context.queue, texture.texture()),
+        image
+    );
+}
+
+#[test]
+fn generates_every_mip_level() {
+    if !common::has_adapter() {
+        eprintln!("Skipping, no adapter");
+        return;
+    }
+    let context = RenderContext::offscreen((1, 1));
+    let texture = Texture::from_image(
+        &context.device,
+        &context.queue,
+        "gradient",
+        &gradient(),
+        &readable(true),
+    );
+    // 5x3, 2x1, 1x1
+    assert_eq!(texture.texture().mip_level_count(), 3);
+    let last = read_texture_level(&context.device, &context.queue, texture.texture(), 2);
+    assert_eq!((last.width, last.height), (1, 1));
+}
+
+#[test]
+fn mip_levels_average_in_linear_space() {
+    if !common::has_adapter() {
+        eprintln!("Skipping, no adapter");
+        return;
+    }
+    let context = RenderContext::offscreen((1, 1));
+    // A black and white checkerboard.
+    let pixels = (0..16)
+        .flat_map(|i| {
+            let value = if (i + i / 4) % 2 == 0 { 255 } else { 0 };
+            [value, value, value, 255]
+        })
+        .collect();
+    let texture = Texture::from_image(
+        &context.device,
+        &context.queue,
+        "checkerboard",
+        &Image::new(4, 4, pixels),
+        &readable(true),
+    );
+    for mip_level in 1..3 {
+        let level = read_texture_level(
+            &context.device,
+            &context.queue,
+            texture.texture(),
+            mip_level,
+        );
+        for pixel in level.pixels.chunks_exact(4) {
+            // Half way between black and white in linear light is 188 in sRGB, not 128.
+            assert!(
+                pixel[..3].iter().all(|channel| channel.abs_diff(188) <= 2),
+                "level {}: {:?}",
+                mip_level,
+                pixel
+            );
+            assert_eq!(pixel[3], 255);
+        }
+    }
+}
+
+#[test]
+fn loads_from_a_file() {
+    if !common::has_adapter() {
+        eprintln!("Skipping, no adapter");
+        return;
+    }
+    let context = RenderContext::offscreen((1, 1));
+    let image = gradient();
+    let path = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("gradient.qoi");
+    std::fs::write(
+        &path,
+        qoi::encode_to_vec(&image.pixels, image.width, image.height).unwrap(),
+    )
+    .unwrap();
+    let texture = Texture::load(&context.device, &context.queue, &path, &readable(true)).unwrap();
+    assert_eq!(texture.size(), (5, 3));
+    assert_eq!(
+        read_texture(&context.device, &context.queue, texture.texture()),
+        image
+    );
+    assert!(matches!(
+        Texture::load(
+            &context.device,
+            &context.queue,
+            path.with_extension("missing"),
+            &readable(true)
+        ),
+        Err(ImageError::Io(_))
+    ));
+}
@@ -0,0 +1,92 @@
+use std::time::Duration;
+use wgpu_experiments::game::Game;
+use wgpu_experiments::timestep::{FixedTimestep, TimestepConfig};
+
+#[derive(Default)]
+struct Counter {
+    position: f64,
+    updates: u32,
+    alphas: Vec<f32>,
+}
+
+impl Game for Counter {
+    fn update(&mut self, dt: Duration) {
+        self.position += dt.as_secs_f64();
+        self.updates += 1;
+    }
+
+    fn render(&mut self, alpha: f32) {
+        self.alphas.push(alpha);
+    }
+
+    fn resize(&mut self, _new_size: winit::dpi::PhysicalSize<u32>) {}
+}
+
+fn config(ticks_per_second: u32) -> TimestepConfig {
+    TimestepConfig {
+        ticks_per_second,
+        ..TimestepConfig::default()
+    }
+}
+
+#[test]
+fn steps_n_ticks_without_a_window() {
+    let mut timestep = FixedTimestep::new(config(100));
+    let mut game = Counter::default();
+    for _ in 0..1000 {
+        timestep.frame(&mut game, Duration::from_millis(10));
+    }
+    assert_eq!(game.updates, 1000);
+    assert_eq!(timestep.total_ticks(), 1000);
+    assert!((game.position - 10.0).abs() < 1e-9);
+    assert_eq!(game.alphas.len(), 1000);
+}
+
+#[test]
+fn simulation_is_independent_of_frame_rate() {
+    let mut fast = FixedTimestep::new(config(60));
+    let mut slow = FixedTimestep::new(config(60));
+    let mut fast_game = Counter::default();
+    let mut slow_game = Counter::default();
+    // Two seconds at 240 FPS and at 30 FPS.
+    for _ in 0..480 {
+        fast.frame(&mut fast_game, Duration::from_secs(1) / 240);
+    }
+    for _ in 0..60 {
+        slow.frame(&mut slow_game, Duration::from_secs(1) / 30);
+    }
+    assert!((fast_game.updates as i64 - 120).abs() <= 1);
+    assert!((slow_game.updates as i64 - 120).abs() <= 1);
+}
+
+#[test]
+fn alpha_is_the_fraction_of_a_tick_left_over() {
+    let mut timestep = FixedTimestep::new(config(10));
+    assert_eq!(timestep.advance(Duration::from_millis(250)), 2);
+    assert!((timestep.alpha() - 0.5).abs() < 1e-6);
+    assert_eq!(timestep.advance(Duration::from_millis(40)), 0);
+    assert!((timestep.alpha() - 0.9).abs() < 1e-6);
+}
+
+#[test]
+fn long_frames_do_not_spiral() {
+    let mut timestep = FixedTimestep::new(TimestepConfig {
+        ticks_per_second: 100,
+        max_steps_per_frame: 3,
+        max_frame_time: Duration::from_secs(10),
+    });
+    assert_eq!(timestep.advance(Duration::from_secs(5)), 3);
+    // The backlog was dropped rather than carried into the next frame.
+    assert!(timestep.alpha() < 1.0);
+    assert_eq!(timestep.advance(Duration::ZERO), 0);
+}
+
+#[test]
+fn frame_time_is_clamped() {
+    let mut timestep = FixedTimestep::new(TimestepConfig {
+        ticks_per_second: 100,
+        max_steps_per_frame: 1000,
+        max_frame_time: Duration::from_millis(50),
+    });
+    assert_eq!(timestep.advance(Duration::from_secs(3)), 5);
+}
@@ -0,0 +1,106 @@
+use wgpu_experiments::vertex::VertexLayout;
+
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Zeroable, bytemuck::Pod, VertexLayout)]
+struct Vertex {
+    position: glam::Vec3,
+    #[vertex(format = Unorm8x4)]
+    color: [u8; 4],
+    uv: glam::Vec2,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, bytemuck::Zeroable, bytemuck::Pod, VertexLayout)]
+#[vertex(step_mode = Instance)]
+struct Instance {
+    #[vertex(location = 5)]
+    offset: glam::Vec2,
+    layer: u32,
+    #[vertex(location = 9)]
+    tint: [f32; 4],
+    flags: [u16; 2],
+}
+
+fn attribute(format: wgpu::VertexFormat, offset: usize, location: u32) -> wgpu::VertexAttribute {
+    wgpu::VertexAttribute {
+        format,
+        offset: offset as u64,
+        shader_location: location,
+    }
+}
+
+#[test]
+fn offsets_match_the_struct() {
+    assert_eq!(
+        Vertex::ATTRIBUTES,
+        [
+            attribute(
+                wgpu::VertexFormat::Float32x3,
+                std::mem::offset_of!(Vertex, position),
+                0
+            ),
+            attribute(
+                wgpu::VertexFormat::Unorm8x4,
+                std::mem::offset_of!(Vertex, color),
+                1
+            ),
+            attribute(
+                wgpu::VertexFormat::Float32x2,
+                std::mem::offset_of!(Vertex, uv),
+                2
+            ),
+        ]
+    );
+    assert_eq!(std::mem::offset_of!(Vertex, color), 12);
+}
+
+#[test]
+fn layout_has_stride_and_step_mode() {
+    assert_eq!(
+        Vertex::LAYOUT,
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<Vertex>() as u64,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: Vertex::ATTRIBUTES,
+        }
+    );
+    assert_eq!(Instance::LAYOUT.step_mode, wgpu::VertexStepMode::Instance);
+    assert_eq!(Instance::LAYOUT.array_stride, 32);
+}
+
+#[test]
+fn locations_count_up_from_the_last_explicit_one() {
+    let locations: Vec<u32> = Instance::ATTRIBUTES
+        .iter()
+        .map(|attribute| attribute.shader_location)
+        .collect();
+    assert_eq!(locations, [5, 6, 9, 10]);
+    let formats: Vec<wgpu::VertexFormat> = Instance::ATTRIBUTES
+        .iter()
+        .map(|attribute| attribute.format)
+        .collect();
+    assert_eq!(
+        formats,
+        [
+            wgpu::VertexFormat::Float32x2,
+            wgpu::VertexFormat::Uint32,
+            wgpu::VertexFormat::Float32x4,
+            wgpu::VertexFormat::Uint16x2,
+        ]
+    );
+}
+
+#[test]
+fn matches_vertex_attr_array_for_packed_structs() {
+    #[repr(C)]
+    #[derive(Clone, Copy, VertexLayout)]
+    struct Packed {
+        a: glam::Vec2,
+        b: glam::Vec3,
+        c: f32,
+    }
+    assert_eq!(
+        Packed::ATTRIBUTES,
+        wgpu::vertex_attr_array![0 => Float32x2, 1 => Float32x3, 2 => Float32]
+    );
+}
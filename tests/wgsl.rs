@@ -0,0 +1,159 @@
+use wgpu_experiments::wgsl::{Member, Prelude, WgslLayout};
+
+#[derive(WgslLayout)]
+struct Transform {
+    color: glam::Vec3,
+    scale: glam::Vec2,
+    offset: glam::Vec2,
+}
+
+#[derive(WgslLayout)]
+struct Uniforms {
+    time: f32,
+    // A struct member of a uniform buffer must be 16 byte aligned.
+    #[wgsl(align = 16)]
+    transform: Transform,
+    #[wgsl(size = 16)]
+    frame: u32,
+    weights: [f32; 3],
+}
+
+fn member(name: &'static str, offset: u64, align: u64, size: u64) -> Member {
+    Member {
+        name,
+        offset,
+        align,
+        size,
+    }
+}
+
+fn floats(bytes: &[u8]) -> Vec<f32> {
+    bytes
+        .chunks_exact(4)
+        .map(|chunk| f32::from_le_bytes(chunk.try_into().unwrap()))
+        .collect()
+}
+
+#[test]
+fn vec3_is_padded_like_wgsl() {
+    assert_eq!(Transform::ALIGN, 16);
+    assert_eq!(Transform::SIZE, 32);
+    assert_eq!(
+        Transform::MEMBERS,
+        [
+            member("color", 0, 16, 12),
+            member("scale", 16, 8, 8),
+            member("offset", 24, 8, 8),
+        ]
+    );
+}
+
+#[test]
+fn bytes_have_zeroed_padding() {
+    let transform = Transform {
+        color: glam::Vec3::new(1.0, 2.0, 3.0),
+        scale: glam::Vec2::new(4.0, 5.0),
+        offset: glam::Vec2::new(6.0, 7.0),
+    };
+    assert_eq!(
+        floats(&transform.to_bytes()),
+        [1.0, 2.0, 3.0, 0.0, 4.0, 5.0, 6.0, 7.0]
+    );
+}
+
+#[test]
+fn align_and_size_overrides() {
+    assert_eq!(
+        Uniforms::MEMBERS,
+        [
+            member("time", 0, 4, 4),
+            member("transform", 16, 16, 32),
+            member("frame", 48, 4, 16),
+            member("weights", 64, 4, 12),
+        ]
+    );
+    assert_eq!(Uniforms::ALIGN, 16);
+    assert_eq!(Uniforms::SIZE, 80);
+    let uniforms = Uniforms {
+        time: 0.5,
+        transform: Transform {
+            color: glam::Vec3::ONE,
+            scale: glam::Vec2::ONE,
+            offset: glam::Vec2::ZERO,
+        },
+        frame: 0,
+        weights: [0.25, 0.5, 0.75],
+    };
+    let bytes = uniforms.to_bytes();
+    assert_eq!(bytes.len(), 80);
+    assert_eq!(floats(&bytes[..4]), [0.5]);
+    assert!(bytes[4..16].iter().all(|byte| *byte == 0));
+    assert_eq!(floats(&bytes[64..76]), [0.25, 0.5, 0.75]);
+}
+
+#[test]
+fn array_elements_are_padded_to_the_stride() {
+    assert_eq!(<[glam::Vec3; 2]>::SIZE, 32);
+    let bytes = wgpu_experiments::wgsl::slice_to_bytes(&[
+        glam::Vec3::new(1.0, 2.0, 3.0),
+        glam::Vec3::new(4.0, 5.0, 6.0),
+    ]);
+    assert_eq!(floats(&bytes), [1.0, 2.0, 3.0, 0.0, 4.0, 5.0, 6.0, 0.0]);
+}
+
+#[test]
+fn mat3_columns_are_padded() {
+    let bytes =
+        glam::Mat3::from_cols_array(&[1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0]).to_bytes();
+    assert_eq!(
+        floats(&bytes),
+        [1.0, 2.0, 3.0, 0.0, 4.0, 5.0, 6.0, 0.0, 7.0, 8.0, 9.0, 0.0]
+    );
+}
+
+#[test]
+fn prelude_declares_contained_structs_first_and_once() {
+    assert_eq!(
+        Prelude::new()
+            .with::<Uniforms>()
+            .with::<Transform>()
+            .to_wgsl(),
+        "// Generated from Rust types by wgpu_experiments::wgsl.
+
+struct Transform {
+    color: vec3f,
+    scale: vec2f,
+    offset: vec2f,
+};
+
+struct Uniforms {
+    time: f32,
+    @align(16) transform: Transform,
+    @size(16) frame: u32,
+    weights: array<f32, 3>,
+};
+
+"
+    );
+}
+
+#[test]
+fn naga_lays_out_the_prelude_like_rust() {
+    let wgsl = Prelude::new().with::<Uniforms>().to_wgsl();
+    let module = naga::front::wgsl::parse_str(&wgsl).unwrap();
+    let (_, ty) = module
+        .types
+        .iter()
+        .find(|(_, ty)| ty.name.as_deref() == Some("Uniforms"))
+        .unwrap();
+    let naga::TypeInner::Struct { members, span } = &ty.inner else {
+        panic!("{:?}", ty);
+    };
+    assert_eq!(*span as u64, Uniforms::SIZE);
+    let offsets: Vec<u64> = members.iter().map(|member| member.offset as u64).collect();
+    let expected: Vec<u64> = Uniforms::MEMBERS
+        .iter()
+        .map(|member| member.offset)
+        .collect();
+    assert_eq!(offsets, expected);
+}